@@ -0,0 +1,363 @@
+use serde::Serialize;
+
+use crate::analysis::*;
+use crate::engine::*;
+use crate::go_core::*;
+use crate::gtp::*;
+
+/// Whose point of view winrates, score leads and ownership are reported
+/// from, as KataGo's `reportAnalysisWinratesAs` setting.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Perspective {
+    Black,
+    White,
+    SideToMove,
+}
+
+impl Perspective {
+    /// Reads the setting's values, `BLACK`, `WHITE` or `SIDETOMOVE`.
+    pub fn from_name(name: &str) -> Option<Perspective> {
+        match name.to_uppercase().as_str() {
+            "BLACK" => Some(Perspective::Black),
+            "WHITE" => Some(Perspective::White),
+            "SIDETOMOVE" => Some(Perspective::SideToMove),
+            _ => None,
+        }
+    }
+
+    // whether numbers from `player`'s point of view have to be turned round
+    fn is_flipped(&self, player: CellState) -> bool {
+        match self {
+            Perspective::Black => player == CellState::White,
+            Perspective::White => player == CellState::Black,
+            Perspective::SideToMove => false,
+        }
+    }
+}
+
+/// Answers analysis queries with a fresh [`MctsPlayer`] search of each
+/// turn asked about. `maxVisits` becomes the search's playout budget; other
+/// queries search for as long as the engine's own configuration says.
+/// Queries are answered in the order they come, each one in full before
+/// the next is read, so there is nothing for `terminate` to stop.
+pub struct AnalysisEngine {
+    config: MctsConfig,
+    report_as: Perspective,
+}
+
+impl AnalysisEngine {
+    /// An engine searching with `config`, reporting from Black's point of
+    /// view as KataGo's example analysis configuration does.
+    pub fn new(config: MctsConfig) -> AnalysisEngine {
+        AnalysisEngine {
+            config: MctsConfig {
+                reuse_tree: false,
+                ..config
+            },
+            report_as: Perspective::Black,
+        }
+    }
+
+    pub fn set_report_as(&mut self, report_as: Perspective) {
+        self.report_as = report_as;
+    }
+
+    /// Handles a line of input, returning the JSON lines to write back,
+    /// without their newlines.
+    pub fn handle_line(&mut self, line: &str) -> Vec<String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Vec::new();
+        }
+        // the id is picked out first so that even a malformed query gets
+        // its error back under it
+        let value: serde_json::Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(e) => return vec![to_line(&get_error(None, e.to_string(), None))],
+        };
+        let id = value
+            .get("id")
+            .and_then(|id| id.as_str())
+            .map(str::to_string);
+        let query: Query = match serde_json::from_value(value) {
+            Ok(query) => query,
+            Err(e) => return vec![to_line(&get_error(id, e.to_string(), None))],
+        };
+        let action = match query.action.as_deref() {
+            None => {
+                return match self.analyze(&query) {
+                    Ok(responses) => responses.iter().map(to_line).collect(),
+                    Err(e) => vec![to_line(&e)],
+                };
+            }
+            Some(action) => action,
+        };
+        let mut response = ActionResponse {
+            id: query.id.clone(),
+            action: action.to_string(),
+            version: None,
+            terminate_id: None,
+        };
+        match action {
+            "query_version" => response.version = Some(env!("CARGO_PKG_VERSION").to_string()),
+            "terminate" => response.terminate_id = query.terminate_id.clone(),
+            "terminate_all" | "clear_cache" => {}
+            _ => {
+                let error = format!("unknown action '{}'", action);
+                return vec![to_line(&get_error(id, error, Some("action")))];
+            }
+        }
+        vec![to_line(&response)]
+    }
+
+    /// Analyses each of the query's turns, or says what is wrong with it.
+    pub fn analyze(&mut self, query: &Query) -> Result<Vec<Response>, ErrorResponse> {
+        let error =
+            |message: String, field: &str| get_error(Some(query.id.clone()), message, Some(field));
+
+        let size = query.board_x_size.unwrap_or(19);
+        if query.board_y_size.unwrap_or(size) != size {
+            return Err(error(
+                "only square boards are supported".to_string(),
+                "boardYSize",
+            ));
+        }
+        if !(2..=25).contains(&size) {
+            return Err(error(
+                format!("unsupported board size {}", size),
+                "boardXSize",
+            ));
+        }
+        let rules = match &query.rules {
+            Some(QueryRules::Name(name)) => get_rules(name)
+                .ok_or_else(|| error(format!("unknown rules '{}'", name), "rules"))?,
+            Some(QueryRules::Rules(rules)) => read_rules(rules).map_err(|e| error(e, "rules"))?,
+            None => return Err(error("the rules are missing".to_string(), "rules")),
+        };
+        let report_as = match query
+            .override_settings
+            .as_ref()
+            .and_then(|s| s.report_analysis_winrates_as.as_ref())
+        {
+            Some(name) => Perspective::from_name(name).ok_or_else(|| {
+                error(
+                    format!("unknown perspective '{}'", name),
+                    "overrideSettings",
+                )
+            })?,
+            None => self.report_as,
+        };
+
+        let mut stones = Vec::new();
+        for (colour, vertex) in &query.initial_stones {
+            match parse_move(colour, vertex, size) {
+                Ok((player, Some(p))) => stones.push((player, p)),
+                Ok((_, None)) => {
+                    return Err(error(
+                        "a setup stone can't be a pass".to_string(),
+                        "initialStones",
+                    ))
+                }
+                Err(e) => return Err(error(e, "initialStones")),
+            }
+        }
+        let mut moves = Vec::new();
+        for (colour, vertex) in &query.moves {
+            moves.push(parse_move(colour, vertex, size).map_err(|e| error(e, "moves"))?);
+        }
+        let first = match &query.initial_player {
+            Some(colour) => parse_colour(colour).map_err(|e| error(e, "initialPlayer"))?,
+            None => moves
+                .first()
+                .map_or(CellState::Black, |&(player, _)| player),
+        };
+        let mut board = Board::from_setup(size, rules, &stones, first)
+            .map_err(|e| error(e, "initialStones"))?;
+        if let Some(komi) = query.komi {
+            board.set_komi(komi);
+        }
+
+        let turns = query
+            .analyze_turns
+            .clone()
+            .unwrap_or_else(|| vec![moves.len()]);
+        if let Some(turn) = turns.iter().find(|&&turn| turn > moves.len()) {
+            let message = format!("turn {} is past the last move", turn);
+            return Err(error(message, "analyzeTurns"));
+        }
+        // every move is checked before anything is searched
+        let mut positions = vec![board.clone()];
+        for (i, &mv) in moves.iter().enumerate() {
+            play_move(&mut board, mv)
+                .map_err(|e| error(format!("move {}: {}", i + 1, e), "moves"))?;
+            positions.push(board.clone());
+        }
+
+        let mut config = self.config;
+        if let Some(visits) = query.max_visits {
+            config.budget = SearchBudget::Playouts(visits.max(1));
+        }
+        let responses = turns
+            .into_iter()
+            .map(|turn| {
+                let analysis = MctsPlayer::new(config).analyze(&positions[turn]);
+                let id = query.id.clone();
+                let ownership = query.include_ownership;
+                get_response(id, turn, &analysis, size, report_as, ownership)
+            })
+            .collect();
+        Ok(responses)
+    }
+}
+
+fn get_error(id: Option<String>, error: String, field: Option<&str>) -> ErrorResponse {
+    ErrorResponse {
+        id,
+        error,
+        field: field.map(str::to_string),
+    }
+}
+
+fn to_line<T: Serialize>(value: &T) -> String {
+    // the responses are plain structs of strings and numbers
+    serde_json::to_string(value).unwrap()
+}
+
+// KataGo's rule names, or any of ours
+fn get_rules(name: &str) -> Option<RuleSet> {
+    match name.to_lowercase().as_str() {
+        "korean" => Some(RuleSet::japanese()),
+        "chinese-ogs" | "chinese-kgs" => Some(RuleSet::chinese()),
+        "stone-scoring" => Some(RuleSet::tromp_taylor()),
+        name => RuleSet::from_name(name),
+    }
+}
+
+// KataGo's rules object, as far as the rule set can follow it
+fn read_rules(rules: &KataGoRules) -> Result<RuleSet, String> {
+    if rules.has_button {
+        return Err("button go isn't supported".to_string());
+    }
+    let ko = match rules.ko.to_uppercase().as_str() {
+        "SIMPLE" => KoRule::Simple,
+        "POSITIONAL" => KoRule::PositionalSuperko,
+        "SITUATIONAL" => KoRule::SituationalSuperko,
+        _ => return Err(format!("unknown ko rule '{}'", rules.ko)),
+    };
+    let scoring = match rules.scoring.to_uppercase().as_str() {
+        "AREA" => ScoringMethod::Area,
+        "TERRITORY" => ScoringMethod::Territory,
+        _ => return Err(format!("unknown scoring '{}'", rules.scoring)),
+    };
+    let handicap_compensation = match rules.white_handicap_bonus.as_deref() {
+        None | Some("0") => HandicapCompensation::None,
+        Some("N") => HandicapCompensation::Full,
+        Some("N-1") => HandicapCompensation::OneLess,
+        Some(bonus) => return Err(format!("unknown handicap bonus '{}'", bonus)),
+    };
+    Ok(RuleSet {
+        name: "Custom",
+        allow_suicide: rules.suicide,
+        ko,
+        scoring,
+        pass_stones: false,
+        handicap_compensation,
+        ..RuleSet::tromp_taylor()
+    })
+}
+
+fn parse_colour(colour: &str) -> Result<CellState, String> {
+    match colour.to_uppercase().as_str() {
+        "B" | "BLACK" => Ok(CellState::Black),
+        "W" | "WHITE" => Ok(CellState::White),
+        _ => Err(format!("invalid colour '{}'", colour)),
+    }
+}
+
+fn parse_move(
+    colour: &str,
+    vertex: &str,
+    size: usize,
+) -> Result<(CellState, Option<Point>), String> {
+    Ok((parse_colour(colour)?, gtp_to_point(vertex, size)?))
+}
+
+// plays a move for `player` whoever's turn it is, since the moves of a
+// query don't have to alternate; the turn is handed over without a pass,
+// which would give a pass stone away or end the game
+fn play_move(board: &mut Board, (player, mv): (CellState, Option<Point>)) -> Result<(), String> {
+    board.set_turn(player);
+    if let Some(p) = mv {
+        if !board.can_place(p) {
+            return Err(format!("{} is illegal", point_to_gtp(p, board.get_size())));
+        }
+    }
+    board.play(mv);
+    Ok(())
+}
+
+fn get_vertex(mv: Option<Point>, size: usize) -> String {
+    match mv {
+        Some(p) => point_to_gtp(p, size),
+        None => "pass".to_string(),
+    }
+}
+
+// the analysis of `turn`, turned round to `report_as`
+fn get_response(
+    id: String,
+    turn: usize,
+    analysis: &Analysis,
+    size: usize,
+    report_as: Perspective,
+    include_ownership: bool,
+) -> Response {
+    let flipped = report_as.is_flipped(analysis.player);
+    let winrate = |w: f64| if flipped { 1.0 - w } else { w };
+    let lead = |l: f64| if flipped { -l } else { l };
+    let move_infos = analysis
+        .moves
+        .iter()
+        .enumerate()
+        .map(|(order, m)| MoveInfo {
+            mv: get_vertex(m.mv, size),
+            visits: m.visits,
+            winrate: winrate(m.winrate),
+            score_lead: lead(m.score_lead),
+            score_mean: lead(m.score_lead),
+            order,
+            pv: m.pv.iter().map(|&mv| get_vertex(mv, size)).collect(),
+        })
+        .collect();
+    // ownership comes from Black's point of view
+    let ownership_flipped = match report_as {
+        Perspective::Black => false,
+        Perspective::White => true,
+        Perspective::SideToMove => analysis.player == CellState::White,
+    };
+    let ownership = analysis
+        .ownership
+        .iter()
+        .map(|&o| if ownership_flipped { -o } else { o })
+        .collect();
+    Response {
+        id,
+        is_during_search: false,
+        turn_number: turn,
+        move_infos,
+        root_info: RootInfo {
+            current_player: match analysis.player {
+                CellState::White => "W".to_string(),
+                _ => "B".to_string(),
+            },
+            visits: analysis.visits,
+            winrate: winrate(analysis.winrate),
+            score_lead: lead(analysis.score_lead),
+        },
+        ownership: if include_ownership {
+            Some(ownership)
+        } else {
+            None
+        },
+    }
+}
@@ -0,0 +1,14 @@
+//! KataGo's JSON analysis protocol over the local engine, so tools written
+//! for KataGo's analysis engine can use the search in [`crate::engine`]
+//! instead. Queries come in as a JSON object per line and every analysed
+//! turn goes back as one; see [`AnalysisEngine`]. Enabled by the `analysis`
+//! feature.
+
+mod engine;
+mod protocol;
+
+#[cfg(test)]
+mod tests;
+
+pub use engine::*;
+pub use protocol::*;
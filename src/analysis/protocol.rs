@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+/// A line of input: a position to analyse, or an action such as
+/// `query_version` when `action` is set. Fields KataGo knows about but
+/// this engine doesn't use, such as `priority`, are ignored.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Query {
+    pub id: String,
+    pub action: Option<String>,
+    /// The query a `terminate` action is for.
+    pub terminate_id: Option<String>,
+    /// The moves of the game, as colour (`B` or `W`) and GTP vertex pairs.
+    #[serde(default)]
+    pub moves: Vec<(String, String)>,
+    /// Stones on the board before the first move.
+    #[serde(default)]
+    pub initial_stones: Vec<(String, String)>,
+    /// Who plays first, Black unless the moves say otherwise.
+    pub initial_player: Option<String>,
+    pub rules: Option<QueryRules>,
+    pub komi: Option<f32>,
+    pub board_x_size: Option<usize>,
+    pub board_y_size: Option<usize>,
+    /// The positions to analyse, by how many of the moves have been
+    /// played; just the final position if there are none.
+    pub analyze_turns: Option<Vec<usize>>,
+    pub max_visits: Option<usize>,
+    #[serde(default)]
+    pub include_ownership: bool,
+    pub override_settings: Option<OverrideSettings>,
+}
+
+/// A query's rules: a name such as `chinese` or `tromp-taylor`, or KataGo's
+/// object form spelling the rules out.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum QueryRules {
+    Name(String),
+    Rules(KataGoRules),
+}
+
+/// KataGo's rules object, e.g.
+/// `{"ko":"POSITIONAL","scoring":"AREA","suicide":true,"whiteHandicapBonus":"0"}`.
+/// `tax` and `friendlyPassOk` are read but make no difference here, and a
+/// button isn't supported.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KataGoRules {
+    /// `SIMPLE`, `POSITIONAL` or `SITUATIONAL`.
+    pub ko: String,
+    /// `AREA` or `TERRITORY`.
+    pub scoring: String,
+    #[serde(default)]
+    pub suicide: bool,
+    #[serde(default)]
+    pub has_button: bool,
+    /// `0`, `N` or `N-1`.
+    pub white_handicap_bonus: Option<String>,
+    pub tax: Option<String>,
+    pub friendly_pass_ok: Option<bool>,
+}
+
+/// The settings a query can change for itself.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OverrideSettings {
+    /// `BLACK`, `WHITE` or `SIDETOMOVE`.
+    pub report_analysis_winrates_as: Option<String>,
+}
+
+/// The analysis of one turn of a query.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub id: String,
+    pub is_during_search: bool,
+    pub turn_number: usize,
+    pub move_infos: Vec<MoveInfo>,
+    pub root_info: RootInfo,
+    /// How surely each point ends up owned, row by row from the top, if the
+    /// query asked for it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ownership: Option<Vec<f64>>,
+}
+
+/// One of the moves considered, the most visited first.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveInfo {
+    #[serde(rename = "move")]
+    pub mv: String,
+    pub visits: u32,
+    pub winrate: f64,
+    pub score_lead: f64,
+    /// The same as `score_lead`, which KataGo keeps under this name too.
+    pub score_mean: f64,
+    pub order: usize,
+    pub pv: Vec<String>,
+}
+
+/// The position as a whole.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RootInfo {
+    pub current_player: String,
+    pub visits: u32,
+    pub winrate: f64,
+    pub score_lead: f64,
+}
+
+/// A query that couldn't be answered, with the field at fault if there is
+/// one. The id is left out if the line couldn't be read far enough to
+/// find it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ErrorResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+}
+
+/// The reply to an action rather than an analysis.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionResponse {
+    pub id: String,
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminate_id: Option<String>,
+}
@@ -0,0 +1,135 @@
+use serde_json::Value;
+
+use crate::analysis::*;
+use crate::engine::*;
+
+fn get_engine() -> AnalysisEngine {
+    AnalysisEngine::new(MctsConfig {
+        budget: SearchBudget::Playouts(100),
+        threads: 1,
+        seed: Some(1),
+        ..MctsConfig::default()
+    })
+}
+
+fn handle(engine: &mut AnalysisEngine, query: &str) -> Vec<Value> {
+    engine
+        .handle_line(query)
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_analysis_queries() {
+    let mut engine = get_engine();
+    let responses = handle(
+        &mut engine,
+        r#"{"id":"a","moves":[["B","C3"],["W","D4"]],"rules":"chinese","komi":7.5,
+            "boardXSize":5,"boardYSize":5,"analyzeTurns":[0,2],"maxVisits":200,
+            "includeOwnership":true}"#,
+    );
+    assert_eq!(responses.len(), 2);
+    for (response, &turn) in responses.iter().zip([0, 2].iter()) {
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["turnNumber"], turn);
+        assert_eq!(response["isDuringSearch"], false);
+        assert_eq!(response["rootInfo"]["visits"], 200);
+        assert_eq!(response["rootInfo"]["currentPlayer"], "B");
+        assert_eq!(response["ownership"].as_array().unwrap().len(), 25);
+        let moves = response["moveInfos"].as_array().unwrap();
+        assert_eq!(moves[0]["order"], 0);
+        assert_eq!(moves[0]["pv"][0], moves[0]["move"]);
+        let winrate = moves[0]["winrate"].as_f64().unwrap();
+        assert!((0.0..=1.0).contains(&winrate));
+    }
+    // Black's stone in the middle is Black's, and ownership is +1 for Black
+    let ownership = responses[1]["ownership"].as_array().unwrap();
+    assert!(ownership[2 * 5 + 2].as_f64().unwrap() > 0.0);
+    assert!(ownership
+        .iter()
+        .all(|o| (-1.0..=1.0).contains(&o.as_f64().unwrap())));
+
+    // White to move, reported from their side of the board instead
+    let black = handle(
+        &mut engine,
+        r#"{"id":"b","moves":[["B","C3"]],"rules":"tromp-taylor","boardXSize":5,
+            "boardYSize":5,"maxVisits":100}"#,
+    );
+    let white = handle(
+        &mut engine,
+        r#"{"id":"b","moves":[["B","C3"]],"rules":"tromp-taylor","boardXSize":5,
+            "boardYSize":5,"maxVisits":100,
+            "overrideSettings":{"reportAnalysisWinratesAs":"SIDETOMOVE"}}"#,
+    );
+    assert_eq!(black[0]["turnNumber"], 1);
+    assert_eq!(black[0]["rootInfo"]["currentPlayer"], "W");
+    assert!(black[0].get("ownership").is_none());
+    let winrate = |r: &Value| r["rootInfo"]["winrate"].as_f64().unwrap();
+    assert!((winrate(&black[0]) + winrate(&white[0]) - 1.0).abs() < 1e-9);
+
+    // the moves needn't alternate, and handing the turn over isn't a pass,
+    // which with Black's pass would have ended the game at turn 2
+    let responses = handle(
+        &mut engine,
+        r#"{"id":"c","moves":[["B","C3"],["B","pass"],["B","D4"],["W","E5"]],
+            "rules":{"ko":"SITUATIONAL","scoring":"AREA","suicide":false,"whiteHandicapBonus":"N-1"},
+            "boardXSize":5,"boardYSize":5,"analyzeTurns":[2,3,4],"maxVisits":50}"#,
+    );
+    let players: Vec<&Value> = responses
+        .iter()
+        .map(|r| &r["rootInfo"]["currentPlayer"])
+        .collect();
+    assert_eq!(players, ["W", "W", "B"]);
+    assert!(!responses[0]["moveInfos"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_analysis_errors() {
+    let mut engine = get_engine();
+    let error = |engine: &mut AnalysisEngine, query: &str| handle(engine, query).remove(0);
+
+    let response = error(&mut engine, "not json");
+    assert!(response.get("id").is_none() && response.get("error").is_some());
+
+    let response = error(
+        &mut engine,
+        r#"{"id":"c","moves":[["B","C3"],["W","C3"]],"rules":"japanese","boardXSize":5,"boardYSize":5}"#,
+    );
+    assert_eq!(response["id"], "c");
+    assert_eq!(response["field"], "moves");
+
+    let response = error(&mut engine, r#"{"id":"d","moves":[],"rules":"go"}"#);
+    assert_eq!(response["field"], "rules");
+    let response = error(
+        &mut engine,
+        r#"{"id":"d","rules":{"ko":"SIMPLE","scoring":"AREA","hasButton":true}}"#,
+    );
+    assert_eq!(response["field"], "rules");
+    let response = error(
+        &mut engine,
+        r#"{"id":"d","rules":{"ko":"NEVER","scoring":"AREA"}}"#,
+    );
+    assert_eq!(response["field"], "rules");
+    let response = error(
+        &mut engine,
+        r#"{"id":"e","rules":"aga","analyzeTurns":[1],"boardXSize":9,"boardYSize":9}"#,
+    );
+    assert_eq!(response["field"], "analyzeTurns");
+    let response = error(
+        &mut engine,
+        r#"{"id":"f","rules":"aga","boardXSize":9,"boardYSize":13}"#,
+    );
+    assert_eq!(response["field"], "boardYSize");
+
+    let response = error(&mut engine, r#"{"id":"g","action":"query_version"}"#);
+    assert_eq!(response["action"], "query_version");
+    assert!(response["version"].is_string());
+    let response = error(
+        &mut engine,
+        r#"{"id":"h","action":"terminate","terminateId":"a"}"#,
+    );
+    assert_eq!(response["terminateId"], "a");
+    let response = error(&mut engine, r#"{"id":"i","action":"explode"}"#);
+    assert_eq!(response["field"], "action");
+}
@@ -0,0 +1,46 @@
+use std::io::{self, BufRead, Write};
+
+use go::analysis::*;
+use go::engine::*;
+
+const USAGE: &str = "usage: go-analysis [--visits N] [--threads N] \
+[--report-as BLACK|WHITE|SIDETOMOVE]";
+
+fn parse_args() -> Result<AnalysisEngine, String> {
+    let mut config = MctsConfig {
+        budget: SearchBudget::Playouts(1000),
+        ..MctsConfig::default()
+    };
+    let mut report_as = Perspective::Black;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--visits" => config.budget = SearchBudget::Playouts(value.parse().map_err(|_| USAGE)?),
+            "--threads" => config.threads = value.parse().map_err(|_| USAGE)?,
+            "--report-as" => report_as = Perspective::from_name(&value).ok_or(USAGE)?,
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let mut engine = AnalysisEngine::new(config);
+    engine.set_report_as(report_as);
+    Ok(engine)
+}
+
+fn main() -> Result<(), String> {
+    let mut engine = parse_args()?;
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        for response in engine.handle_line(&line) {
+            writeln!(stdout, "{}", response)
+                .and_then(|_| stdout.flush())
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
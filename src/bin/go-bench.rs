@@ -0,0 +1,166 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use go::engine::*;
+use go::go_core::*;
+
+const USAGE: &str = "usage: go-bench [--engine mcts|random] [--opponent mcts|random] \
+[--games N] [--size N] [--komi K] [--rules NAME] [--playouts N] [--threads N] \
+[--parallel N] [--sgf DIR]";
+
+struct Args {
+    engine: String,
+    opponent: String,
+    tournament: TournamentConfig,
+    config: MctsConfig,
+    sgf_dir: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut parsed = Args {
+        engine: "mcts".to_string(),
+        opponent: "random".to_string(),
+        tournament: TournamentConfig::default(),
+        config: MctsConfig {
+            budget: SearchBudget::Playouts(1000),
+            ..MctsConfig::default()
+        },
+        sgf_dir: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(USAGE)?;
+        let number = || value.parse::<usize>().map_err(|_| USAGE);
+        match arg.as_str() {
+            "--engine" => parsed.engine = value,
+            "--opponent" => parsed.opponent = value,
+            "--games" => parsed.tournament.games = number()?,
+            "--size" => parsed.tournament.size = number()?,
+            "--komi" => parsed.tournament.komi = Some(value.parse().map_err(|_| USAGE)?),
+            "--rules" => parsed.tournament.rules = RuleSet::from_name(&value).ok_or(USAGE)?,
+            "--playouts" => parsed.config.budget = SearchBudget::Playouts(number()?),
+            "--threads" => parsed.config.threads = number()?,
+            "--parallel" => parsed.tournament.threads = number()?,
+            "--sgf" => parsed.sgf_dir = Some(PathBuf::from(value)),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    for engine in [&parsed.engine, &parsed.opponent].iter() {
+        if engine.as_str() != "mcts" && engine.as_str() != "random" {
+            return Err(USAGE.to_string());
+        }
+    }
+    Ok(parsed)
+}
+
+// the playouts the measured engine has run and how long they took
+type Totals = Arc<Mutex<(usize, Duration)>>;
+
+// an MCTS engine that adds up its search stats after every move
+struct Measured {
+    mcts: MctsPlayer,
+    totals: Totals,
+}
+
+impl MoveGenerator for Measured {
+    fn get_name(&self) -> &str {
+        self.mcts.get_name()
+    }
+
+    fn generate_move(&mut self, board: &Board) -> Option<Point> {
+        let mv = self.mcts.generate_move(board);
+        if let Some(stats) = self.mcts.get_stats() {
+            let mut totals = self.totals.lock().unwrap();
+            totals.0 += stats.playouts;
+            totals.1 += stats.elapsed;
+        }
+        mv
+    }
+}
+
+// the engine called `name`, measured if `totals` is given
+fn create_engine(
+    name: &str,
+    config: MctsConfig,
+    totals: Option<&Totals>,
+) -> Box<dyn MoveGenerator> {
+    match (name, totals) {
+        ("mcts", Some(totals)) => Box::new(Measured {
+            mcts: MctsPlayer::new(config),
+            totals: totals.clone(),
+        }),
+        ("mcts", None) => Box::new(MctsPlayer::new(config)),
+        _ => Box::new(RandomPlayer::new()),
+    }
+}
+
+fn percent(x: f64) -> String {
+    format!("{:.1}%", 100.0 * x)
+}
+
+// plays the engine against its opponent, the MCTS search against the
+// random baseline by default, alternating colours
+fn main() -> Result<(), String> {
+    let args = parse_args()?;
+    if let Some(dir) = &args.sgf_dir {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("couldn't create {}: {}", dir.display(), e))?;
+    }
+
+    let totals = Totals::default();
+    let first = || create_engine(&args.engine, args.config, Some(&totals));
+    let second = || create_engine(&args.opponent, args.config, None);
+    let report = |game: &GameResult| {
+        let outcome = match game.get_winner() {
+            CellState::None => "drew",
+            winner if winner == game.first_colour => "won",
+            _ => "lost",
+        };
+        println!(
+            "game {}: {} {} as {} ({})",
+            game.index + 1,
+            args.engine,
+            outcome,
+            game.first_colour,
+            game.score
+        );
+    };
+    let result = run_tournament(&args.tournament, &first, &second, &report);
+
+    if let Some(dir) = &args.sgf_dir {
+        for game in &result.games {
+            let path = dir.join(format!("game-{:03}.sgf", game.index + 1));
+            std::fs::write(&path, &game.sgf)
+                .map_err(|e| format!("couldn't write {}: {}", path.display(), e))?;
+        }
+    }
+
+    let (wins, draws, losses) = result.get_record();
+    let (low, high) = result.get_confidence_interval();
+    println!(
+        "{} won {}, drew {} and lost {} of {} games against {}: {} (95% confidence {} to {})",
+        args.engine,
+        wins,
+        draws,
+        losses,
+        result.games.len(),
+        args.opponent,
+        percent(result.get_win_rate()),
+        percent(low),
+        percent(high)
+    );
+    let (elo, (elo_low, elo_high)) = result.get_elo_difference();
+    println!(
+        "Elo difference {:+.0} (95% confidence {:+.0} to {:+.0})",
+        elo, elo_low, elo_high
+    );
+    let (playouts, elapsed) = *totals.lock().unwrap();
+    if playouts > 0 {
+        println!(
+            "{:.0} playouts per second",
+            playouts as f64 / elapsed.as_secs_f64().max(1e-9)
+        );
+    }
+    Ok(())
+}
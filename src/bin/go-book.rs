@@ -0,0 +1,22 @@
+use std::path::Path;
+
+use go::engine::*;
+
+const USAGE: &str = "usage: go-book GAMES_DIR OUTPUT [--depth N]";
+
+// builds an opening book from a directory of SGF games and saves it, so
+// that the GUI and go-gtp don't have to read the games every time
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (games, output, depth) = match args.as_slice() {
+        [games, output] => (games, output, DEFAULT_BOOK_DEPTH),
+        [games, output, flag, depth] if flag == "--depth" => {
+            (games, output, depth.parse().map_err(|_| USAGE)?)
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+    let book = OpeningBook::build(Path::new(games), depth)?;
+    book.save(Path::new(output))?;
+    println!("{} positions", book.get_position_count());
+    Ok(())
+}
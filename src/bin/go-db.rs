@@ -0,0 +1,128 @@
+use std::path::Path;
+
+use go::go_core::*;
+use go::gtp::*;
+use go::sgf::*;
+
+const USAGE: &str = "usage: go-db add DATABASE PATH...\n       \
+go-db search DATABASE GAME.sgf [--moves N] [--region aa:ee]";
+
+// how many of the games found are listed
+const MAX_LISTED: usize = 20;
+
+fn open(path: &Path) -> Result<GameDatabase, String> {
+    if path.exists() {
+        GameDatabase::load(path)
+    } else {
+        Ok(GameDatabase::new())
+    }
+}
+
+// adds SGF files and directories of them to the database, creating it if
+// it doesn't exist yet
+fn add(database_path: &Path, paths: &[String]) -> Result<(), String> {
+    let mut database = open(database_path)?;
+    for path in paths.iter().map(Path::new) {
+        if path.is_dir() {
+            for problem in database.add_dir(path)? {
+                eprintln!("skipped {}", problem);
+            }
+        } else {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+            database.add_sgf(&path.display().to_string(), &text)?;
+        }
+    }
+    database.save(database_path)?;
+    println!("{} games", database.get_game_count());
+    Ok(())
+}
+
+// searches for the position after the first `moves` moves of a game,
+// or just the part of it in `region`, in the same place up to symmetry
+fn search(database_path: &Path, game: &Path, options: &[String]) -> Result<(), String> {
+    let mut moves = usize::MAX;
+    let mut region = None;
+    for option in options.chunks(2) {
+        match option {
+            [flag, value] if flag == "--moves" => moves = value.parse().map_err(|_| USAGE)?,
+            [flag, value] if flag == "--region" => region = Some(value.clone()),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let database = GameDatabase::load(database_path)?;
+    let text = std::fs::read_to_string(game)
+        .map_err(|e| format!("couldn't read {}: {}", game.display(), e))?;
+    // read the game the same way the database does
+    let mut query = GameDatabase::new();
+    query.add_sgf("", &text)?;
+    let record = query.get_game(0);
+    let board = record.get_board(moves.min(record.get_moves().len()));
+    let size = board.get_size();
+
+    let result = match &region {
+        Some(region) => {
+            let corners: Vec<Point> = region.split(':').filter_map(sgf_to_point).collect();
+            let (a, b) = match corners.as_slice() {
+                [a, b] => (*a, *b),
+                _ => return Err(format!("invalid region '{}'", region)),
+            };
+            let mut points = Vec::new();
+            for y in a.y.min(b.y)..=a.y.max(b.y) {
+                for x in a.x.min(b.x)..=a.x.max(b.x) {
+                    points.push(Point::new(x, y));
+                }
+            }
+            database.search_region(&board, &points)
+        }
+        None => database.search_position(&board),
+    };
+
+    println!("{} games", result.games.len());
+    for found in result.games.iter().take(MAX_LISTED) {
+        let record = database.get_game(found.game);
+        println!(
+            "  {} move {}: {} vs {} {}",
+            record.source,
+            found.move_number,
+            name(&record.black),
+            name(&record.white),
+            record.result
+        );
+    }
+    println!("next moves");
+    for next in &result.next_moves {
+        let mv = match next.mv {
+            Some(p) => point_to_gtp(p, size),
+            None if region.is_some() => "elsewhere".to_string(),
+            None => "pass/end".to_string(),
+        };
+        println!(
+            "  {:9} {} games, Black won {}, White won {}",
+            mv, next.games, next.black_wins, next.white_wins
+        );
+    }
+    Ok(())
+}
+
+fn name(player: &str) -> &str {
+    if player.is_empty() {
+        "?"
+    } else {
+        player
+    }
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [command, database, paths @ ..] if command == "add" && !paths.is_empty() => {
+            add(Path::new(database), paths)
+        }
+        [command, database, game, options @ ..] if command == "search" => {
+            search(Path::new(database), Path::new(game), options)
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
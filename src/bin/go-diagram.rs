@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use go::frontend::*;
+use go::go_core::*;
+use go::sgf::*;
+
+const USAGE: &str = "usage: go-diagram GAME.sgf OUTPUT.svg|OUTPUT.png [--move N] \
+[--region aa:ii] [--numbers FIRST] [--coordinates on|off] [--spacing PIXELS] \
+[--style wood|print] [--circle aa,bb] [--cross aa,bb] [--triangle aa,bb] [--square aa,bb] [--label aa:A,bb:B]";
+
+// narrower than this and the stones are too small to read, wider and the
+// images get needlessly huge
+const MIN_SPACING: f32 = 8.0;
+const MAX_SPACING: f32 = 200.0;
+
+fn parse_points(value: &str) -> Result<Vec<Point>, String> {
+    value
+        .split(',')
+        .map(|p| sgf_to_point(p).ok_or_else(|| format!("invalid point '{}'", p)))
+        .collect()
+}
+
+fn parse_region(value: &str) -> Result<(Point, Point), String> {
+    let corners: Vec<Point> = value.split(':').filter_map(sgf_to_point).collect();
+    match corners.as_slice() {
+        [a, b] => Ok((*a, *b)),
+        _ => Err(format!("invalid region '{}'", value)),
+    }
+}
+
+// draws the position after `--move` moves of the game's main line, or at
+// the end of it, with the record's own markup at that move
+fn run(game: &Path, output: &Path, options: &[String]) -> Result<(), String> {
+    let mut review = Review::load(game)?;
+    let mut moves = usize::MAX;
+    for option in options.chunks(2) {
+        if let [flag, value] = option {
+            if flag == "--move" {
+                moves = value.parse().map_err(|_| USAGE)?;
+            }
+        }
+    }
+    while review.get_depth() < moves && review.forward() {}
+
+    let mut diagram = Diagram::new(review.get_board());
+    diagram.add_markup(&review.get_markup());
+    let mut style = DiagramStyle::default();
+    for option in options.chunks(2) {
+        let (flag, value) = match option {
+            [flag, value] => (flag.as_str(), value.as_str()),
+            _ => return Err(USAGE.to_string()),
+        };
+        match flag {
+            "--move" => {}
+            "--region" => {
+                let (a, b) = parse_region(value)?;
+                diagram.set_region(a, b)?;
+            }
+            "--numbers" => diagram.number_moves_from(value.parse().map_err(|_| USAGE)?),
+            "--coordinates" => match value {
+                "on" => diagram.set_coordinates(true),
+                "off" => diagram.set_coordinates(false),
+                _ => return Err(USAGE.to_string()),
+            },
+            "--spacing" => {
+                style.spacing = value.parse().map_err(|_| USAGE)?;
+                if !(MIN_SPACING..=MAX_SPACING).contains(&style.spacing) {
+                    return Err(format!(
+                        "--spacing must be from {} to {} pixels",
+                        MIN_SPACING, MAX_SPACING
+                    ));
+                }
+            }
+            "--style" => {
+                let spacing = style.spacing;
+                style = match value {
+                    "wood" => DiagramStyle::default(),
+                    "print" => DiagramStyle::print(),
+                    _ => return Err(USAGE.to_string()),
+                };
+                style.spacing = spacing;
+            }
+            "--circle" | "--cross" | "--triangle" | "--square" => {
+                let mark = match flag {
+                    "--circle" => Mark::Circle,
+                    "--cross" => Mark::Cross,
+                    "--triangle" => Mark::Triangle,
+                    _ => Mark::Square,
+                };
+                for p in parse_points(value)? {
+                    diagram.add_mark(p, mark.clone());
+                }
+            }
+            "--label" => {
+                for label in value.split(',') {
+                    let (point, text) = label
+                        .split_once(':')
+                        .ok_or_else(|| format!("invalid label '{}'", label))?;
+                    let p =
+                        sgf_to_point(point).ok_or_else(|| format!("invalid point '{}'", point))?;
+                    diagram.add_mark(p, Mark::Label(text.to_string()));
+                }
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    diagram.set_style(style);
+
+    match output.extension().and_then(|e| e.to_str()) {
+        Some("svg") => std::fs::write(output, diagram.to_svg())
+            .map_err(|e| format!("couldn't write {}: {}", output.display(), e)),
+        Some("png") => diagram.to_image().save_png(output),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [game, output, options @ ..] => run(Path::new(game), Path::new(output), options),
+        _ => Err(USAGE.to_string()),
+    }
+}
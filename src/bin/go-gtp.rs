@@ -0,0 +1,71 @@
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use go::engine::*;
+use go::go_core::*;
+use go::gtp::*;
+
+const USAGE: &str = "usage: go-gtp [--engine mcts|random] [--playouts N | --time SECONDS] \
+[--threads N] [--rules NAME] [--size N] [--book PATH]";
+
+fn parse_args() -> Result<(Board, Box<dyn MoveGenerator>), String> {
+    let mut engine = "mcts".to_string();
+    let mut config = MctsConfig::default();
+    let mut rules = RuleSet::default();
+    let mut size = 19;
+    let mut book = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--engine" => engine = value,
+            "--playouts" => {
+                config.budget = SearchBudget::Playouts(value.parse().map_err(|_| USAGE)?)
+            }
+            "--time" => {
+                let seconds: f64 = value.parse().map_err(|_| USAGE)?;
+                let time = Duration::try_from_secs_f64(seconds).map_err(|_| USAGE)?;
+                config.budget = SearchBudget::Time(time);
+            }
+            "--threads" => config.threads = value.parse().map_err(|_| USAGE)?,
+            "--rules" => rules = RuleSet::from_name(&value).ok_or(USAGE)?,
+            "--size" => size = value.parse().map_err(|_| USAGE)?,
+            "--book" => book = Some(OpeningBook::open(Path::new(&value))?),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let engine: Box<dyn MoveGenerator> = match engine.as_str() {
+        "mcts" => Box::new(MctsPlayer::new(config)),
+        "random" => Box::new(RandomPlayer::new()),
+        _ => return Err(USAGE.to_string()),
+    };
+    let engine = match book {
+        Some(book) => Box::new(BookPlayer::new(book, engine)),
+        None => engine,
+    };
+    Ok((Board::new(size, rules), engine))
+}
+
+fn main() -> Result<(), String> {
+    let (board, engine) = parse_args()?;
+    let mut gtp = GtpEngine::new(board, engine);
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if let Some(response) = gtp.handle_line(&line) {
+            stdout
+                .write_all(response.as_bytes())
+                .and_then(|_| stdout.flush())
+                .map_err(|e| e.to_string())?;
+        }
+        if !gtp.is_running() {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use go::go_core::*;
+use go::net::*;
+
+const USAGE: &str =
+    "usage: go-server [--port N] [--size N] [--handicap N] [--komi K] [--rules NAME]";
+
+fn parse_args() -> Result<(u16, Board), String> {
+    let mut port = DEFAULT_PORT;
+    let mut size = 19;
+    let mut handicap = 0;
+    let mut komi = None;
+    let mut rules = RuleSet::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--port" => port = value.parse().map_err(|_| USAGE)?,
+            "--size" => size = value.parse().map_err(|_| USAGE)?,
+            "--handicap" => handicap = value.parse().map_err(|_| USAGE)?,
+            "--komi" => komi = Some(value.parse().map_err(|_| USAGE)?),
+            "--rules" => rules = RuleSet::from_name(&value).ok_or(USAGE)?,
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    if !(2..=25).contains(&size) {
+        return Err(USAGE.to_string());
+    }
+    let mut board = Board::new(size, rules);
+    board.set_handicap(handicap, HandicapPlacement::Fixed)?;
+    if let Some(komi) = komi {
+        board.set_komi(komi);
+    }
+    Ok((port, board))
+}
+
+// hosts a game on the local network until it's stopped
+fn main() -> Result<(), String> {
+    let (port, board) = parse_args()?;
+    let server = GameServer::bind(&format!("0.0.0.0:{}", port), board)?;
+    println!("hosting a game on {}", server.get_address()?);
+    server.run()
+}
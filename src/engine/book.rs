@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::engine::*;
+use crate::go_core::*;
+use crate::sgf::*;
+
+/// How many moves into each game the book records by default.
+pub const DEFAULT_BOOK_DEPTH: usize = 30;
+
+// the first line of a saved book
+const BOOK_HEADER: &str = "# go opening book v1";
+
+/// How a move in the book has done: how many games played it and how many
+/// of those the player who played it went on to win or lose. Draws and
+/// games without a result only count as games.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BookStats {
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+}
+
+impl BookStats {
+    /// The fraction of decided games the move won, if any were decided.
+    pub fn get_win_rate(&self) -> Option<f32> {
+        let decided = self.wins + self.losses;
+        if decided == 0 {
+            None
+        } else {
+            Some(self.wins as f32 / decided as f32)
+        }
+    }
+}
+
+/// A move the book knows for a position.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BookMove {
+    pub mv: Point,
+    pub stats: BookStats,
+}
+
+// a position: the board size, whether Black is to play and the hash of the
+// canonical form
+type BookKey = (usize, bool, u64);
+
+/// Opening moves collected from game records, with how often each was
+/// played and how it did. Positions are keyed by their canonical form
+/// (see [`Board::get_canonical_form`]), so the same opening reached in a
+/// different corner or through a different move order shares its entry.
+/// Colours aren't swapped, since komi makes them play differently.
+///
+/// Saved books are text: a header line, then a line per move of the board
+/// size, `B` or `W` for the player to move, the position's hash in hex,
+/// the move's SGF coordinate on the canonical board and its games, wins
+/// and losses.
+#[derive(Clone, Debug, Default)]
+pub struct OpeningBook {
+    positions: HashMap<BookKey, Vec<(Point, BookStats)>>,
+}
+
+impl OpeningBook {
+    pub fn new() -> OpeningBook {
+        OpeningBook::default()
+    }
+
+    /// Builds a book from every `.sgf` file in `dir`, recording the first
+    /// `depth` moves of each game's main line.
+    pub fn build(dir: &Path, depth: usize) -> Result<OpeningBook, String> {
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("couldn't read {}: {}", dir.display(), e))?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("sgf"))
+            })
+            .collect();
+        paths.sort();
+
+        let mut book = OpeningBook::new();
+        for path in paths {
+            let text = fs::read_to_string(&path)
+                .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+            let games = parse_sgf(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+            for game in &games {
+                book.add_game(game, depth)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+            }
+        }
+        Ok(book)
+    }
+
+    /// Records the first `depth` moves of a game's main line, crediting
+    /// them with the result in its `RE` property. The game stops counting
+    /// at a pass or at a move that doesn't fit the position.
+    pub fn add_game(&mut self, root: &SgfNode, depth: usize) -> Result<(), String> {
+        let mut board = root.get_setup_board()?;
+        let winner = match root.get("RE").map(|re| re.trim().to_uppercase()) {
+            Some(ref re) if re.starts_with("B+") => CellState::Black,
+            Some(ref re) if re.starts_with("W+") => CellState::White,
+            _ => CellState::None,
+        };
+
+        let mut node = root;
+        let mut played = 0;
+        while played < depth {
+            if let Some((player, mv)) = node.get_move() {
+                let p = match mv {
+                    Some(p) if player == board.get_turn() && board.can_place(p) => p,
+                    _ => break,
+                };
+                let (key, transform, symmetries) = get_key(&board);
+                let mv = get_canonical_move(p, transform, &symmetries, board.get_size());
+                let moves = self.positions.entry(key).or_default();
+                let index = match moves.iter().position(|(q, _)| *q == mv) {
+                    Some(i) => i,
+                    None => {
+                        moves.push((mv, BookStats::default()));
+                        moves.len() - 1
+                    }
+                };
+                let stats = &mut moves[index].1;
+                stats.games += 1;
+                if winner == player {
+                    stats.wins += 1;
+                } else if winner == player.get_other_player() {
+                    stats.losses += 1;
+                }
+                board.place(p);
+                played += 1;
+            }
+            match node.children.first() {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// The number of positions in the book.
+    pub fn get_position_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// The book's moves for the player to move on `board`, most played
+    /// first. Moves that are equivalent because the position is symmetric
+    /// are each listed with the same statistics.
+    pub fn get_moves(&self, board: &Board) -> Vec<BookMove> {
+        let size = board.get_size();
+        let (key, transform, symmetries) = get_key(board);
+        let mut moves: Vec<BookMove> = Vec::new();
+        for &(mv, stats) in self.positions.get(&key).map_or(&[][..], |m| m.as_slice()) {
+            let p = transform.inverse().apply_point(mv, size);
+            for symmetry in &symmetries {
+                let q = symmetry.apply(p, size);
+                if !moves.iter().any(|m| m.mv == q) && board.can_place(q) {
+                    moves.push(BookMove { mv: q, stats });
+                }
+            }
+        }
+        moves.sort_by_key(|m| std::cmp::Reverse(m.stats.games));
+        moves
+    }
+
+    /// Builds a book from `path` with the default depth if it's a
+    /// directory of games, or loads it if it's a saved book.
+    pub fn open(path: &Path) -> Result<OpeningBook, String> {
+        if path.is_dir() {
+            OpeningBook::build(path, DEFAULT_BOOK_DEPTH)
+        } else {
+            OpeningBook::load(path)
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<OpeningBook, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        OpeningBook::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<OpeningBook, String> {
+        let mut lines = text.lines();
+        if lines.next().map(str::trim) != Some(BOOK_HEADER) {
+            return Err("not an opening book".to_string());
+        }
+        let mut book = OpeningBook::new();
+        for (number, line) in lines.enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let invalid = || format!("line {}: invalid entry", number + 2);
+            if fields.len() != 7 {
+                return Err(invalid());
+            }
+            let size = fields[0].parse().map_err(|_| invalid())?;
+            let black = match fields[1] {
+                "B" => true,
+                "W" => false,
+                _ => return Err(invalid()),
+            };
+            let hash = u64::from_str_radix(fields[2], 16).map_err(|_| invalid())?;
+            let mv = sgf_to_point(fields[3]).ok_or_else(invalid)?;
+            let stats = BookStats {
+                games: fields[4].parse().map_err(|_| invalid())?,
+                wins: fields[5].parse().map_err(|_| invalid())?,
+                losses: fields[6].parse().map_err(|_| invalid())?,
+            };
+            book.positions
+                .entry((size, black, hash))
+                .or_default()
+                .push((mv, stats));
+        }
+        Ok(book)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.write())
+            .map_err(|e| format!("couldn't write {}: {}", path.display(), e))
+    }
+
+    /// The book in its saved form, sorted so that the same book always
+    /// saves the same way.
+    pub fn write(&self) -> String {
+        let mut lines = Vec::new();
+        for (&(size, black, hash), moves) in &self.positions {
+            for (mv, stats) in moves {
+                lines.push(format!(
+                    "{} {} {:016x} {} {} {} {}",
+                    size,
+                    if black { "B" } else { "W" },
+                    hash,
+                    point_to_sgf(*mv),
+                    stats.games,
+                    stats.wins,
+                    stats.losses
+                ));
+            }
+        }
+        lines.sort();
+        let mut text = format!("{}\n", BOOK_HEADER);
+        for line in lines {
+            text += &line;
+            text.push('\n');
+        }
+        text
+    }
+}
+
+// the key for the position, the transform to its canonical form and the
+// position's own symmetries
+fn get_key(board: &Board) -> (BookKey, Transform, Vec<Symmetry>) {
+    let (hash, transform) = board.get_canonical_hash(false);
+    let black = board.get_turn() == CellState::Black;
+    (
+        (board.get_size(), black, hash),
+        transform,
+        board.get_symmetries(),
+    )
+}
+
+// the move on the canonical board, choosing the same one of any moves the
+// position's symmetries make equivalent
+fn get_canonical_move(
+    p: Point,
+    transform: Transform,
+    symmetries: &[Symmetry],
+    size: usize,
+) -> Point {
+    symmetries
+        .iter()
+        .map(|s| transform.apply_point(s.apply(p, size), size))
+        .min_by_key(|q| (q.y, q.x))
+        .unwrap()
+}
+
+/// Plays the book's most played move while the game is still in the book,
+/// and asks another engine once it isn't.
+pub struct BookPlayer {
+    book: OpeningBook,
+    engine: Box<dyn MoveGenerator>,
+}
+
+impl BookPlayer {
+    pub fn new(book: OpeningBook, engine: Box<dyn MoveGenerator>) -> BookPlayer {
+        BookPlayer { book, engine }
+    }
+}
+
+impl MoveGenerator for BookPlayer {
+    fn get_name(&self) -> &str {
+        self.engine.get_name()
+    }
+
+    fn generate_move(&mut self, board: &Board) -> Option<Point> {
+        match self.book.get_moves(board).first() {
+            Some(book_move) => Some(book_move.mv),
+            None => self.engine.generate_move(board),
+        }
+    }
+
+    fn set_time_budget(&mut self, budget: std::time::Duration) {
+        self.engine.set_time_budget(budget);
+    }
+}
@@ -0,0 +1,583 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::engine::*;
+use crate::go_core::*;
+
+// unvisited moves without any AMAF statistics are tried before anything else
+const FIRST_PLAY_URGENCY: f64 = 1.1;
+// the longest principal variation an analysis reports
+const PV_LENGTH: usize = 10;
+
+/// How long a search runs for.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SearchBudget {
+    Playouts(usize),
+    Time(Duration),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MctsConfig {
+    pub budget: SearchBudget,
+    pub threads: usize,
+    /// The UCT exploration constant.
+    pub exploration: f64,
+    /// How many real visits a move needs before its AMAF statistics count
+    /// for as much as its own. Zero turns RAVE off.
+    pub rave_equivalence: f64,
+    /// How many times a leaf is played out from before it's expanded.
+    pub expand_after: u32,
+    /// Keep the part of the tree that is still relevant between moves.
+    pub reuse_tree: bool,
+    /// Seeds the playouts, for repeatable single-threaded searches.
+    pub seed: Option<u64>,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            budget: SearchBudget::Time(Duration::from_secs(1)),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            exploration: 0.25,
+            rave_equivalence: 1000.0,
+            expand_after: 2,
+            reuse_tree: true,
+            seed: None,
+        }
+    }
+}
+
+/// What the last search found.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SearchStats {
+    pub playouts: usize,
+    pub elapsed: Duration,
+    /// How often the chosen move won its playouts.
+    pub winrate: f64,
+    /// Playouts that were already in the reused tree.
+    pub reused: usize,
+}
+
+/// One of the moves the last search looked at, from the point of view of
+/// the player who would play it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveAnalysis {
+    /// The move, or `None` for a pass.
+    pub mv: Option<Point>,
+    pub visits: u32,
+    /// How often the move won its playouts.
+    pub winrate: f64,
+    /// How many points the player was ahead by at the end of its playouts,
+    /// on average.
+    pub score_lead: f64,
+    /// The line the search expects, starting with this move.
+    pub pv: Vec<Option<Point>>,
+}
+
+/// Everything the last search found out about the position, from the point
+/// of view of the player to move unless it says otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    pub player: CellState,
+    pub visits: u32,
+    pub winrate: f64,
+    pub score_lead: f64,
+    /// The moves that were searched, the most visited first.
+    pub moves: Vec<MoveAnalysis>,
+    /// How likely each point is to end up Black's, from 1, or White's, down
+    /// to -1, row by row from the top. This is averaged over the playouts of
+    /// the last search only.
+    pub ownership: Vec<f64>,
+}
+
+struct Node {
+    mv: Option<Point>,
+    // the player who played `mv`, whose point of view `wins` is from
+    player: CellState,
+    children: Vec<usize>,
+    expanded: bool,
+    visits: u32,
+    wins: f64,
+    // the total of Black's winning margins, negative when White won, over
+    // the playouts through this node
+    score: f64,
+    amaf_visits: u32,
+    amaf_wins: f64,
+    // threads currently searching below this node, counted as losses so
+    // that they spread out
+    virtual_loss: u32,
+}
+
+impl Node {
+    fn new(mv: Option<Point>, player: CellState) -> Node {
+        Node {
+            mv,
+            player,
+            children: Vec::new(),
+            expanded: false,
+            visits: 0,
+            wins: 0.0,
+            score: 0.0,
+            amaf_visits: 0,
+            amaf_wins: 0.0,
+            virtual_loss: 0,
+        }
+    }
+}
+
+// nodes live in one vector and refer to their children by index; the root
+// is always the first
+struct Tree {
+    board: Board,
+    nodes: Vec<Node>,
+    // the total of each point's owner over the playouts of the current
+    // search, counting Black as 1 and White as -1
+    ownership: Vec<f64>,
+    samples: u32,
+}
+
+impl Tree {
+    fn new(board: &Board) -> Tree {
+        let size = board.get_size();
+        Tree {
+            board: board.clone(),
+            nodes: vec![Node::new(None, board.get_turn().get_other_player())],
+            ownership: vec![0.0; size * size],
+            samples: 0,
+        }
+    }
+
+    // follows the moves played since this tree was searched down to the
+    // current position, or gives up if the game isn't a continuation; the
+    // colours have to match too, since the turn can be handed over without
+    // a move
+    fn advance_to(mut self, board: &Board) -> Option<Tree> {
+        let old = &self.board;
+        if old.get_size() != board.get_size()
+            || old.get_rules() != board.get_rules()
+            || old.get_komi() != board.get_komi()
+            || old.get_setup_stones() != board.get_setup_stones()
+            || old.is_placing_handicap()
+            || !board.get_moves().starts_with(old.get_moves())
+        {
+            return None;
+        }
+        let mut root = 0;
+        for &(player, mv) in &board.get_moves()[old.get_moves().len()..] {
+            root = *self.nodes[root]
+                .children
+                .iter()
+                .find(|&&c| self.nodes[c].mv == mv && self.nodes[c].player == player)?;
+        }
+        if self.nodes[root].player.get_other_player() != board.get_turn() {
+            return None;
+        }
+        self.reroot(root);
+        self.board = board.clone();
+        Some(self)
+    }
+
+    // copies the subtree under `root` into a fresh vector, dropping the rest
+    fn reroot(&mut self, root: usize) {
+        if root == 0 {
+            return;
+        }
+        let mut old = std::mem::take(&mut self.nodes);
+        let mut queue = vec![root];
+        let mut next = 0;
+        while next < queue.len() {
+            let mut node =
+                std::mem::replace(&mut old[queue[next]], Node::new(None, CellState::None));
+            let first = queue.len();
+            queue.extend(node.children.iter());
+            node.children = (first..queue.len()).collect();
+            node.virtual_loss = 0;
+            self.nodes.push(node);
+            next += 1;
+        }
+    }
+
+    fn get_value(&self, parent: &Node, child: &Node, config: &MctsConfig) -> f64 {
+        let n = (child.visits + child.virtual_loss) as f64;
+        let amaf = if child.amaf_visits > 0 && config.rave_equivalence > 0.0 {
+            Some(child.amaf_wins / child.amaf_visits as f64)
+        } else {
+            None
+        };
+        if n == 0.0 {
+            return amaf.unwrap_or(FIRST_PLAY_URGENCY);
+        }
+        let q = child.wins / n;
+        let mixed = match amaf {
+            Some(amaf) => {
+                let k = config.rave_equivalence;
+                let beta = (k / (3.0 * n + k)).sqrt();
+                (1.0 - beta) * q + beta * amaf
+            }
+            None => q,
+        };
+        let total = (parent.visits + parent.virtual_loss).max(1) as f64;
+        mixed + config.exploration * (total.ln() / n).sqrt()
+    }
+
+    // walks down from the root picking the best child each time, and marks
+    // the path with a virtual loss
+    fn select(&mut self, config: &MctsConfig) -> Vec<usize> {
+        let mut path = vec![0];
+        let mut current = 0;
+        self.nodes[0].virtual_loss += 1;
+        while self.nodes[current].expanded && !self.nodes[current].children.is_empty() {
+            let parent = &self.nodes[current];
+            let mut best = parent.children[0];
+            let mut best_value = f64::MIN;
+            for &c in &parent.children {
+                let value = self.get_value(parent, &self.nodes[c], config);
+                if value > best_value {
+                    best = c;
+                    best_value = value;
+                }
+            }
+            current = best;
+            self.nodes[current].virtual_loss += 1;
+            path.push(current);
+        }
+        path
+    }
+
+    fn expand(&mut self, leaf: usize, moves: &[Option<Point>], player: CellState) {
+        if self.nodes[leaf].expanded {
+            // another thread got there first
+            return;
+        }
+        for &mv in moves {
+            self.nodes.push(Node::new(mv, player));
+            let idx = self.nodes.len() - 1;
+            self.nodes[leaf].children.push(idx);
+        }
+        self.nodes[leaf].expanded = true;
+    }
+
+    // `sequence` holds every move of the simulation, the tree moves along
+    // `path` first and then the playout, which ended with `score` and the
+    // points owned as in `ownership`
+    fn backup(
+        &mut self,
+        path: &[usize],
+        sequence: &[(CellState, Option<Point>)],
+        score: Score,
+        ownership: &[CellState],
+    ) {
+        let size = self.board.get_size();
+        let winner = score.get_winner();
+        let margin = (score.black - score.white) as f64;
+        for (total, &owner) in self.ownership.iter_mut().zip(ownership) {
+            *total += match owner {
+                CellState::Black => 1.0,
+                CellState::White => -1.0,
+                CellState::None => 0.0,
+            };
+        }
+        self.samples += 1;
+
+        // the first player to play each point from the current node onwards
+        let mut first = vec![CellState::None; size * size];
+        for &(player, mv) in sequence[path.len() - 1..].iter().rev() {
+            if let Some(p) = mv {
+                first[p.y as usize * size + p.x as usize] = player;
+            }
+        }
+
+        for (depth, &idx) in path.iter().enumerate().rev() {
+            if depth + 1 < path.len() {
+                let (player, mv) = sequence[depth];
+                if let Some(p) = mv {
+                    first[p.y as usize * size + p.x as usize] = player;
+                }
+            }
+
+            let node = &mut self.nodes[idx];
+            node.visits += 1;
+            node.virtual_loss -= 1;
+            node.wins += get_result(node.player, winner);
+            node.score += margin;
+
+            let to_move = node.player.get_other_player();
+            let result = get_result(to_move, winner);
+            for i in 0..self.nodes[idx].children.len() {
+                let c = self.nodes[idx].children[i];
+                let child = &mut self.nodes[c];
+                if let Some(p) = child.mv {
+                    if first[p.y as usize * size + p.x as usize] == to_move {
+                        child.amaf_visits += 1;
+                        child.amaf_wins += result;
+                    }
+                }
+            }
+        }
+    }
+
+    fn get_best_child(&self) -> Option<&Node> {
+        self.nodes[0]
+            .children
+            .iter()
+            .map(|&c| &self.nodes[c])
+            .max_by_key(|n| n.visits)
+    }
+
+    // the most visited line from `node`, starting with its own move
+    fn get_pv(&self, node: usize) -> Vec<Option<Point>> {
+        let mut pv = vec![self.nodes[node].mv];
+        let mut current = node;
+        while pv.len() < PV_LENGTH {
+            let next = self.nodes[current]
+                .children
+                .iter()
+                .copied()
+                .filter(|&c| self.nodes[c].visits > 0)
+                .max_by_key(|&c| self.nodes[c].visits);
+            match next {
+                Some(c) => {
+                    pv.push(self.nodes[c].mv);
+                    current = c;
+                }
+                None => break,
+            }
+        }
+        pv
+    }
+
+    fn get_analysis(&self) -> Analysis {
+        let root = &self.nodes[0];
+        let player = self.board.get_turn();
+        // Black's margins and the root's wins are turned round to the point
+        // of view of whoever plays the node's move
+        let lead = |node: &Node, player: CellState| {
+            let mean = node.score / node.visits.max(1) as f64;
+            match player {
+                CellState::White => -mean,
+                _ => mean,
+            }
+        };
+        let mut moves: Vec<MoveAnalysis> = root
+            .children
+            .iter()
+            .filter(|&&c| self.nodes[c].visits > 0)
+            .map(|&c| {
+                let node = &self.nodes[c];
+                MoveAnalysis {
+                    mv: node.mv,
+                    visits: node.visits,
+                    winrate: node.wins / node.visits as f64,
+                    score_lead: lead(node, node.player),
+                    pv: self.get_pv(c),
+                }
+            })
+            .collect();
+        moves.sort_by_key(|m| std::cmp::Reverse(m.visits));
+        let samples = self.samples.max(1) as f64;
+        Analysis {
+            player,
+            visits: root.visits,
+            winrate: if root.visits == 0 {
+                0.5
+            } else {
+                1.0 - root.wins / root.visits as f64
+            },
+            score_lead: lead(root, player),
+            moves,
+            ownership: self.ownership.iter().map(|total| total / samples).collect(),
+        }
+    }
+}
+
+fn get_result(player: CellState, winner: CellState) -> f64 {
+    if winner == player {
+        1.0
+    } else if winner == CellState::None {
+        0.5
+    } else {
+        0.0
+    }
+}
+
+/// Every move worth searching for the player to move: the legal moves that
+/// don't fill their own eyes and aren't in `settled`, and a pass.
+pub fn get_candidate_moves(board: &Board, settled: &[Point]) -> Vec<Option<Point>> {
+    let player = board.get_turn();
+    let mut moves: Vec<Option<Point>> = board
+        .get_empty_points()
+        .into_iter()
+        .filter(|&p| !is_eye(board, p, player) && !settled.contains(&p) && board.can_place(p))
+        .map(Some)
+        .collect();
+    if !board.is_placing_handicap() {
+        moves.push(None);
+    }
+    moves
+}
+
+/// A Monte Carlo tree search player: UCT with RAVE over light random
+/// playouts, searching on several threads at once.
+pub struct MctsPlayer {
+    config: MctsConfig,
+    tree: Option<Tree>,
+    stats: Option<SearchStats>,
+    searches: u64,
+}
+
+impl MctsPlayer {
+    pub fn new(config: MctsConfig) -> MctsPlayer {
+        MctsPlayer {
+            config,
+            tree: None,
+            stats: None,
+            searches: 0,
+        }
+    }
+
+    pub fn get_config(&self) -> &MctsConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: MctsConfig) {
+        self.config = config;
+    }
+
+    /// The statistics of the last search, if there has been one.
+    pub fn get_stats(&self) -> Option<SearchStats> {
+        self.stats
+    }
+
+    /// Searches `board` as for a move and reports on what was found.
+    pub fn analyze(&mut self, board: &Board) -> Analysis {
+        self.search(board);
+        self.get_analysis().unwrap()
+    }
+
+    /// What the last search found, if there has been one.
+    pub fn get_analysis(&self) -> Option<Analysis> {
+        self.tree.as_ref().map(|tree| tree.get_analysis())
+    }
+
+    fn search(&mut self, board: &Board) {
+        let tree = match self.tree.take() {
+            Some(tree) if self.config.reuse_tree => tree.advance_to(board),
+            _ => None,
+        };
+        let mut tree = tree.unwrap_or_else(|| Tree::new(board));
+        let reused = tree.nodes[0].visits as usize;
+        tree.ownership.iter_mut().for_each(|total| *total = 0.0);
+        tree.samples = 0;
+
+        let config = self.config;
+        let seed = match config.seed {
+            Some(seed) => seed.wrapping_add(self.searches),
+            None => Rng::from_time().next_u64(),
+        };
+        self.searches += 1;
+
+        // unconditional life can't be undone, so these points stay pointless
+        // to play everywhere below the root
+        let mut settled = board.get_unconditional_territory(CellState::Black);
+        settled.extend(board.get_unconditional_territory(CellState::White));
+
+        let tree = Mutex::new(tree);
+        let playouts = AtomicUsize::new(0);
+        let start = Instant::now();
+        thread::scope(|scope| {
+            for i in 0..config.threads.max(1) {
+                let tree = &tree;
+                let playouts = &playouts;
+                let settled = &settled;
+                let mut rng = Rng::new(seed.wrapping_add(i as u64 * 0x9e37_79b9));
+                scope.spawn(move || loop {
+                    let done = match config.budget {
+                        SearchBudget::Playouts(n) => playouts.fetch_add(1, Ordering::Relaxed) >= n,
+                        SearchBudget::Time(limit) => start.elapsed() >= limit,
+                    };
+                    if done {
+                        break;
+                    }
+                    simulate(tree, board, settled, &config, &mut rng);
+                    if let SearchBudget::Time(_) = config.budget {
+                        playouts.fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+
+        let tree = tree.into_inner().unwrap();
+        let total = tree.nodes[0].visits as usize;
+        self.stats = Some(SearchStats {
+            playouts: total - reused,
+            elapsed: start.elapsed(),
+            winrate: tree
+                .get_best_child()
+                .map_or(0.0, |n| n.wins / n.visits.max(1) as f64),
+            reused,
+        });
+        self.tree = Some(tree);
+    }
+}
+
+// one selection, expansion, playout and backup
+fn simulate(
+    tree: &Mutex<Tree>,
+    root: &Board,
+    settled: &[Point],
+    config: &MctsConfig,
+    rng: &mut Rng,
+) {
+    let mut board = root.clone();
+    let mut sequence = Vec::new();
+
+    let path = {
+        let mut tree = tree.lock().unwrap();
+        let path = tree.select(config);
+        for &idx in &path[1..] {
+            let node = &tree.nodes[idx];
+            sequence.push((node.player, node.mv));
+        }
+        path
+    };
+    for &(_, mv) in &sequence {
+        board.play(mv);
+    }
+
+    let leaf = path[path.len() - 1];
+    let (score, ownership) = if board.is_game_over() {
+        let ownership = PlayoutBoard::new(&board).get_ownership();
+        (board.score(&[]), ownership)
+    } else {
+        let visits = tree.lock().unwrap().nodes[leaf].visits;
+        if visits + 1 >= config.expand_after {
+            let moves = get_candidate_moves(&board, settled);
+            tree.lock().unwrap().expand(leaf, &moves, board.get_turn());
+        }
+        let mut playout = PlayoutBoard::new(&board);
+        playout.playout(rng, &mut sequence);
+        let ownership = playout.get_ownership();
+        (playout.get_score(&ownership), ownership)
+    };
+
+    tree.lock()
+        .unwrap()
+        .backup(&path, &sequence, score, &ownership);
+}
+
+impl MoveGenerator for MctsPlayer {
+    fn get_name(&self) -> &str {
+        "MCTS"
+    }
+
+    fn generate_move(&mut self, board: &Board) -> Option<Point> {
+        self.search(board);
+        self.tree.as_ref()?.get_best_child()?.mv
+    }
+
+    fn set_time_budget(&mut self, budget: Duration) {
+        self.config.budget = SearchBudget::Time(budget);
+    }
+}
@@ -0,0 +1,47 @@
+//! Computer players, all built on [`MoveGenerator`].
+
+mod book;
+mod mcts;
+mod pattern;
+mod playout;
+mod random;
+mod rng;
+mod tournament;
+
+#[cfg(test)]
+mod tests;
+
+pub use book::*;
+pub use mcts::*;
+pub use pattern::*;
+pub use playout::*;
+pub use random::*;
+pub use rng::*;
+pub use tournament::*;
+
+use crate::go_core::*;
+
+/// Anything that can choose moves: a bot, a remote player or a script.
+/// Generators are `Send` so that front ends can think on another thread.
+pub trait MoveGenerator: Send {
+    fn get_name(&self) -> &str;
+
+    /// Chooses a move for the player to move on `board`, or `None` to pass.
+    fn generate_move(&mut self, board: &Board) -> Option<Point>;
+
+    /// Asks the generator to spend about `budget` on its next move, for
+    /// playing on a clock. Generators that don't search ignore it.
+    fn set_time_budget(&mut self, _budget: std::time::Duration) {}
+}
+
+/// Whether the empty point `p` is a single-point eye of `player`: every
+/// neighbour is one of their stones and it isn't false (see
+/// [`Board::is_false_eye`]).
+pub fn is_eye(board: &Board, p: Point, player: CellState) -> bool {
+    board.get(p) == CellState::None
+        && board
+            .get_adjacent(p)
+            .iter()
+            .all(|&q| board.get(q) == player)
+        && !board.is_false_eye(p, player)
+}
@@ -0,0 +1,320 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::go_core::*;
+
+// the points a pattern covers around the move, in the order they're
+// encoded. The diamond is everything within two steps.
+const SQUARE_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+const DIAMOND_OFFSETS: [(i32, i32); 12] = [
+    (0, -2),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-2, 0),
+    (-1, 0),
+    (1, 0),
+    (2, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (0, 2),
+];
+
+// each cell takes two bits of a pattern's code
+const EMPTY: u32 = 0;
+const OWN: u32 = 1;
+const OPPONENT: u32 = 2;
+const EDGE: u32 = 3;
+
+// a pattern with more wildcards than this expands to too many codes
+const MAX_WILDCARDS: usize = 6;
+
+/// The area a pattern covers around the move.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PatternShape {
+    /// The eight neighbours.
+    Square,
+    /// Every point within two steps (Manhattan distance) of the move.
+    Diamond,
+}
+
+impl PatternShape {
+    fn get_offsets(&self) -> &'static [(i32, i32)] {
+        match self {
+            PatternShape::Square => &SQUARE_OFFSETS,
+            PatternShape::Diamond => &DIAMOND_OFFSETS,
+        }
+    }
+}
+
+/// A shape around an empty point, seen by the player about to play there.
+/// Each cell is `.` for empty, `X` for the player's stone, `O` for the
+/// opponent's, `-` for off the board or `?` for anything.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    pub name: String,
+    pub weight: f32,
+    pub shape: PatternShape,
+    /// The cells in the order of the shape's points: left to right, top to
+    /// bottom, leaving out the move itself.
+    pub cells: Vec<char>,
+    /// Whether the pattern also matches with the colours swapped.
+    pub either_colour: bool,
+}
+
+/// A set of patterns indexed by every symmetric version of them, so that
+/// matching a point is one hash lookup per shape. Patterns are written for
+/// the player to move, so the same ones match for either colour.
+///
+/// In a pattern file `#` starts a comment and each pattern is a line
+/// `pattern <name> <weight>`, with `either` on the end if it should also
+/// match with the colours swapped, followed by its rows, with `*` marking
+/// the move. A 3x3 pattern has three rows and a diamond five (of 1, 3, 5,
+/// 3 and 1 cells). Spaces between the cells are ignored:
+///
+/// ```text
+/// # a hane at the head of two stones
+/// pattern hane 2.0
+/// X O ?
+/// . * .
+/// ? ? ?
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PatternLibrary {
+    patterns: Vec<Pattern>,
+    square: HashMap<u32, Vec<usize>>,
+    diamond: HashMap<u32, Vec<usize>>,
+}
+
+impl PatternLibrary {
+    pub fn new() -> PatternLibrary {
+        PatternLibrary::default()
+    }
+
+    /// Reads a pattern file (see [`PatternLibrary`] for the format).
+    pub fn load(path: &Path) -> Result<PatternLibrary, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        PatternLibrary::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<PatternLibrary, String> {
+        let mut library = PatternLibrary::new();
+        let mut header: Option<(usize, String, f32, bool)> = None;
+        let mut rows: Vec<String> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            if words.next() == Some("pattern") {
+                if let Some((start, name, weight, either)) = header.take() {
+                    let pattern = parse_pattern(name, weight, either, &rows);
+                    library.add(pattern.map_err(|e| at(start, e))?)?;
+                }
+                let name = words.next().ok_or_else(|| at(number, "missing name"))?;
+                let weight = words
+                    .next()
+                    .ok_or_else(|| at(number, "missing weight"))?
+                    .parse()
+                    .map_err(|_| at(number, "invalid weight"))?;
+                let either = match words.next() {
+                    None => false,
+                    Some("either") => true,
+                    Some(word) => return Err(at(number, format!("unexpected '{}'", word))),
+                };
+                header = Some((number, name.to_string(), weight, either));
+                rows.clear();
+            } else if header.is_some() {
+                rows.push(line.split_whitespace().collect());
+            } else {
+                return Err(at(number, "expected 'pattern'"));
+            }
+        }
+        if let Some((start, name, weight, either)) = header {
+            let pattern = parse_pattern(name, weight, either, &rows);
+            library.add(pattern.map_err(|e| at(start, e))?)?;
+        }
+        Ok(library)
+    }
+
+    /// Adds a pattern under all eight symmetries of the board.
+    pub fn add(&mut self, pattern: Pattern) -> Result<(), String> {
+        let offsets = pattern.shape.get_offsets();
+        if pattern.cells.len() != offsets.len() {
+            return Err(format!(
+                "pattern {} has the wrong number of cells",
+                pattern.name
+            ));
+        }
+        let wildcards = pattern.cells.iter().filter(|c| **c == '?').count();
+        if wildcards > MAX_WILDCARDS {
+            return Err(format!(
+                "pattern {} has more than {} wildcards",
+                pattern.name, MAX_WILDCARDS
+            ));
+        }
+
+        let index = self.patterns.len();
+        let mut codes = HashSet::new();
+        for symmetry in Symmetry::ALL.iter() {
+            // the cell of the pattern that lands on each point of the shape
+            let mut cells = vec!['?'; offsets.len()];
+            for (i, &(dx, dy)) in offsets.iter().enumerate() {
+                let moved = symmetry.apply_offset(Point::new(dx, dy));
+                let moved = (moved.x, moved.y);
+                let j = offsets.iter().position(|&o| o == moved).unwrap();
+                cells[j] = pattern.cells[i];
+            }
+            let mut inverted = Vec::new();
+            if pattern.either_colour {
+                inverted = cells.iter().map(|&c| invert(c)).collect();
+            }
+            codes.extend(expand(&cells));
+            codes.extend(expand(&inverted));
+        }
+        let table = match pattern.shape {
+            PatternShape::Square => &mut self.square,
+            PatternShape::Diamond => &mut self.diamond,
+        };
+        for code in codes {
+            table.entry(code).or_default().push(index);
+        }
+        self.patterns.push(pattern);
+        Ok(())
+    }
+
+    pub fn get_patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    /// The patterns that match a move by `player` at `p`, which should be
+    /// empty.
+    pub fn find(&self, board: &Board, p: Point, player: CellState) -> Vec<&Pattern> {
+        let mut found = Vec::new();
+        for (shape, table) in [
+            (PatternShape::Square, &self.square),
+            (PatternShape::Diamond, &self.diamond),
+        ]
+        .iter()
+        {
+            if table.is_empty() {
+                continue;
+            }
+            let code = get_code(board, p, player, shape.get_offsets());
+            if let Some(indices) = table.get(&code) {
+                found.extend(indices.iter().map(|&i| &self.patterns[i]));
+            }
+        }
+        found
+    }
+
+    /// The weight of the heaviest pattern matching a move by `player` at
+    /// `p`, or `None` if nothing matches.
+    pub fn get_weight(&self, board: &Board, p: Point, player: CellState) -> Option<f32> {
+        self.find(board, p, player)
+            .into_iter()
+            .map(|pattern| pattern.weight)
+            .reduce(f32::max)
+    }
+}
+
+fn at(line: usize, error: impl std::fmt::Display) -> String {
+    format!("line {}: {}", line + 1, error)
+}
+
+fn parse_pattern(
+    name: String,
+    weight: f32,
+    either_colour: bool,
+    rows: &[String],
+) -> Result<Pattern, String> {
+    let (shape, widths): (PatternShape, &[usize]) = match rows.len() {
+        3 => (PatternShape::Square, &[3, 3, 3]),
+        5 => (PatternShape::Diamond, &[1, 3, 5, 3, 1]),
+        _ => return Err(format!("pattern {} needs 3 or 5 rows", name)),
+    };
+    let mut cells = Vec::new();
+    for (row, &width) in rows.iter().zip(widths) {
+        if row.chars().count() != width {
+            return Err(format!("pattern {} has a row of the wrong width", name));
+        }
+        cells.extend(row.chars());
+    }
+    let middle = cells.len() / 2;
+    if cells[middle] != '*' {
+        return Err(format!("pattern {} needs a * in the middle", name));
+    }
+    cells.remove(middle);
+    if let Some(c) = cells.iter().find(|c| !".XO-?".contains(**c)) {
+        return Err(format!("pattern {} has an unknown cell '{}'", name, c));
+    }
+    Ok(Pattern {
+        name,
+        weight,
+        shape,
+        cells,
+        either_colour,
+    })
+}
+
+fn invert(cell: char) -> char {
+    match cell {
+        'X' => 'O',
+        'O' => 'X',
+        c => c,
+    }
+}
+
+// every code the cells can match, one for each way of filling in the
+// wildcards
+fn expand(cells: &[char]) -> Vec<u32> {
+    if cells.is_empty() {
+        return Vec::new();
+    }
+    let mut codes = vec![0];
+    for (i, c) in cells.iter().enumerate() {
+        let values: &[u32] = match c {
+            '.' => &[EMPTY],
+            'X' => &[OWN],
+            'O' => &[OPPONENT],
+            '-' => &[EDGE],
+            _ => &[EMPTY, OWN, OPPONENT, EDGE],
+        };
+        codes = codes
+            .iter()
+            .flat_map(|code| values.iter().map(move |v| code | v << (2 * i)))
+            .collect();
+    }
+    codes
+}
+
+fn get_code(board: &Board, p: Point, player: CellState, offsets: &[(i32, i32)]) -> u32 {
+    let size = board.get_size() as i32;
+    let mut code = 0;
+    for (i, &(dx, dy)) in offsets.iter().enumerate() {
+        let q = p + Point::new(dx, dy);
+        let value = if q.x < 0 || q.y < 0 || q.x >= size || q.y >= size {
+            EDGE
+        } else {
+            match board.get(q) {
+                CellState::None => EMPTY,
+                state if state == player => OWN,
+                _ => OPPONENT,
+            }
+        };
+        code |= value << (2 * i);
+    }
+    code
+}
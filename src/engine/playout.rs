@@ -0,0 +1,402 @@
+use crate::engine::*;
+use crate::go_core::*;
+
+const EMPTY: u8 = 0;
+const BLACK: u8 = 1;
+const WHITE: u8 = 2;
+const BORDER: u8 = 3;
+
+fn to_color(state: CellState) -> u8 {
+    match state {
+        CellState::Black => BLACK,
+        CellState::White => WHITE,
+        CellState::None => EMPTY,
+    }
+}
+
+fn to_cell_state(color: u8) -> CellState {
+    match color {
+        BLACK => CellState::Black,
+        WHITE => CellState::White,
+        _ => CellState::None,
+    }
+}
+
+/// A stripped down board for random playouts, many times faster than
+/// [`Board`]: simple ko only, no suicide, no history and area scoring.
+///
+/// Points are stored in one array with a border around the edge, and each
+/// group keeps its pseudo-liberties (a liberty counted once per adjacent
+/// stone) along with their sum and sum of squares, which is enough to tell
+/// when the group is captured or in atari without walking it.
+#[derive(Clone)]
+pub struct PlayoutBoard {
+    size: usize,
+    // the padded width, `size + 2`
+    stride: usize,
+    cells: Vec<u8>,
+    // each stone's group is named by one of its stones, and the stones of a
+    // group form a circular list through `next`
+    head: Vec<usize>,
+    next: Vec<usize>,
+    stones: Vec<u32>,
+    libs: Vec<u32>,
+    lib_sum: Vec<u64>,
+    lib_sum_sq: Vec<u64>,
+    empty: Vec<usize>,
+    empty_pos: Vec<usize>,
+    ko: Option<usize>,
+    turn: u8,
+    komi: f32,
+    passes: u32,
+}
+
+impl PlayoutBoard {
+    pub fn new(board: &Board) -> PlayoutBoard {
+        let size = board.get_size();
+        let stride = size + 2;
+        let len = stride * stride;
+        let rules = board.get_rules();
+        let compensation = match rules.scoring {
+            ScoringMethod::Area => rules.handicap_compensation.get_points(board.get_handicap()),
+            ScoringMethod::Territory => 0.0,
+        };
+        let mut playout = PlayoutBoard {
+            size,
+            stride,
+            cells: vec![BORDER; len],
+            head: (0..len).collect(),
+            next: (0..len).collect(),
+            stones: vec![0; len],
+            libs: vec![0; len],
+            lib_sum: vec![0; len],
+            lib_sum_sq: vec![0; len],
+            empty: Vec::with_capacity(size * size),
+            empty_pos: vec![0; len],
+            ko: board
+                .get_ko()
+                .map(|p| (p.y as usize + 1) * stride + p.x as usize + 1),
+            turn: to_color(board.get_turn()),
+            komi: board.get_komi() + compensation,
+            passes: 0,
+        };
+        for y in 0..size {
+            for x in 0..size {
+                let idx = playout.to_index(Point::new(x as i32, y as i32));
+                playout.cells[idx] = EMPTY;
+                playout.add_empty(idx);
+            }
+        }
+        for y in 0..size {
+            for x in 0..size {
+                let p = Point::new(x as i32, y as i32);
+                let color = to_color(board.get(p));
+                if color != EMPTY {
+                    playout.add_stone(playout.to_index(p), color);
+                }
+            }
+        }
+        playout
+    }
+
+    fn to_index(&self, p: Point) -> usize {
+        (p.y as usize + 1) * self.stride + p.x as usize + 1
+    }
+
+    fn to_point(&self, idx: usize) -> Point {
+        Point::new(
+            (idx % self.stride) as i32 - 1,
+            (idx / self.stride) as i32 - 1,
+        )
+    }
+
+    fn get_neighbours(&self, idx: usize) -> [usize; 4] {
+        [idx - 1, idx + 1, idx - self.stride, idx + self.stride]
+    }
+
+    fn add_empty(&mut self, idx: usize) {
+        self.empty_pos[idx] = self.empty.len();
+        self.empty.push(idx);
+    }
+
+    fn remove_empty(&mut self, idx: usize) {
+        let pos = self.empty_pos[idx];
+        let last = self.empty[self.empty.len() - 1];
+        self.empty[pos] = last;
+        self.empty_pos[last] = pos;
+        self.empty.pop();
+    }
+
+    fn add_liberty(&mut self, group: usize, idx: usize) {
+        self.libs[group] += 1;
+        self.lib_sum[group] += idx as u64;
+        self.lib_sum_sq[group] += (idx * idx) as u64;
+    }
+
+    fn remove_liberty(&mut self, group: usize, idx: usize) {
+        self.libs[group] -= 1;
+        self.lib_sum[group] -= idx as u64;
+        self.lib_sum_sq[group] -= (idx * idx) as u64;
+    }
+
+    // with one real liberty every pseudo-liberty is the same point, which
+    // is exactly when libs * sum of squares equals sum squared
+    fn is_in_atari(&self, group: usize) -> bool {
+        let libs = self.libs[group] as u64;
+        libs > 0 && libs * self.lib_sum_sq[group] == self.lib_sum[group] * self.lib_sum[group]
+    }
+
+    pub fn get_turn(&self) -> CellState {
+        to_cell_state(self.turn)
+    }
+
+    pub fn get(&self, p: Point) -> CellState {
+        to_cell_state(self.cells[self.to_index(p)])
+    }
+
+    /// Whether the player to move may play at `p`.
+    pub fn is_legal(&self, p: Point) -> bool {
+        self.is_legal_index(self.to_index(p))
+    }
+
+    fn is_legal_index(&self, idx: usize) -> bool {
+        if self.cells[idx] != EMPTY || self.ko == Some(idx) {
+            return false;
+        }
+        for n in self.get_neighbours(idx).iter() {
+            let color = self.cells[*n];
+            if color == EMPTY {
+                return true;
+            }
+            if color == self.turn && !self.is_in_atari(self.head[*n]) {
+                return true;
+            }
+            if color != self.turn && color != BORDER && self.is_in_atari(self.head[*n]) {
+                return true;
+            }
+        }
+        false
+    }
+
+    // the same rule as `Board::is_false_eye`, on the flat board
+    fn is_eye_index(&self, idx: usize, color: u8) -> bool {
+        if self
+            .get_neighbours(idx)
+            .iter()
+            .any(|&n| self.cells[n] != color && self.cells[n] != BORDER)
+        {
+            return false;
+        }
+        let s = self.stride;
+        let mut borders = 0;
+        let mut opponents = 0;
+        for &d in [idx - s - 1, idx - s + 1, idx + s - 1, idx + s + 1].iter() {
+            match self.cells[d] {
+                BORDER => borders += 1,
+                EMPTY => {}
+                c if c != color => opponents += 1,
+                _ => {}
+            }
+        }
+        if borders > 0 {
+            opponents == 0
+        } else {
+            opponents <= 1
+        }
+    }
+
+    /// Plays a legal move for the player to move, or passes for `None`.
+    pub fn play(&mut self, mv: Option<Point>) {
+        match mv {
+            Some(p) => {
+                let idx = self.to_index(p);
+                self.put_stone(idx);
+                self.passes = 0;
+            }
+            None => {
+                self.ko = None;
+                self.passes += 1;
+            }
+        }
+        self.turn = if self.turn == BLACK { WHITE } else { BLACK };
+    }
+
+    fn put_stone(&mut self, idx: usize) {
+        let opponent = if self.turn == BLACK { WHITE } else { BLACK };
+        self.ko = None;
+        self.add_stone(idx, self.turn);
+
+        let mut captured = 0;
+        let mut captured_at = idx;
+        for &n in self.get_neighbours(idx).iter() {
+            if self.cells[n] == opponent && self.libs[self.head[n]] == 0 {
+                captured_at = self.head[n];
+                captured += self.remove_group(self.head[n]);
+            }
+        }
+
+        let group = self.head[idx];
+        if captured == 1 && self.stones[group] == 1 && self.is_in_atari(group) {
+            self.ko = Some(captured_at);
+        }
+    }
+
+    // puts a stone down and joins it to its neighbours without capturing
+    // anything, which is also how the starting position is copied over
+    fn add_stone(&mut self, idx: usize, color: u8) {
+        self.cells[idx] = color;
+        self.remove_empty(idx);
+        self.head[idx] = idx;
+        self.next[idx] = idx;
+        self.stones[idx] = 1;
+        self.libs[idx] = 0;
+        self.lib_sum[idx] = 0;
+        self.lib_sum_sq[idx] = 0;
+
+        let neighbours = self.get_neighbours(idx);
+        for &n in neighbours.iter() {
+            match self.cells[n] {
+                EMPTY => self.add_liberty(idx, n),
+                BORDER => {}
+                _ => self.remove_liberty(self.head[n], idx),
+            }
+        }
+        for &n in neighbours.iter() {
+            if self.cells[n] == color && self.head[n] != self.head[idx] {
+                self.merge(self.head[idx], self.head[n]);
+            }
+        }
+    }
+
+    fn merge(&mut self, a: usize, b: usize) {
+        let (big, small) = if self.stones[a] >= self.stones[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let mut stone = small;
+        loop {
+            self.head[stone] = big;
+            stone = self.next[stone];
+            if stone == small {
+                break;
+            }
+        }
+        self.next.swap(big, small);
+        self.stones[big] += self.stones[small];
+        self.libs[big] += self.libs[small];
+        self.lib_sum[big] += self.lib_sum[small];
+        self.lib_sum_sq[big] += self.lib_sum_sq[small];
+    }
+
+    fn remove_group(&mut self, group: usize) -> u32 {
+        let mut removed = Vec::with_capacity(self.stones[group] as usize);
+        let mut stone = group;
+        loop {
+            removed.push(stone);
+            stone = self.next[stone];
+            if stone == group {
+                break;
+            }
+        }
+        for &stone in &removed {
+            self.cells[stone] = EMPTY;
+            self.add_empty(stone);
+        }
+        for &stone in &removed {
+            for &n in self.get_neighbours(stone).iter() {
+                if self.cells[n] == BLACK || self.cells[n] == WHITE {
+                    self.add_liberty(self.head[n], stone);
+                }
+            }
+        }
+        removed.len() as u32
+    }
+
+    /// A random legal move for the player to move that doesn't fill their
+    /// own eye, or `None` if there isn't one. The search starts at a random
+    /// empty point and takes the first that will do.
+    pub fn get_random_move(&self, rng: &mut Rng) -> Option<Point> {
+        let count = self.empty.len();
+        if count == 0 {
+            return None;
+        }
+        let start = rng.below(count);
+        for i in 0..count {
+            let idx = self.empty[(start + i) % count];
+            if !self.is_eye_index(idx, self.turn) && self.is_legal_index(idx) {
+                return Some(self.to_point(idx));
+            }
+        }
+        None
+    }
+
+    /// Who owns each point by area, row by row from the top: its stone, or
+    /// for an empty point whoever has stones on every side of it. Only
+    /// meaningful once the playout has filled the board.
+    pub fn get_ownership(&self) -> Vec<CellState> {
+        let mut ownership = Vec::with_capacity(self.size * self.size);
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let idx = self.to_index(Point::new(x as i32, y as i32));
+                let owner = match self.cells[idx] {
+                    EMPTY => {
+                        let mut seen = EMPTY;
+                        for &n in self.get_neighbours(idx).iter() {
+                            match self.cells[n] {
+                                BORDER | EMPTY => {}
+                                c if seen == EMPTY || seen == c => seen = c,
+                                _ => seen = BORDER,
+                            }
+                        }
+                        seen
+                    }
+                    c => c,
+                };
+                ownership.push(to_cell_state(owner));
+            }
+        }
+        ownership
+    }
+
+    /// The score of `ownership`, from [`PlayoutBoard::get_ownership`], with
+    /// komi.
+    pub fn get_score(&self, ownership: &[CellState]) -> Score {
+        let mut black = 0.0;
+        let mut white = self.komi;
+        for &owner in ownership {
+            match owner {
+                CellState::Black => black += 1.0,
+                CellState::White => white += 1.0,
+                CellState::None => {}
+            }
+        }
+        Score { black, white }
+    }
+
+    /// The winner by area. Only meaningful once the playout has filled the
+    /// board.
+    pub fn get_winner(&self) -> CellState {
+        self.get_score(&self.get_ownership()).get_winner()
+    }
+
+    /// Plays random moves until both players pass and returns the winner.
+    /// The moves are appended to `sequence`.
+    pub fn playout(
+        &mut self,
+        rng: &mut Rng,
+        sequence: &mut Vec<(CellState, Option<Point>)>,
+    ) -> CellState {
+        let limit = self.size * self.size * 3;
+        for _ in 0..limit {
+            if self.passes >= 2 {
+                break;
+            }
+            let player = self.get_turn();
+            let mv = self.get_random_move(rng);
+            self.play(mv);
+            sequence.push((player, mv));
+        }
+        self.get_winner()
+    }
+}
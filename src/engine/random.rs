@@ -0,0 +1,49 @@
+use crate::engine::*;
+use crate::go_core::*;
+
+/// Plays a uniformly random legal move, never filling one of its own
+/// single-point eyes, and passes when nothing else is left.
+pub struct RandomPlayer {
+    rng: Rng,
+}
+
+impl RandomPlayer {
+    pub fn new() -> RandomPlayer {
+        RandomPlayer {
+            rng: Rng::from_time(),
+        }
+    }
+
+    pub fn with_seed(seed: u64) -> RandomPlayer {
+        RandomPlayer {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Default for RandomPlayer {
+    fn default() -> Self {
+        RandomPlayer::new()
+    }
+}
+
+impl MoveGenerator for RandomPlayer {
+    fn get_name(&self) -> &str {
+        "Random"
+    }
+
+    fn generate_move(&mut self, board: &Board) -> Option<Point> {
+        get_random_move(board, &mut self.rng)
+    }
+}
+
+/// A uniformly random legal move for the player to move on `board` that
+/// doesn't fill their own eye, or `None` if there isn't one.
+pub fn get_random_move(board: &Board, rng: &mut Rng) -> Option<Point> {
+    let player = board.get_turn();
+    let mut candidates = board.get_empty_points();
+    rng.shuffle(&mut candidates);
+    candidates
+        .into_iter()
+        .find(|&p| !is_eye(board, p, player) && board.can_place(p))
+}
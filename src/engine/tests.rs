@@ -0,0 +1,401 @@
+use crate::engine::*;
+use crate::go_core::*;
+use crate::sgf::*;
+
+#[test]
+fn test_is_eye() {
+    let board = Board::from_str(
+        "
+        .b.w.
+        b.b..
+        .b...
+        .....
+        .....
+        ",
+        CellState::White,
+    );
+    assert!(is_eye(&board, Point::new(0, 0), CellState::Black));
+    assert!(is_eye(&board, Point::new(1, 1), CellState::Black));
+    assert!(!is_eye(&board, Point::new(1, 1), CellState::White));
+    assert!(!is_eye(&board, Point::new(2, 0), CellState::Black));
+
+    // one opposing diagonal makes an edge eye false but not a central one
+    let board = Board::from_str(
+        "
+        .b.b.
+        .wbb.
+        .b.b.
+        ..b..
+        .....
+        ",
+        CellState::Black,
+    );
+    assert!(!is_eye(&board, Point::new(2, 0), CellState::Black));
+    assert!(is_eye(&board, Point::new(2, 2), CellState::Black));
+}
+
+#[test]
+fn test_random_player_keeps_its_eyes() {
+    // the only empty points are black's own eyes, so black passes
+    let board = Board::from_str(
+        "
+        .bbbb
+        bbbbb
+        bb.bb
+        bbbbb
+        bbbb.
+        ",
+        CellState::Black,
+    );
+    let mut player = RandomPlayer::with_seed(1);
+    assert_eq!(player.generate_move(&board), None);
+}
+
+#[test]
+fn test_random_game() {
+    let mut board = Board::new(9, RuleSet::chinese());
+    let mut black = RandomPlayer::with_seed(1);
+    let mut white = RandomPlayer::with_seed(2);
+    for _ in 0..1000 {
+        if board.is_game_over() {
+            break;
+        }
+        let mv = if board.get_turn() == CellState::Black {
+            black.generate_move(&board)
+        } else {
+            white.generate_move(&board)
+        };
+        if let Some(p) = mv {
+            assert!(board.can_place(p));
+        }
+        board.play(mv);
+    }
+    assert!(board.is_game_over());
+}
+
+// a single thread keeps the search repeatable
+fn get_mcts_config(playouts: usize) -> MctsConfig {
+    MctsConfig {
+        budget: SearchBudget::Playouts(playouts),
+        threads: 1,
+        seed: Some(1),
+        ..MctsConfig::default()
+    }
+}
+
+#[test]
+fn test_mcts_captures() {
+    // a capturing race where both groups' last liberty is (2, 0): whoever
+    // plays there takes the other group, so it's the only move that wins
+    let board = Board::from_str(
+        "
+        bb.ww
+        bbwww
+        bwwbb
+        wwb..
+        bbb..
+        ",
+        CellState::Black,
+    );
+    let mut player = MctsPlayer::new(get_mcts_config(2000));
+    assert_eq!(player.generate_move(&board), Some(Point::new(2, 0)));
+
+    let stats = player.get_stats().unwrap();
+    assert_eq!(stats.playouts, 2000);
+    assert!(stats.winrate > 0.5);
+}
+
+#[test]
+fn test_mcts_analysis() {
+    // the same race as above: Black wins by capturing at (2, 0)
+    let board = Board::from_str(
+        "
+        bb.ww
+        bbwww
+        bwwbb
+        wwb..
+        bbb..
+        ",
+        CellState::Black,
+    );
+    let mut player = MctsPlayer::new(get_mcts_config(2000));
+    let analysis = player.analyze(&board);
+    assert_eq!(analysis.player, CellState::Black);
+    assert_eq!(analysis.visits, 2000);
+    assert!(analysis.winrate > 0.5);
+
+    let best = &analysis.moves[0];
+    assert_eq!(best.mv, Some(Point::new(2, 0)));
+    assert!(best.winrate > 0.5 && best.score_lead > 0.0);
+    assert_eq!(best.pv[0], best.mv);
+    assert!(analysis
+        .moves
+        .windows(2)
+        .all(|m| m[0].visits >= m[1].visits));
+    let total: u32 = analysis.moves.iter().map(|m| m.visits).sum();
+    assert!(total < 2000);
+
+    // Black's corner group survives the race
+    assert_eq!(analysis.ownership.len(), 25);
+    assert!(analysis.ownership[5 + 1] > 0.5);
+    assert!(analysis.ownership.iter().all(|o| (-1.0..=1.0).contains(o)));
+    assert_eq!(player.get_analysis(), Some(analysis));
+}
+
+#[test]
+fn test_mcts_reuses_tree() {
+    let mut board = Board::new(5, RuleSet::chinese());
+    let mut player = MctsPlayer::new(MctsConfig {
+        threads: 2,
+        ..get_mcts_config(500)
+    });
+
+    // the best move has the most playouts, so searching for the other side
+    // afterwards starts from its subtree
+    let mv = player.generate_move(&board);
+    board.play(mv);
+    player.generate_move(&board);
+    let stats = player.get_stats().unwrap();
+    assert_eq!(stats.playouts, 500);
+    assert!(stats.reused > 0);
+
+    // moving twice in a row, as GTP's `genmove` allows, leaves the replies
+    // searched for the other side behind
+    let mv = player.generate_move(&board);
+    board.play(mv);
+    board.set_turn(board.get_turn().get_other_player());
+    let second = player.generate_move(&board);
+    assert_eq!(player.get_stats().unwrap().reused, 0);
+    assert_eq!(player.get_analysis().unwrap().player, board.get_turn());
+    assert!(second.is_none_or(|p| board.can_place(p)));
+
+    // a different game starts from scratch
+    let board = Board::new(5, RuleSet::chinese());
+    player.generate_move(&board);
+    assert_eq!(player.get_stats().unwrap().reused, 0);
+}
+
+#[test]
+fn test_playout_board_matches_board() {
+    // both boards play the same random game under simple ko
+    let mut board = Board::new(9, RuleSet::japanese());
+    let mut playout = PlayoutBoard::new(&board);
+    let mut rng = Rng::new(7);
+    for _ in 0..300 {
+        let mv = playout.get_random_move(&mut rng);
+        if let Some(p) = mv {
+            assert!(board.can_place(p));
+        }
+        board.play(mv);
+        playout.play(mv);
+        for y in 0..9 {
+            for x in 0..9 {
+                let p = Point::new(x, y);
+                assert_eq!(playout.get(p), board.get(p));
+                assert_eq!(playout.is_legal(p), board.can_place(p));
+            }
+        }
+        assert_eq!(playout.get_turn(), board.get_turn());
+    }
+}
+
+#[test]
+fn test_patterns() {
+    let library = PatternLibrary::parse(
+        "
+        # black to move cuts
+        pattern cut 2.0
+        X O ?
+        O * ?
+        ? ? ?
+
+        pattern first-line 0.5 either
+        ? . ?
+        . * .
+        - - -
+
+        pattern mouth 1.0
+            ?
+          X . X
+        ? . * . ?
+          . X .
+            ?
+        ",
+    )
+    .unwrap();
+    assert_eq!(library.get_patterns().len(), 3);
+
+    let board = Board::new(5, RuleSet::japanese());
+    let names = |p: Point, player: CellState| -> Vec<String> {
+        library
+            .find(&board, p, player)
+            .into_iter()
+            .map(|pattern| pattern.name.clone())
+            .collect()
+    };
+    // on every side of the board but not in the corner
+    for p in [(2, 4), (0, 2), (2, 0), (4, 2)].iter() {
+        assert_eq!(
+            names(Point::new(p.0, p.1), CellState::Black),
+            vec!["first-line"]
+        );
+    }
+    assert!(names(Point::new(0, 4), CellState::Black).is_empty());
+    assert!(names(Point::new(2, 2), CellState::Black).is_empty());
+
+    // white cuts
+    let board = Board::from_str(
+        "
+        .....
+        ..wb.
+        ..b..
+        .....
+        .....
+        ",
+        CellState::Black,
+    );
+    let cut = Point::new(3, 2);
+    assert_eq!(library.find(&board, cut, CellState::White)[0].name, "cut");
+    assert!(library.find(&board, cut, CellState::Black).is_empty());
+    assert_eq!(library.get_weight(&board, cut, CellState::White), Some(2.0));
+    assert_eq!(library.get_weight(&board, cut, CellState::Black), None);
+
+    let board = Board::from_str(
+        "
+        .......
+        .......
+        ...b...
+        .......
+        ..b.b..
+        .......
+        .......
+        ",
+        CellState::Black,
+    );
+    assert_eq!(
+        library.find(&board, Point::new(3, 3), CellState::Black)[0].name,
+        "mouth"
+    );
+
+    let bundled = include_str!("../../resources/patterns.txt");
+    assert!(!PatternLibrary::parse(bundled)
+        .unwrap()
+        .get_patterns()
+        .is_empty());
+
+    assert!(PatternLibrary::parse("pattern bad 1.0\n...\n...\n...").is_err());
+    assert!(PatternLibrary::parse("pattern bad 1.0\n???\n?*?\n???").is_err());
+}
+
+#[test]
+fn test_opening_book() {
+    // the second game opens in another corner and transposes into the first
+    let games = parse_sgf(
+        "(;SZ[9]RE[B+3.5];B[cc];W[gg];B[cg];W[gc])
+         (;SZ[9]RE[W+R];B[gc];W[cg])",
+    )
+    .unwrap();
+    let mut book = OpeningBook::new();
+    for game in &games {
+        book.add_game(game, 3).unwrap();
+    }
+    assert_eq!(book.get_position_count(), 3);
+
+    // every 3-3 point on the empty board
+    let mut board = Board::new(9, RuleSet::japanese());
+    let moves = book.get_moves(&board);
+    assert_eq!(moves.len(), 4);
+    let stats = BookStats {
+        games: 2,
+        wins: 1,
+        losses: 1,
+    };
+    assert!(moves.iter().all(|m| m.stats == stats));
+    assert_eq!(stats.get_win_rate(), Some(0.5));
+
+    board.place(Point::new(6, 6));
+    let moves = book.get_moves(&board);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].mv, Point::new(2, 2));
+    assert_eq!(moves[0].stats.games, 2);
+
+    board.place(Point::new(2, 2));
+    let moves: Vec<Point> = book.get_moves(&board).iter().map(|m| m.mv).collect();
+    assert_eq!(moves.len(), 2);
+    assert!(moves.contains(&Point::new(6, 2)) && moves.contains(&Point::new(2, 6)));
+    // past the depth the games were read to
+    board.place(Point::new(2, 6));
+    assert!(book.get_moves(&board).is_empty());
+
+    let saved = OpeningBook::parse(&book.write()).unwrap();
+    assert_eq!(saved.write(), book.write());
+    assert!(OpeningBook::parse("9 B 0 cc 1 1 0").is_err());
+
+    let mut player = BookPlayer::new(book, Box::new(RandomPlayer::with_seed(1)));
+    let empty = Board::new(9, RuleSet::japanese());
+    let mv = player.generate_move(&empty).unwrap();
+    assert!(mv.x % 4 == 2 && mv.y % 4 == 2);
+    assert!(player.generate_move(&board).is_some());
+}
+
+#[test]
+fn test_tournament() {
+    let config = TournamentConfig {
+        games: 4,
+        size: 5,
+        komi: Some(0.5),
+        threads: 2,
+        ..TournamentConfig::default()
+    };
+    let random = || -> Box<dyn MoveGenerator> { Box::new(RandomPlayer::with_seed(7)) };
+    let finished = std::sync::Mutex::new(0);
+    let result = run_tournament(&config, &random, &random, &|_| {
+        *finished.lock().unwrap() += 1;
+    });
+    assert_eq!(*finished.lock().unwrap(), 4);
+    assert_eq!(result.games.len(), 4);
+    for (i, game) in result.games.iter().enumerate() {
+        assert_eq!(game.index, i);
+        let colour = if i % 2 == 0 {
+            CellState::Black
+        } else {
+            CellState::White
+        };
+        assert_eq!(game.first_colour, colour);
+        let root = &parse_sgf(&game.sgf).unwrap()[0];
+        assert_eq!(root.get("PB"), Some("Random"));
+        assert_eq!(root.get("KM"), Some("0.5"));
+        assert_eq!(root.get("RE"), Some(game.score.to_string().as_str()));
+    }
+    let (wins, draws, losses) = result.get_record();
+    assert_eq!(wins + draws + losses, 4);
+
+    // three wins and a loss
+    let mut result = result;
+    for (game, winner) in result
+        .games
+        .iter_mut()
+        .zip([true, true, true, false].iter())
+    {
+        game.score = if (game.first_colour == CellState::Black) == *winner {
+            Score {
+                black: 10.0,
+                white: 0.5,
+            }
+        } else {
+            Score {
+                black: 0.0,
+                white: 0.5,
+            }
+        };
+    }
+    assert_eq!(result.get_record(), (3, 0, 1));
+    assert_eq!(result.get_win_rate(), 0.75);
+    let (low, high) = result.get_confidence_interval();
+    assert!(low > 0.0 && low < 0.75 && high > 0.75 && high < 1.0);
+    let (elo, (elo_low, elo_high)) = result.get_elo_difference();
+    assert!((elo - 190.8).abs() < 0.1);
+    assert!(elo_low < elo && elo < elo_high);
+    assert_eq!(get_elo_difference(0.5), 0.0);
+    assert_eq!(get_elo_difference(1.0), f64::INFINITY);
+}
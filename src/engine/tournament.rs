@@ -0,0 +1,200 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::engine::*;
+use crate::go_core::*;
+use crate::sgf::*;
+
+// the z score for a 95% confidence interval
+const CONFIDENCE_Z: f64 = 1.96;
+
+/// Creates an engine for one of a tournament's threads.
+pub type EngineFactory<'a> = dyn Fn() -> Box<dyn MoveGenerator> + Sync + 'a;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TournamentConfig {
+    pub games: usize,
+    pub size: usize,
+    pub rules: RuleSet,
+    /// The komi, or `None` for the rules' own.
+    pub komi: Option<f32>,
+    /// Games still going after this many moves are scored as they stand,
+    /// or by default after three moves for every point of the board.
+    pub max_moves: Option<usize>,
+    /// How many games to play at once, each thread with its own engines.
+    pub threads: usize,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        TournamentConfig {
+            games: 10,
+            size: 9,
+            rules: RuleSet::chinese(),
+            komi: None,
+            max_moves: None,
+            threads: 1,
+        }
+    }
+}
+
+/// A finished tournament game. The first engine plays Black in the even
+/// numbered games (counting from zero) and White in the rest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameResult {
+    pub index: usize,
+    /// The colour the first engine played.
+    pub first_colour: CellState,
+    pub score: Score,
+    /// The game's SGF record, with the players and the result.
+    pub sgf: String,
+}
+
+impl GameResult {
+    pub fn get_winner(&self) -> CellState {
+        self.score.get_winner()
+    }
+}
+
+/// The games of a tournament, in order, and what they say about the first
+/// engine against the second.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TournamentResult {
+    pub games: Vec<GameResult>,
+}
+
+impl TournamentResult {
+    /// The first engine's wins, draws and losses.
+    pub fn get_record(&self) -> (usize, usize, usize) {
+        let mut record = (0, 0, 0);
+        for game in &self.games {
+            match game.get_winner() {
+                CellState::None => record.1 += 1,
+                winner if winner == game.first_colour => record.0 += 1,
+                _ => record.2 += 1,
+            }
+        }
+        record
+    }
+
+    /// The first engine's share of the points, a draw counting as half a
+    /// win, or a half if no games were played.
+    pub fn get_win_rate(&self) -> f64 {
+        if self.games.is_empty() {
+            return 0.5;
+        }
+        let (wins, draws, _) = self.get_record();
+        (wins as f64 + draws as f64 / 2.0) / self.games.len() as f64
+    }
+
+    /// The 95% confidence interval for the first engine's win rate, as a
+    /// Wilson score interval so that it stays sensible for small numbers
+    /// of games and lopsided results.
+    pub fn get_confidence_interval(&self) -> (f64, f64) {
+        let n = self.games.len() as f64;
+        if n == 0.0 {
+            return (0.0, 1.0);
+        }
+        let p = self.get_win_rate();
+        let z2 = CONFIDENCE_Z * CONFIDENCE_Z;
+        let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let spread =
+            CONFIDENCE_Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+        ((centre - spread).max(0.0), (centre + spread).min(1.0))
+    }
+
+    /// How much stronger the first engine is in Elo points, with the
+    /// confidence interval. These are infinite when an engine won every
+    /// game.
+    pub fn get_elo_difference(&self) -> (f64, (f64, f64)) {
+        let (low, high) = self.get_confidence_interval();
+        (
+            get_elo_difference(self.get_win_rate()),
+            (get_elo_difference(low), get_elo_difference(high)),
+        )
+    }
+}
+
+/// The Elo difference that gives the stronger player an expected score of
+/// `win_rate`.
+pub fn get_elo_difference(win_rate: f64) -> f64 {
+    -400.0 * (1.0 / win_rate - 1.0).log10()
+}
+
+/// Plays `config.games` games between engines from `first` and `second`,
+/// alternating colours, calling `on_game` as each one finishes (not
+/// necessarily in order when playing on several threads). Moves an engine
+/// gets wrong are played as passes, and finished games are scored with
+/// the stones [`Board::get_dead_stones`] finds removed.
+pub fn run_tournament(
+    config: &TournamentConfig,
+    first: &EngineFactory<'_>,
+    second: &EngineFactory<'_>,
+    on_game: &(dyn Fn(&GameResult) + Sync),
+) -> TournamentResult {
+    let next_game = AtomicUsize::new(0);
+    let games = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..config.threads.clamp(1, config.games.max(1)) {
+            scope.spawn(|| {
+                let mut engines = (first(), second());
+                loop {
+                    let index = next_game.fetch_add(1, Ordering::SeqCst);
+                    if index >= config.games {
+                        break;
+                    }
+                    let result = play_game(config, index, &mut engines.0, &mut engines.1);
+                    on_game(&result);
+                    games.lock().unwrap().push(result);
+                }
+            });
+        }
+    });
+    let mut games = games.into_inner().unwrap();
+    games.sort_by_key(|game| game.index);
+    TournamentResult { games }
+}
+
+fn play_game(
+    config: &TournamentConfig,
+    index: usize,
+    first: &mut Box<dyn MoveGenerator>,
+    second: &mut Box<dyn MoveGenerator>,
+) -> GameResult {
+    let mut board = Board::new(config.size, config.rules);
+    if let Some(komi) = config.komi {
+        board.set_komi(komi);
+    }
+    let (first_colour, black, white) = if index.is_multiple_of(2) {
+        (CellState::Black, first, second)
+    } else {
+        (CellState::White, second, first)
+    };
+    let max_moves = config.max_moves.unwrap_or(config.size * config.size * 3);
+    while !board.is_game_over() && board.get_moves().len() < max_moves {
+        let engine = match board.get_turn() {
+            CellState::Black => &mut *black,
+            _ => &mut *white,
+        };
+        let mv = engine.generate_move(&board);
+        board.play(mv.filter(|&p| board.can_place(p)));
+    }
+
+    let score = board.score(&board.get_dead_stones());
+    let result = score.to_string();
+    let sgf = write_game_with_properties(
+        &board,
+        &[
+            ("PB", black.get_name()),
+            ("PW", white.get_name()),
+            ("RE", &result),
+        ],
+    );
+    GameResult {
+        index,
+        first_colour,
+        score,
+        sgf,
+    }
+}
@@ -0,0 +1,1182 @@
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::engine::*;
+use crate::frontend::*;
+use crate::go_core::*;
+use crate::net::*;
+use crate::sgf::*;
+
+// a computer player choosing its move on another thread, which hands
+// itself back along with the move
+type Thinking = JoinHandle<(Box<dyn MoveGenerator>, Option<Point>)>;
+
+const GHOST_ALPHA: u8 = 160;
+const STATUS_COLOR: Color = [0, 0, 0, 255];
+const STATUS_SCALE: u32 = 2;
+const LABEL_SCALE: u32 = 2;
+const OWNERSHIP_ALPHA: f32 = 180.0;
+const BOOK_COLOR: Color = [0, 160, 0, 255];
+const BOOK_ALPHA: f32 = 160.0;
+const CLOCK_IDLE_COLOR: Color = [128, 128, 128, 255];
+const CLOCK_FLAGGED_COLOR: Color = [200, 0, 0, 255];
+// how long chat and server errors stay in the status line
+const NOTICE_TIME: Duration = Duration::from_secs(5);
+// the width in tiles of the panel beside the board that shows the comments
+// while reviewing a game
+const COMMENT_WIDTH: u32 = 10;
+const COMMENT_SCALE: u32 = 2;
+
+/// Owns the game and the interaction state shared by every front end:
+/// input events go in, a list of sprites to draw comes out.
+pub struct GameController {
+    game: Board,
+    settings: NewGameSettings,
+    black_engine: Option<Box<dyn MoveGenerator>>,
+    white_engine: Option<Box<dyn MoveGenerator>>,
+    thinking: Option<Thinking>,
+    practice: Option<Practice>,
+    review: Option<Review>,
+    dialog: Option<NewGameDialog>,
+    tile_size: u32,
+    mouse: Option<Point>,
+    show_overlay: bool,
+    show_influence: bool,
+    // the estimate for the position with the given hash and move count,
+    // worked out again whenever the board changes
+    estimate: Option<(u64, usize, Estimate)>,
+    // the overlay's ladder against the group at the point, and the stones
+    // counted as dead once the game is over, kept the same way
+    ladder: Option<(Point, u64, usize, Option<Ladder>)>,
+    dead_stones: Option<(u64, usize, Vec<Point>)>,
+    book: Option<OpeningBook>,
+    show_book: bool,
+    // the game clock, if the game has a time limit
+    clock: Option<GameClock>,
+    // the server, when playing a game hosted on the network
+    remote: Option<GameClient>,
+    // the last chat message or error from the server and when it came
+    notice: Option<(String, Instant)>,
+    running: bool,
+}
+
+impl GameController {
+    pub fn new(game: Board, tile_size: u32) -> GameController {
+        let settings = NewGameSettings {
+            size: game.get_size(),
+            handicap: game.get_handicap(),
+            rules: *game.get_rules(),
+            ..NewGameSettings::default()
+        };
+        GameController {
+            game,
+            settings,
+            black_engine: None,
+            white_engine: None,
+            thinking: None,
+            practice: None,
+            review: None,
+            dialog: None,
+            tile_size,
+            mouse: None,
+            show_overlay: false,
+            show_influence: false,
+            estimate: None,
+            ladder: None,
+            dead_stones: None,
+            book: None,
+            show_book: false,
+            clock: None,
+            remote: None,
+            notice: None,
+            running: true,
+        }
+    }
+
+    pub fn get_game(&self) -> &Board {
+        &self.game
+    }
+
+    pub fn get_tile_size(&self) -> u32 {
+        self.tile_size
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn set_show_overlay(&mut self, show: bool) {
+        self.show_overlay = show;
+    }
+
+    /// Shows the book's moves for the position as candidate markers.
+    pub fn set_book(&mut self, book: OpeningBook) {
+        self.book = Some(book);
+        self.show_book = true;
+    }
+
+    /// Hands `player`'s moves in the current game over to `player_type`.
+    pub fn set_player(&mut self, player: CellState, player_type: PlayerType) {
+        match player {
+            CellState::White => self.settings.white = player_type,
+            _ => self.settings.black = player_type,
+        }
+        *self.get_engine(player) = player_type.create_engine();
+        if player == self.game.get_turn() {
+            self.thinking = None;
+        }
+    }
+
+    /// Plays the current game and every new one on the clock. The clock
+    /// starts once the handicap stones are down.
+    pub fn set_time_control(&mut self, control: TimeControl) {
+        self.settings.time = control;
+        self.clock = create_clock(control);
+    }
+
+    pub fn get_clock(&self) -> Option<&GameClock> {
+        self.clock.as_ref()
+    }
+
+    /// Plays the game hosted by the server `client` is connected to
+    /// instead of a local one. Moves are sent to the server and only
+    /// played once it has accepted them, and a spectator's clicks are
+    /// ignored.
+    pub fn connect(&mut self, client: GameClient) {
+        self.settings.black = PlayerType::Human;
+        self.settings.white = PlayerType::Human;
+        self.black_engine = None;
+        self.white_engine = None;
+        self.thinking = None;
+        self.practice = None;
+        self.review = None;
+        self.clock = None;
+        if let Some(board) = client.get_board() {
+            self.game = board.clone();
+        }
+        self.remote = Some(client);
+    }
+
+    // plays a move for the player to move, on the clock if there is one or
+    // by sending it to the server
+    fn play(&mut self, mv: Option<Point>) {
+        if let Some(remote) = &mut self.remote {
+            if let Err(e) = remote.send(ClientMessage::Play(mv)) {
+                eprintln!("{}", e);
+            }
+            return;
+        }
+        match &mut self.clock {
+            Some(clock) => {
+                clock.play(&mut self.game, mv, Instant::now());
+            }
+            None => self.game.play(mv),
+        }
+    }
+
+    // resigns for the player to move
+    fn resign(&mut self) {
+        match &mut self.remote {
+            Some(remote) => {
+                if let Err(e) = remote.send(ClientMessage::Resign) {
+                    eprintln!("{}", e);
+                }
+            }
+            None => self
+                .game
+                .forfeit(self.game.get_turn(), ForfeitReason::Resignation),
+        }
+    }
+
+    fn get_engine(&mut self, player: CellState) -> &mut Option<Box<dyn MoveGenerator>> {
+        match player {
+            CellState::White => &mut self.white_engine,
+            _ => &mut self.black_engine,
+        }
+    }
+
+    fn is_human_turn(&self) -> bool {
+        if let Some(remote) = &self.remote {
+            return remote.get_colour() == Some(self.game.get_turn()) && !self.game.is_game_over();
+        }
+        if self.review.is_some() {
+            return !self.game.is_game_over();
+        }
+        match &self.practice {
+            Some(practice) => practice.get_problem().get_status() == ProblemStatus::Solving,
+            None => self.settings.get_player(self.game.get_turn()) == PlayerType::Human,
+        }
+    }
+
+    pub fn open_new_game_dialog(&mut self) {
+        self.dialog = Some(NewGameDialog::new(self.settings));
+    }
+
+    pub fn start_game(&mut self, settings: NewGameSettings) -> Result<(), String> {
+        self.game = settings.create_board()?;
+        self.remote = None;
+        self.settings = settings;
+        self.clock = create_clock(settings.time);
+        self.practice = None;
+        self.review = None;
+        self.black_engine = settings.black.create_engine();
+        self.white_engine = settings.white.create_engine();
+        // a search for the old game finishes in the background and is dropped
+        self.thinking = None;
+        self.mouse = None;
+        Ok(())
+    }
+
+    /// Switches to practising the problems in `practice` until a new game
+    /// is started. Both colours are played by hand.
+    pub fn start_practice(&mut self, practice: Practice) {
+        self.settings.black = PlayerType::Human;
+        self.settings.white = PlayerType::Human;
+        self.black_engine = None;
+        self.white_engine = None;
+        self.thinking = None;
+        self.mouse = None;
+        self.clock = None;
+        self.remote = None;
+        self.review = None;
+        self.game = practice.get_problem().get_board().clone();
+        self.practice = Some(practice);
+    }
+
+    /// Switches to stepping through the game record in `review` until a new
+    /// game is started. Clicking plays a move for whoever is to play,
+    /// following the record or adding a variation to it.
+    pub fn start_review(&mut self, review: Review) {
+        self.settings.black = PlayerType::Human;
+        self.settings.white = PlayerType::Human;
+        self.black_engine = None;
+        self.white_engine = None;
+        self.thinking = None;
+        self.mouse = None;
+        self.clock = None;
+        self.remote = None;
+        self.practice = None;
+        self.game = review.get_board().clone();
+        self.review = Some(review);
+    }
+
+    // handles the clicks and keys that mean something different while
+    // practising problems, returning whether the event was used
+    fn handle_practice_event(&mut self, event: InputEvent) -> bool {
+        let practice = match &mut self.practice {
+            Some(practice) => practice,
+            None => return false,
+        };
+        match event {
+            InputEvent::MouseDown { x, y } => {
+                self.mouse = self.point_at(x, y);
+                if let Some(p) = self.mouse {
+                    if self.is_human_turn() && self.game.can_place(p) {
+                        self.play_practice_move(Some(p));
+                    }
+                }
+            }
+            InputEvent::KeyDown(Key::Char('p')) => {
+                if self.is_human_turn() {
+                    self.play_practice_move(None);
+                }
+            }
+            InputEvent::KeyDown(Key::Char('r')) => practice.retry(),
+            InputEvent::KeyDown(Key::Right) => practice.next(),
+            InputEvent::KeyDown(Key::Left) => practice.previous(),
+            _ => return false,
+        }
+        if let Some(practice) = &self.practice {
+            self.game = practice.get_problem().get_board().clone();
+        }
+        true
+    }
+
+    // handles the clicks and keys for moving around the game record while
+    // reviewing, returning whether the event was used
+    fn handle_review_event(&mut self, event: InputEvent) -> bool {
+        let review = match &mut self.review {
+            Some(review) => review,
+            None => return false,
+        };
+        match event {
+            InputEvent::MouseDown { x, y } => {
+                self.mouse = self.point_at(x, y);
+                if let Some(p) = self.mouse {
+                    if self.game.can_place(p) {
+                        self.play_review_move(Some(p));
+                    }
+                }
+            }
+            InputEvent::KeyDown(Key::Char('p')) => {
+                if !self.game.is_game_over() {
+                    self.play_review_move(None);
+                }
+            }
+            InputEvent::KeyDown(Key::Char('s')) => {
+                let notice = match review.save() {
+                    Ok(()) => "SAVED".to_string(),
+                    Err(e) => e,
+                };
+                self.notice = Some((notice, Instant::now()));
+            }
+            InputEvent::KeyDown(Key::Left) => {
+                review.back();
+            }
+            InputEvent::KeyDown(Key::Right) => {
+                review.forward();
+            }
+            InputEvent::KeyDown(Key::Up) => review.previous_variation(),
+            InputEvent::KeyDown(Key::Down) => review.next_variation(),
+            // nobody resigns a game being reviewed
+            InputEvent::KeyDown(Key::Char('r')) => {}
+            InputEvent::KeyDown(Key::Home) => review.go_to_start(),
+            InputEvent::KeyDown(Key::End) => review.go_to_end(),
+            _ => return false,
+        }
+        if let Some(review) = &self.review {
+            self.game = review.get_board().clone();
+        }
+        true
+    }
+
+    fn play_review_move(&mut self, mv: Option<Point>) {
+        if let Some(review) = &mut self.review {
+            if let Err(e) = review.play(mv) {
+                eprintln!("couldn't play the move: {}", e);
+            }
+        }
+    }
+
+    fn play_practice_move(&mut self, mv: Option<Point>) {
+        if let Some(practice) = &mut self.practice {
+            if let Err(e) = practice.play(mv) {
+                eprintln!("couldn't play the move: {}", e);
+            }
+        }
+    }
+
+    /// Starts a computer player thinking if it's their turn, and plays
+    /// their move once they've chosen it, runs the clock and keeps the
+    /// territory estimate up to date while it's shown. Called once a frame,
+    /// after the events have been handled.
+    pub fn update(&mut self) {
+        self.update_remote();
+        self.update_clock();
+
+        if self.show_influence {
+            let key = (self.game.get_hash(), self.game.get_moves().len());
+            if self
+                .estimate
+                .as_ref()
+                .is_none_or(|(hash, moves, _)| (*hash, *moves) != key)
+            {
+                self.estimate = Some((key.0, key.1, self.game.get_estimate()));
+            }
+        }
+        self.update_ladder();
+        self.update_dead_stones();
+
+        if let Some(handle) = &self.thinking {
+            if !handle.is_finished() {
+                return;
+            }
+        }
+        if let Some(handle) = self.thinking.take() {
+            let player = self.game.get_turn();
+            match handle.join() {
+                Ok((engine, mv)) => {
+                    *self.get_engine(player) = Some(engine);
+                    // a bad engine move shouldn't hang the game
+                    let mv = mv.filter(|&p| self.game.can_place(p));
+                    self.play(mv);
+                }
+                Err(_) => {
+                    eprintln!("the computer player for {} crashed", player);
+                    self.set_player(player, PlayerType::Human);
+                }
+            }
+            return;
+        }
+
+        if self.dialog.is_some() || self.game.is_game_over() {
+            return;
+        }
+        let player = self.game.get_turn();
+        let budget = self.clock.as_ref().and_then(|c| c.get_move_budget(player));
+        if let Some(mut engine) = self.get_engine(player).take() {
+            if let Some(budget) = budget {
+                engine.set_time_budget(budget);
+            }
+            let game = self.game.clone();
+            self.thinking = Some(thread::spawn(move || {
+                let mv = engine.generate_move(&game);
+                (engine, mv)
+            }));
+        }
+    }
+
+    // reads the ladder under the mouse again only when the mouse moves to
+    // another point or the board changes
+    fn update_ladder(&mut self) {
+        let p = match self.mouse {
+            Some(p) if self.show_overlay => p,
+            _ => return,
+        };
+        let key = (p, self.game.get_hash(), self.game.get_moves().len());
+        if self
+            .ladder
+            .as_ref()
+            .is_none_or(|(q, hash, moves, _)| (*q, *hash, *moves) != key)
+        {
+            self.ladder = Some((key.0, key.1, key.2, self.game.read_ladder(p)));
+        }
+    }
+
+    fn update_dead_stones(&mut self) {
+        if !self.game.is_game_over() || self.game.get_forfeit().is_some() {
+            return;
+        }
+        let key = (self.game.get_hash(), self.game.get_moves().len());
+        if self
+            .dead_stones
+            .as_ref()
+            .is_none_or(|(hash, moves, _)| (*hash, *moves) != key)
+        {
+            self.dead_stones = Some((key.0, key.1, self.game.get_dead_stones()));
+        }
+    }
+
+    // the dead stones, once they've been worked out for this position
+    fn get_dead_stones(&self) -> Option<&[Point]> {
+        match &self.dead_stones {
+            Some((hash, moves, dead))
+                if *hash == self.game.get_hash() && *moves == self.game.get_moves().len() =>
+            {
+                Some(dead)
+            }
+            _ => None,
+        }
+    }
+
+    // takes in what the server has sent, keeping the board in step with it
+    fn update_remote(&mut self) {
+        let remote = match &mut self.remote {
+            Some(remote) => remote,
+            None => return,
+        };
+        let messages = remote.poll();
+        if messages.is_empty() {
+            return;
+        }
+        if let Some(board) = remote.get_board() {
+            self.game = board.clone();
+        }
+        for message in messages {
+            match message {
+                ServerMessage::Chat { name, text } => {
+                    self.notice = Some((format!("{}: {}", name, text), Instant::now()));
+                }
+                ServerMessage::Error(e) => self.notice = Some((e, Instant::now())),
+                _ => {}
+            }
+        }
+    }
+
+    // the clock runs for the player to move, except while the dialog is
+    // open or the handicap stones are being placed, and a player whose time
+    // runs out loses
+    fn update_clock(&mut self) {
+        let clock = match &mut self.clock {
+            Some(clock) => clock,
+            None => return,
+        };
+        let now = Instant::now();
+        if clock.check(&mut self.game, now) {
+            // a search for the player who ran out is dropped
+            self.thinking = None;
+        }
+        let paused =
+            self.dialog.is_some() || self.game.is_game_over() || self.game.is_placing_handicap();
+        if paused {
+            clock.stop(now);
+        } else if clock.get_running() != Some(self.game.get_turn()) {
+            clock.start(self.game.get_turn(), now);
+        }
+    }
+
+    // the board is drawn with a one tile margin all the way around, and
+    // the comments go to the right of it while reviewing
+    pub fn get_window_size(&self) -> (u32, u32) {
+        let size = (self.game.get_size() as u32 + 2) * self.tile_size;
+        match self.review {
+            Some(_) => (size + COMMENT_WIDTH * self.tile_size, size),
+            None => (size, size),
+        }
+    }
+
+    pub fn point_at(&self, x: i32, y: i32) -> Option<Point> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        let p = Point::new(x / self.tile_size as i32 - 1, y / self.tile_size as i32 - 1);
+        let size = self.game.get_size() as i32;
+        if p.x >= 0 && p.y >= 0 && p.x < size && p.y < size {
+            Some(p)
+        } else {
+            None
+        }
+    }
+
+    pub fn handle_event(&mut self, event: InputEvent) {
+        if let Some(dialog) = &mut self.dialog {
+            match event {
+                InputEvent::KeyDown(key) => match dialog.handle_key(key) {
+                    DialogResult::Open => {}
+                    DialogResult::Cancel => self.dialog = None,
+                    DialogResult::Start(settings) => {
+                        self.dialog = None;
+                        if let Err(e) = self.start_game(settings) {
+                            eprintln!("couldn't start a new game: {}", e);
+                        }
+                    }
+                },
+                InputEvent::Quit => self.running = false,
+                _ => {}
+            }
+            return;
+        }
+        if self.handle_practice_event(event) || self.handle_review_event(event) {
+            return;
+        }
+
+        match event {
+            InputEvent::MouseMove { x, y } => {
+                self.mouse = self.point_at(x, y);
+            }
+            InputEvent::MouseDown { x, y } => {
+                self.mouse = self.point_at(x, y);
+                if let Some(p) = self.mouse {
+                    if self.is_human_turn() && self.game.can_place(p) {
+                        self.play(Some(p));
+                    }
+                }
+            }
+            InputEvent::KeyDown(Key::Char('n')) => {
+                if self.remote.is_none() {
+                    self.open_new_game_dialog();
+                }
+            }
+            InputEvent::KeyDown(Key::Char('p')) => {
+                if self.is_human_turn() {
+                    self.play(None);
+                }
+            }
+            InputEvent::KeyDown(Key::Char('r')) => {
+                if self.is_human_turn() {
+                    self.resign();
+                }
+            }
+            InputEvent::KeyDown(Key::Char('i')) => {
+                self.show_influence = !self.show_influence;
+            }
+            InputEvent::KeyDown(Key::Char('b')) => {
+                self.show_book = !self.show_book;
+            }
+            InputEvent::KeyDown(Key::Escape) => {
+                self.running = false;
+            }
+            InputEvent::KeyDown(_) => {}
+            InputEvent::ToggleOverlay => {
+                self.show_overlay = !self.show_overlay;
+            }
+            InputEvent::Quit => {
+                self.running = false;
+            }
+        }
+    }
+
+    pub fn get_draw_list(&self) -> Vec<DrawCommand> {
+        let mut draw_list = Vec::new();
+        self.draw_board(&mut draw_list);
+        self.draw_stones(&mut draw_list);
+        self.draw_ghost(&mut draw_list);
+        if self.game.is_game_over() && self.game.get_forfeit().is_none() {
+            self.draw_dead_stones(&mut draw_list);
+        }
+        if self.show_influence {
+            self.draw_ownership(&mut draw_list);
+        }
+        self.draw_book(&mut draw_list);
+        self.draw_markup(&mut draw_list);
+        if self.show_overlay {
+            self.draw_overlay(&mut draw_list);
+        }
+        self.draw_status(&mut draw_list);
+        self.draw_clocks(&mut draw_list);
+        self.draw_comment(&mut draw_list);
+        if let Some(dialog) = &self.dialog {
+            let (width, height) = self.get_window_size();
+            dialog.draw(width, height, &mut draw_list);
+        }
+        draw_list
+    }
+
+    fn sprite_at(&self, layer: Layer, sprite: Sprite, p: Point, alpha: u8) -> DrawCommand {
+        DrawCommand::Sprite {
+            layer,
+            sprite,
+            x: (p.x + 1) * self.tile_size as i32,
+            y: (p.y + 1) * self.tile_size as i32,
+            size: self.tile_size,
+            alpha,
+        }
+    }
+
+    fn draw_board(&self, draw_list: &mut Vec<DrawCommand>) {
+        let size = self.game.get_size();
+        for y in 0..size {
+            for x in 0..size {
+                let sprite = Sprite::for_intersection(size, x, y);
+                let p = Point::new(x as i32, y as i32);
+                draw_list.push(self.sprite_at(Layer::Board, sprite, p, 255));
+            }
+        }
+    }
+
+    fn draw_stones(&self, draw_list: &mut Vec<DrawCommand>) {
+        let size = self.game.get_size();
+        for y in 0..size {
+            for x in 0..size {
+                let p = Point::new(x as i32, y as i32);
+                if let Some(sprite) = stone_sprite(self.game.get(p)) {
+                    draw_list.push(self.sprite_at(Layer::Stones, sprite, p, 255));
+                }
+            }
+        }
+    }
+
+    fn draw_ghost(&self, draw_list: &mut Vec<DrawCommand>) {
+        if let Some(p) = self.mouse {
+            if self.is_human_turn() && self.game.can_place(p) {
+                if let Some(sprite) = stone_sprite(self.game.get_turn()) {
+                    draw_list.push(self.sprite_at(Layer::Stones, sprite, p, GHOST_ALPHA));
+                }
+            }
+        }
+    }
+
+    // marks the stones the score counts as dead
+    fn draw_dead_stones(&self, draw_list: &mut Vec<DrawCommand>) {
+        for &p in self.get_dead_stones().unwrap_or_default() {
+            draw_list.push(self.sprite_at(Layer::Overlay, Sprite::RedDot, p, 255));
+        }
+    }
+
+    // the current estimate, if it's shown and has been worked out for this
+    // position
+    fn get_estimate(&self) -> Option<&Estimate> {
+        match &self.estimate {
+            Some((hash, moves, estimate))
+                if self.show_influence
+                    && *hash == self.game.get_hash()
+                    && *moves == self.game.get_moves().len() =>
+            {
+                Some(estimate)
+            }
+            _ => None,
+        }
+    }
+
+    // a translucent square on each point in black or white for whoever
+    // looks like owning it, stronger the surer the estimate is. Stones on
+    // their owner's points are left alone.
+    fn draw_ownership(&self, draw_list: &mut Vec<DrawCommand>) {
+        let estimate = match self.get_estimate() {
+            Some(estimate) => estimate,
+            None => return,
+        };
+        let tile = self.tile_size as i32;
+        let square = self.tile_size * 2 / 3;
+        let offset = (tile - square as i32) / 2;
+        for (y, row) in estimate.ownership.iter().enumerate() {
+            for (x, &owner) in row.iter().enumerate() {
+                let (state, rgb) = if owner > 0.0 {
+                    (CellState::Black, 0)
+                } else if owner < 0.0 {
+                    (CellState::White, 255)
+                } else {
+                    continue;
+                };
+                if self.game.get(Point::new(x as i32, y as i32)) == state {
+                    continue;
+                }
+                draw_list.push(DrawCommand::Rect {
+                    layer: Layer::Overlay,
+                    x: (x as i32 + 1) * tile + offset,
+                    y: (y as i32 + 1) * tile + offset,
+                    width: square,
+                    height: square,
+                    color: [rgb, rgb, rgb, (owner.abs() * OWNERSHIP_ALPHA) as u8],
+                });
+            }
+        }
+    }
+
+    // the book's moves for the position, if it's shown and it's someone's
+    // turn in a game
+    fn get_book_moves(&self) -> Vec<BookMove> {
+        match &self.book {
+            Some(book)
+                if self.show_book && self.practice.is_none() && !self.game.is_game_over() =>
+            {
+                book.get_moves(&self.game)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // a green square on each book move, stronger the more often it was
+    // played, labelled with its win rate as a percentage
+    fn draw_book(&self, draw_list: &mut Vec<DrawCommand>) {
+        let moves = self.get_book_moves();
+        let most = match moves.first() {
+            Some(m) => m.stats.games as f32,
+            None => return,
+        };
+        let tile = self.tile_size as i32;
+        let square = self.tile_size * 2 / 3;
+        let offset = (tile - square as i32) / 2;
+        for m in &moves {
+            let alpha = m.stats.games as f32 / most * BOOK_ALPHA;
+            let [r, g, b, _] = BOOK_COLOR;
+            draw_list.push(DrawCommand::Rect {
+                layer: Layer::Overlay,
+                x: (m.mv.x + 1) * tile + offset,
+                y: (m.mv.y + 1) * tile + offset,
+                width: square,
+                height: square,
+                color: [r, g, b, alpha as u8],
+            });
+            let text = match m.stats.get_win_rate() {
+                Some(rate) => format!("{:.0}", rate * 100.0),
+                None => "?".to_string(),
+            };
+            let width = get_text_width(&text, LABEL_SCALE) as i32;
+            let height = (GLYPH_HEIGHT * LABEL_SCALE) as i32;
+            draw_list.push(DrawCommand::Text {
+                layer: Layer::Overlay,
+                x: (m.mv.x + 1) * tile + (tile - width) / 2,
+                y: (m.mv.y + 1) * tile + (tile - height) / 2,
+                scale: LABEL_SCALE,
+                text,
+                color: [255, 255, 255, 255],
+            });
+        }
+    }
+
+    // the record's circles, crosses, triangles, squares and labels for the
+    // position being reviewed, in white on black stones and black everywhere
+    // else
+    fn draw_markup(&self, draw_list: &mut Vec<DrawCommand>) {
+        let markup = match &self.review {
+            Some(review) => review.get_markup(),
+            None => return,
+        };
+        let tile = self.tile_size as i32;
+        let color_at = |p: Point| match self.game.get(p) {
+            CellState::Black => [255, 255, 255, 255],
+            _ => [0, 0, 0, 255],
+        };
+        let line = (self.tile_size / 16).max(1);
+        let mut rect = |x: i32, y: i32, width: u32, height: u32, color: Color| {
+            draw_list.push(DrawCommand::Rect {
+                layer: Layer::Overlay,
+                x,
+                y,
+                width,
+                height,
+                color,
+            });
+        };
+        // marks are never thinner than their lines, even on tiny tiles
+        let side = (self.tile_size / 2).max(line);
+        let inset = (tile - side as i32) / 2;
+        for &p in &markup.squares {
+            let (x, y) = ((p.x + 1) * tile + inset, (p.y + 1) * tile + inset);
+            let color = color_at(p);
+            rect(x, y, side, line, color);
+            rect(x, y + (side - line) as i32, side, line, color);
+            rect(x, y, line, side, color);
+            rect(x + (side - line) as i32, y, line, side, color);
+        }
+        // circles, crosses and triangles are drawn a pixel row at a time
+        let radius = side as f32 / 2.0;
+        let inner = radius - line as f32;
+        for &p in &markup.circles {
+            let centre = (p.x + 1) * tile + tile / 2;
+            let top = (p.y + 1) * tile + inset;
+            let color = color_at(p);
+            for row in 0..side {
+                let dy = row as f32 + 0.5 - radius;
+                let outer = (radius * radius - dy * dy).sqrt().round() as i32;
+                let hole = if dy.abs() < inner {
+                    (inner * inner - dy * dy).sqrt().round() as i32
+                } else {
+                    0
+                };
+                let width = (outer - hole).max(1) as u32;
+                rect(centre - outer, top + row as i32, width, 1, color);
+                rect(centre + hole, top + row as i32, width, 1, color);
+            }
+        }
+        for &p in &markup.crosses {
+            let (x, y) = ((p.x + 1) * tile + inset, (p.y + 1) * tile + inset);
+            let color = color_at(p);
+            for row in 0..=side - line {
+                let (row, back) = (row as i32, (side - line - row) as i32);
+                rect(x + row, y + row, line, line, color);
+                rect(x + back, y + row, line, line, color);
+            }
+        }
+        // triangles widen from the apex
+        let height = (side * 7 / 8).max(line);
+        for &p in &markup.triangles {
+            let centre = (p.x + 1) * tile + tile / 2;
+            let top = (p.y + 1) * tile + (tile - height as i32) / 2;
+            let color = color_at(p);
+            for row in 0..height {
+                let half = (row * side / 2 / height) as i32;
+                rect(centre - half, top + row as i32, line, 1, color);
+                rect(
+                    centre + half - line as i32 + 1,
+                    top + row as i32,
+                    line,
+                    1,
+                    color,
+                );
+            }
+            rect(
+                centre - (side / 2) as i32,
+                top + (height - line) as i32,
+                side,
+                line,
+                color,
+            );
+        }
+        for (p, text) in &markup.labels {
+            let width = get_text_width(text, LABEL_SCALE) as i32;
+            let height = (GLYPH_HEIGHT * LABEL_SCALE) as i32;
+            draw_list.push(DrawCommand::Text {
+                layer: Layer::Overlay,
+                x: (p.x + 1) * tile + (tile - width) / 2,
+                y: (p.y + 1) * tile + (tile - height) / 2,
+                scale: LABEL_SCALE,
+                text: text.clone(),
+                color: color_at(*p),
+            });
+        }
+    }
+
+    // the comment on the position being reviewed, wrapped to fit the panel
+    // to the right of the board
+    fn draw_comment(&self, draw_list: &mut Vec<DrawCommand>) {
+        let comment = match self.review.as_ref().and_then(|r| r.get_comment()) {
+            Some(comment) => comment,
+            None => return,
+        };
+        let tile = self.tile_size as i32;
+        let left = (self.game.get_size() as i32 + 2) * tile;
+        let columns = ((COMMENT_WIDTH - 1) * self.tile_size) / ((GLYPH_WIDTH + 1) * COMMENT_SCALE);
+        let line_height = ((GLYPH_HEIGHT + 2) * COMMENT_SCALE) as i32;
+        for (i, text) in wrap_text(comment, columns as usize).into_iter().enumerate() {
+            draw_list.push(DrawCommand::Text {
+                layer: Layer::Hud,
+                x: left,
+                y: tile + i as i32 * line_height,
+                scale: COMMENT_SCALE,
+                text,
+                color: STATUS_COLOR,
+            });
+        }
+    }
+
+    // highlights the group and liberties under the mouse, or the boundary of
+    // the empty region under it. A group in atari gets its ladder instead.
+    fn draw_overlay(&self, draw_list: &mut Vec<DrawCommand>) {
+        let p = match self.mouse {
+            Some(p) => p,
+            None => return,
+        };
+        let stone = self.game.get(p);
+        if let Some(ladder) = self.get_overlay_ladder() {
+            self.draw_ladder(ladder, draw_list);
+        } else if stone != CellState::None {
+            let mut group = Vec::new();
+            self.game.get_group(stone, p, &mut group);
+            for q in group {
+                draw_list.push(self.sprite_at(Layer::Overlay, Sprite::BlueDot, q, 255));
+            }
+            for q in self.game.get_liberties(p) {
+                draw_list.push(self.sprite_at(Layer::Overlay, Sprite::RedDot, q, 255));
+            }
+        } else {
+            for q in self.game.get_boundary(p) {
+                draw_list.push(self.sprite_at(Layer::Overlay, Sprite::PinkDot, q, 255));
+            }
+        }
+    }
+
+    // the ladder against the group under the mouse while the overlay is on
+    fn get_overlay_ladder(&self) -> Option<&Ladder> {
+        match &self.ladder {
+            Some((p, hash, moves, ladder))
+                if self.show_overlay
+                    && Some(*p) == self.mouse
+                    && *hash == self.game.get_hash()
+                    && *moves == self.game.get_moves().len() =>
+            {
+                ladder.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    // the ladder's moves as numbered ghost stones, with the breaker marked
+    fn draw_ladder(&self, ladder: &Ladder, draw_list: &mut Vec<DrawCommand>) {
+        let tile = self.tile_size as i32;
+        for (i, &(player, q)) in ladder.moves.iter().enumerate() {
+            if let Some(sprite) = stone_sprite(player) {
+                draw_list.push(self.sprite_at(Layer::Overlay, sprite, q, GHOST_ALPHA));
+            }
+            let text = (i + 1).to_string();
+            let width = get_text_width(&text, LABEL_SCALE) as i32;
+            let height = (GLYPH_HEIGHT * LABEL_SCALE) as i32;
+            let color = match player {
+                CellState::Black => [255, 255, 255, 255],
+                _ => [0, 0, 0, 255],
+            };
+            draw_list.push(DrawCommand::Text {
+                layer: Layer::Overlay,
+                x: (q.x + 1) * tile + (tile - width) / 2,
+                y: (q.y + 1) * tile + (tile - height) / 2,
+                scale: LABEL_SCALE,
+                text,
+                color,
+            });
+        }
+        if let Some(q) = ladder.breaker {
+            draw_list.push(self.sprite_at(Layer::Overlay, Sprite::GreenDot, q, 255));
+        }
+    }
+
+    fn get_status(&self) -> String {
+        if let Some(practice) = &self.practice {
+            let number = format!("{}/{}", practice.get_index() + 1, practice.get_count());
+            let problem = practice.get_problem();
+            return match problem.get_status() {
+                ProblemStatus::Solving if practice.is_solved(practice.get_index()) => {
+                    format!("{} {} TO PLAY (SOLVED)", number, problem.get_player())
+                }
+                ProblemStatus::Solving => format!("{} {} TO PLAY", number, problem.get_player()),
+                ProblemStatus::Solved => {
+                    format!("{} CORRECT! {} SOLVED", number, practice.get_solved_count())
+                }
+                ProblemStatus::Failed => format!("{} WRONG, R TO RETRY", number),
+            }
+            .to_uppercase();
+        }
+        if let Some(review) = &self.review {
+            return self.get_review_status(review);
+        }
+        if let Some(status) = self.get_remote_status() {
+            status
+        } else if let Some(forfeit) = self.game.get_forfeit() {
+            format!("GAME OVER: {}", forfeit)
+        } else if self.game.is_game_over() {
+            match self.get_dead_stones() {
+                Some(dead) => format!("GAME OVER: {}", self.game.score(dead)),
+                None => "GAME OVER".to_string(),
+            }
+        } else if self.game.is_placing_handicap() {
+            format!(
+                "PLACE {} HANDICAP STONES",
+                self.game.get_handicap_remaining()
+            )
+        } else if let Some(ladder) = self.get_overlay_ladder() {
+            if ladder.works {
+                "THE LADDER WORKS".to_string()
+            } else {
+                "THE LADDER IS BROKEN".to_string()
+            }
+        } else if let Some(m) = self
+            .get_book_moves()
+            .into_iter()
+            .find(|m| Some(m.mv) == self.mouse)
+        {
+            match m.stats.get_win_rate() {
+                Some(rate) => format!("BOOK: {} GAMES, {:.0}% WON", m.stats.games, rate * 100.0),
+                None => format!("BOOK: {} GAMES", m.stats.games),
+            }
+        } else if let Some(estimate) = self.get_estimate() {
+            format!("ESTIMATE: {}", estimate.score)
+        } else if self.thinking.is_some() {
+            format!("{} IS THINKING", self.game.get_turn()).to_uppercase()
+        } else {
+            format!("{} TO PLAY", self.game.get_turn()).to_uppercase()
+        }
+    }
+
+    // a recent notice, or how far into the record the position is, which
+    // variation it's in and who is to play
+    fn get_review_status(&self, review: &Review) -> String {
+        if let Some((notice, _)) = self
+            .notice
+            .as_ref()
+            .filter(|(_, time)| time.elapsed() < NOTICE_TIME)
+        {
+            return notice.to_uppercase();
+        }
+        let mut status = format!("MOVE {}", review.get_depth());
+        let (index, count) = review.get_variation();
+        if count > 1 {
+            status += &format!(" VARIATION {}/{}", index + 1, count);
+        }
+        if self.game.is_game_over() {
+            status + " GAME OVER"
+        } else {
+            format!("{} {} TO PLAY", status, self.game.get_turn()).to_uppercase()
+        }
+    }
+
+    // the connection, a recent message from the server or whose turn it is
+    // in a network game, unless the game is over
+    fn get_remote_status(&self) -> Option<String> {
+        let remote = self.remote.as_ref()?;
+        let status = if !remote.is_connected() {
+            "DISCONNECTED FROM THE SERVER".to_string()
+        } else if remote.get_board().is_none() {
+            "JOINING THE GAME".to_string()
+        } else if self.game.is_game_over() {
+            // the server's result, which is the one that counts
+            return remote
+                .get_result()
+                .map(|result| format!("GAME OVER: {}", result));
+        } else if let Some((notice, _)) = self
+            .notice
+            .as_ref()
+            .filter(|(_, time)| time.elapsed() < NOTICE_TIME)
+        {
+            notice.to_uppercase()
+        } else if self.is_human_turn() {
+            "YOUR TURN".to_string()
+        } else if remote.is_spectator() {
+            let turn = self.game.get_turn();
+            let name = remote.get_player_name(turn).unwrap_or("");
+            format!("WATCHING: {} ({}) TO PLAY", name, turn).to_uppercase()
+        } else {
+            let turn = self.game.get_turn();
+            match remote.get_player_name(turn) {
+                Some(name) => format!("{} ({}) TO PLAY", name, turn).to_uppercase(),
+                None => format!("WAITING FOR {}", turn).to_uppercase(),
+            }
+        };
+        Some(status)
+    }
+
+    // the status goes in the margin below the board
+    fn draw_status(&self, draw_list: &mut Vec<DrawCommand>) {
+        let tile = self.tile_size as i32;
+        let text_height = (GLYPH_HEIGHT * STATUS_SCALE) as i32;
+        draw_list.push(DrawCommand::Text {
+            layer: Layer::Hud,
+            x: tile / 2,
+            y: (self.game.get_size() as i32 + 1) * tile + (tile - text_height) / 2,
+            scale: STATUS_SCALE,
+            text: self.get_status(),
+            color: STATUS_COLOR,
+        });
+    }
+
+    // both clocks go in the margin above the board, Black's on the left and
+    // White's on the right, greyed out while they aren't running
+    fn draw_clocks(&self, draw_list: &mut Vec<DrawCommand>) {
+        let clock = match &self.clock {
+            Some(clock) => clock,
+            None => return,
+        };
+        let tile = self.tile_size as i32;
+        let (width, _) = self.get_window_size();
+        let y = (tile - (GLYPH_HEIGHT * STATUS_SCALE) as i32) / 2;
+        for &player in [CellState::Black, CellState::White].iter() {
+            let player_clock = clock.get_clock(player);
+            let text =
+                format!("{} {}", player, player_clock.format(clock.get_control())).to_uppercase();
+            let color = if player_clock.flagged {
+                CLOCK_FLAGGED_COLOR
+            } else if clock.get_running() == Some(player) {
+                STATUS_COLOR
+            } else {
+                CLOCK_IDLE_COLOR
+            };
+            let x = match player {
+                CellState::Black => tile / 2,
+                _ => width as i32 - tile / 2 - get_text_width(&text, STATUS_SCALE) as i32,
+            };
+            draw_list.push(DrawCommand::Text {
+                layer: Layer::Hud,
+                x,
+                y,
+                scale: STATUS_SCALE,
+                text,
+                color,
+            });
+        }
+    }
+}
+
+// splits `text` into lines of at most `columns` characters, breaking
+// between words where it can and keeping the comment's own line breaks
+fn wrap_text(text: &str, columns: usize) -> Vec<String> {
+    // a panel too narrow for any text still gets a character a line
+    let columns = columns.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            // words too long for a line of their own are cut up
+            while word.len() > columns {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                lines.push(word.drain(..columns).collect());
+            }
+            let word: String = word.into_iter().collect();
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > columns {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &word;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn create_clock(control: TimeControl) -> Option<GameClock> {
+    match control {
+        TimeControl::Unlimited => None,
+        control => Some(GameClock::new(control)),
+    }
+}
+
+pub fn stone_sprite(state: CellState) -> Option<Sprite> {
+    match state {
+        CellState::White => Some(Sprite::White),
+        CellState::Black => Some(Sprite::Black),
+        _ => None,
+    }
+}
@@ -0,0 +1,698 @@
+use std::fmt::Write;
+
+use crate::frontend::*;
+use crate::go_core::*;
+use crate::sgf::Markup;
+
+const COLUMNS: &str = "ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+// each pixel of a rendered image is the average of this many squared
+const SUPERSAMPLING: u32 = 4;
+
+/// A mark drawn on a point of a diagram.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mark {
+    Circle,
+    Cross,
+    Triangle,
+    Square,
+    /// A few letters or digits, e.g. `A` or `12`.
+    Label(String),
+}
+
+/// How a diagram looks. Distances are in pixels, or in SVG user units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiagramStyle {
+    /// The distance between neighbouring lines.
+    pub spacing: f32,
+    pub line_width: f32,
+    /// The width of the lines along the edge of the board.
+    pub edge_width: f32,
+    pub background: Color,
+    pub line: Color,
+    pub black: Color,
+    pub white: Color,
+    /// The font family named in SVG output; images use the built-in font.
+    pub font: String,
+}
+
+impl Default for DiagramStyle {
+    fn default() -> DiagramStyle {
+        DiagramStyle {
+            spacing: 24.0,
+            line_width: 1.0,
+            edge_width: 2.0,
+            background: [220, 179, 92, 255],
+            line: [0, 0, 0, 255],
+            black: [0, 0, 0, 255],
+            white: [255, 255, 255, 255],
+            font: "Helvetica, Arial, sans-serif".to_string(),
+        }
+    }
+}
+
+impl DiagramStyle {
+    /// Plain black and white, for printing.
+    pub fn print() -> DiagramStyle {
+        DiagramStyle {
+            background: [255, 255, 255, 255],
+            ..DiagramStyle::default()
+        }
+    }
+}
+
+// what a diagram is drawn with, in diagram coordinates
+#[derive(Clone, Debug, PartialEq)]
+enum Shape {
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        color: Color,
+    },
+    Circle {
+        centre: (f32, f32),
+        radius: f32,
+        fill: Option<Color>,
+        stroke: Option<(Color, f32)>,
+    },
+    /// A closed outline.
+    Polygon {
+        points: Vec<(f32, f32)>,
+        width: f32,
+        color: Color,
+    },
+    /// Text centred on a point, `size` high.
+    Text {
+        centre: (f32, f32),
+        size: f32,
+        text: String,
+        color: Color,
+    },
+}
+
+/// A printable diagram of a position, or of a rectangle of it, drawn as SVG
+/// or as an image without needing a window. Stones can be numbered with the
+/// moves that played them and points marked with circles, crosses,
+/// triangles, squares and labels.
+#[derive(Clone)]
+pub struct Diagram {
+    board: Board,
+    // the corners of the region shown, top left first
+    region: (Point, Point),
+    coordinates: bool,
+    numbered_from: Option<usize>,
+    marks: Vec<(Point, Mark)>,
+    style: DiagramStyle,
+}
+
+impl Diagram {
+    /// A diagram of the whole of `board` with coordinates and the default
+    /// style.
+    pub fn new(board: &Board) -> Diagram {
+        let last = board.get_size() as i32 - 1;
+        Diagram {
+            board: board.clone(),
+            region: (Point::new(0, 0), Point::new(last, last)),
+            coordinates: true,
+            numbered_from: None,
+            marks: Vec::new(),
+            style: DiagramStyle::default(),
+        }
+    }
+
+    /// Shows only the rectangle with corners `a` and `b`, both included.
+    pub fn set_region(&mut self, a: Point, b: Point) -> Result<(), String> {
+        let size = self.board.get_size() as i32;
+        if [a, b]
+            .iter()
+            .any(|p| p.x < 0 || p.y < 0 || p.x >= size || p.y >= size)
+        {
+            return Err("the region isn't on the board".to_string());
+        }
+        self.region = (
+            Point::new(a.x.min(b.x), a.y.min(b.y)),
+            Point::new(a.x.max(b.x), a.y.max(b.y)),
+        );
+        Ok(())
+    }
+
+    pub fn set_coordinates(&mut self, coordinates: bool) {
+        self.coordinates = coordinates;
+    }
+
+    /// Numbers the stones played from move `first` on, counting from 1, with
+    /// their move numbers. Stones that have since been captured aren't
+    /// numbered, and a point played more than once shows its last move.
+    pub fn number_moves_from(&mut self, first: usize) {
+        self.numbered_from = Some(first.max(1));
+    }
+
+    pub fn add_mark(&mut self, p: Point, mark: Mark) {
+        self.marks.retain(|&(q, _)| q != p);
+        self.marks.push((p, mark));
+    }
+
+    /// Adds the marks from an SGF node.
+    pub fn add_markup(&mut self, markup: &Markup) {
+        for &p in &markup.circles {
+            self.add_mark(p, Mark::Circle);
+        }
+        for &p in &markup.crosses {
+            self.add_mark(p, Mark::Cross);
+        }
+        for &p in &markup.triangles {
+            self.add_mark(p, Mark::Triangle);
+        }
+        for &p in &markup.squares {
+            self.add_mark(p, Mark::Square);
+        }
+        for (p, text) in &markup.labels {
+            self.add_mark(*p, Mark::Label(text.clone()));
+        }
+    }
+
+    pub fn get_style(&self) -> &DiagramStyle {
+        &self.style
+    }
+
+    pub fn set_style(&mut self, style: DiagramStyle) {
+        self.style = style;
+    }
+
+    /// The width and height of the diagram.
+    pub fn get_size(&self) -> (f32, f32) {
+        let (a, b) = self.region;
+        let margin = self.get_margin();
+        let spacing = self.style.spacing;
+        (
+            (b.x - a.x) as f32 * spacing + 2.0 * margin,
+            (b.y - a.y) as f32 * spacing + 2.0 * margin,
+        )
+    }
+
+    // the distance from the outside of the diagram to the first line,
+    // leaving room for the coordinates
+    fn get_margin(&self) -> f32 {
+        if self.coordinates {
+            self.style.spacing * 1.4
+        } else {
+            self.style.spacing * 0.75
+        }
+    }
+
+    fn get_centre(&self, p: Point) -> (f32, f32) {
+        let margin = self.get_margin();
+        let spacing = self.style.spacing;
+        (
+            margin + (p.x - self.region.0.x) as f32 * spacing,
+            margin + (p.y - self.region.0.y) as f32 * spacing,
+        )
+    }
+
+    fn is_shown(&self, p: Point) -> bool {
+        let (a, b) = self.region;
+        p.x >= a.x && p.x <= b.x && p.y >= a.y && p.y <= b.y
+    }
+
+    // the number of each stone still on the board that was played from
+    // `numbered_from` on
+    fn get_move_numbers(&self) -> Vec<(Point, usize)> {
+        let first = match self.numbered_from {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+        let mut numbers: Vec<(Point, usize)> = Vec::new();
+        for (i, &(player, mv)) in self.board.get_moves().iter().enumerate() {
+            if let Some(p) = mv {
+                numbers.retain(|&(q, _)| q != p);
+                if i + 1 >= first && self.board.get(p) == player {
+                    numbers.push((p, i + 1));
+                }
+            }
+        }
+        numbers
+    }
+
+    fn get_shapes(&self) -> Vec<Shape> {
+        let style = &self.style;
+        let spacing = style.spacing;
+        let size = self.board.get_size() as i32;
+        let (a, b) = self.region;
+        let mut shapes = Vec::new();
+
+        // lines carry on half a point past the region where the board does
+        let top = self.get_centre(a).1 - if a.y > 0 { spacing / 2.0 } else { 0.0 };
+        let bottom = self.get_centre(b).1 + if b.y < size - 1 { spacing / 2.0 } else { 0.0 };
+        let left = self.get_centre(a).0 - if a.x > 0 { spacing / 2.0 } else { 0.0 };
+        let right = self.get_centre(b).0 + if b.x < size - 1 { spacing / 2.0 } else { 0.0 };
+        let edge = |i: i32| {
+            if i == 0 || i == size - 1 {
+                style.edge_width
+            } else {
+                style.line_width
+            }
+        };
+        for x in a.x..=b.x {
+            let cx = self.get_centre(Point::new(x, a.y)).0;
+            shapes.push(Shape::Line {
+                from: (cx, top),
+                to: (cx, bottom),
+                width: edge(x),
+                color: style.line,
+            });
+        }
+        for y in a.y..=b.y {
+            let cy = self.get_centre(Point::new(a.x, y)).1;
+            shapes.push(Shape::Line {
+                from: (left, cy),
+                to: (right, cy),
+                width: edge(y),
+                color: style.line,
+            });
+        }
+
+        if self.coordinates {
+            let size_text = spacing * 0.4;
+            let margin = self.get_margin();
+            for x in a.x..=b.x {
+                let (cx, _) = self.get_centre(Point::new(x, a.y));
+                let column = COLUMNS.chars().nth(x as usize).unwrap_or('?');
+                shapes.push(Shape::Text {
+                    centre: (cx, margin - spacing),
+                    size: size_text,
+                    text: column.to_string(),
+                    color: style.line,
+                });
+            }
+            for y in a.y..=b.y {
+                let (_, cy) = self.get_centre(Point::new(a.x, y));
+                shapes.push(Shape::Text {
+                    centre: (margin - spacing, cy),
+                    size: size_text,
+                    text: (size - y).to_string(),
+                    color: style.line,
+                });
+            }
+        }
+
+        let mut labels: Vec<(Point, String)> = self
+            .get_move_numbers()
+            .into_iter()
+            .map(|(p, n)| (p, n.to_string()))
+            .collect();
+        for (p, mark) in &self.marks {
+            if let Mark::Label(text) = mark {
+                labels.retain(|(q, _)| q != p);
+                labels.push((*p, text.clone()));
+            }
+        }
+
+        for y in a.y..=b.y {
+            for x in a.x..=b.x {
+                let p = Point::new(x, y);
+                let centre = self.get_centre(p);
+                match self.board.get(p) {
+                    CellState::Black => shapes.push(Shape::Circle {
+                        centre,
+                        radius: spacing * 0.48,
+                        fill: Some(style.black),
+                        stroke: None,
+                    }),
+                    CellState::White => shapes.push(Shape::Circle {
+                        centre,
+                        radius: spacing * 0.48 - style.line_width / 2.0,
+                        fill: Some(style.white),
+                        stroke: Some((style.line, style.line_width)),
+                    }),
+                    CellState::None => {
+                        // labels on empty points are cut out of the lines
+                        if labels.iter().any(|&(q, _)| q == p) {
+                            shapes.push(Shape::Circle {
+                                centre,
+                                radius: spacing * 0.4,
+                                fill: Some(style.background),
+                                stroke: None,
+                            });
+                        } else if is_star_point(size as usize, x as usize, y as usize) {
+                            shapes.push(Shape::Circle {
+                                centre,
+                                radius: spacing * 0.1,
+                                fill: Some(style.line),
+                                stroke: None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        let width = style.line_width * 1.5;
+        for (p, mark) in &self.marks {
+            if !self.is_shown(*p) {
+                continue;
+            }
+            let (cx, cy) = self.get_centre(*p);
+            let color = self.get_ink(*p);
+            match mark {
+                Mark::Circle => shapes.push(Shape::Circle {
+                    centre: (cx, cy),
+                    radius: spacing * 0.25,
+                    fill: None,
+                    stroke: Some((color, width)),
+                }),
+                Mark::Cross => {
+                    let half = spacing * 0.2;
+                    for &dy in [-half, half].iter() {
+                        shapes.push(Shape::Line {
+                            from: (cx - half, cy - dy),
+                            to: (cx + half, cy + dy),
+                            width,
+                            color,
+                        });
+                    }
+                }
+                Mark::Triangle => {
+                    let r = spacing * 0.3;
+                    let points = (0..3)
+                        .map(|i| {
+                            let angle = std::f32::consts::PI * (i as f32 * 2.0 / 3.0 - 0.5);
+                            (cx + r * angle.cos(), cy + r * angle.sin() + r * 0.15)
+                        })
+                        .collect();
+                    shapes.push(Shape::Polygon {
+                        points,
+                        width,
+                        color,
+                    });
+                }
+                Mark::Square => {
+                    let half = spacing * 0.2;
+                    shapes.push(Shape::Polygon {
+                        points: vec![
+                            (cx - half, cy - half),
+                            (cx + half, cy - half),
+                            (cx + half, cy + half),
+                            (cx - half, cy + half),
+                        ],
+                        width,
+                        color,
+                    });
+                }
+                Mark::Label(_) => {}
+            }
+        }
+
+        for (p, text) in labels {
+            if !self.is_shown(p) {
+                continue;
+            }
+            let scale = if text.chars().count() > 2 { 0.36 } else { 0.45 };
+            shapes.push(Shape::Text {
+                centre: self.get_centre(p),
+                size: spacing * scale,
+                text,
+                color: self.get_ink(p),
+            });
+        }
+        shapes
+    }
+
+    // the colour marks on `p` stand out in
+    fn get_ink(&self, p: Point) -> Color {
+        if self.board.get(p) == CellState::Black {
+            self.style.white
+        } else {
+            self.style.line
+        }
+    }
+
+    /// The diagram as a standalone SVG document.
+    pub fn to_svg(&self) -> String {
+        let (width, height) = self.get_size();
+        let mut svg = String::new();
+        // writing to a String can't fail
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = width,
+            h = height
+        );
+        let _ = writeln!(
+            svg,
+            "<rect width=\"{}\" height=\"{}\" {}/>",
+            width,
+            height,
+            svg_paint("fill", self.style.background)
+        );
+        for shape in self.get_shapes() {
+            let _ = match shape {
+                Shape::Line {
+                    from,
+                    to,
+                    width,
+                    color,
+                } => writeln!(
+                    svg,
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke-width=\"{}\" stroke-linecap=\"square\" {}/>",
+                    from.0,
+                    from.1,
+                    to.0,
+                    to.1,
+                    width,
+                    svg_paint("stroke", color)
+                ),
+                Shape::Circle {
+                    centre,
+                    radius,
+                    fill,
+                    stroke,
+                } => {
+                    let fill = fill.map_or("fill=\"none\"".to_string(), |c| svg_paint("fill", c));
+                    let stroke = stroke.map_or(String::new(), |(c, width)| {
+                        format!(" stroke-width=\"{}\" {}", width, svg_paint("stroke", c))
+                    });
+                    writeln!(
+                        svg,
+                        "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}{}/>",
+                        centre.0, centre.1, radius, fill, stroke
+                    )
+                }
+                Shape::Polygon {
+                    points,
+                    width,
+                    color,
+                } => {
+                    let points: Vec<String> =
+                        points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+                    writeln!(
+                        svg,
+                        "<polygon points=\"{}\" fill=\"none\" stroke-width=\"{}\" {}/>",
+                        points.join(" "),
+                        width,
+                        svg_paint("stroke", color)
+                    )
+                }
+                Shape::Text {
+                    centre,
+                    size,
+                    text,
+                    color,
+                } => writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" {}>{}</text>",
+                    centre.0,
+                    centre.1,
+                    escape_xml(&self.style.font),
+                    size,
+                    svg_paint("fill", color),
+                    escape_xml(&text)
+                ),
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// The diagram drawn into an image, one pixel per unit, with smoothed
+    /// edges. Text is in the built-in 5x7 pixel font, scaled up in whole
+    /// steps, so labels look blocky; `to_svg` has smooth text.
+    pub fn to_image(&self) -> Image {
+        let (width, height) = self.get_size();
+        let scale = SUPERSAMPLING as f32;
+        let mut canvas = Image::new(
+            (width.ceil() * scale) as u32,
+            (height.ceil() * scale) as u32,
+        );
+        for px in canvas.pixels.chunks_mut(4) {
+            px.copy_from_slice(&self.style.background);
+        }
+        for shape in self.get_shapes() {
+            draw_shape(&mut canvas, &shape, scale);
+        }
+        downsample(&canvas, SUPERSAMPLING)
+    }
+}
+
+fn svg_paint(attribute: &str, color: Color) -> String {
+    let paint = format!(
+        "{}=\"#{:02x}{:02x}{:02x}\"",
+        attribute, color[0], color[1], color[2]
+    );
+    if color[3] == 255 {
+        paint
+    } else {
+        let opacity = color[3] as f32 / 255.0;
+        format!("{} {}-opacity=\"{:.3}\"", paint, attribute, opacity)
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// blends `color` over the pixel at (x, y)
+fn plot(image: &mut Image, x: i32, y: i32, color: Color) {
+    if x < 0 || y < 0 || x as u32 >= image.width || y as u32 >= image.height {
+        return;
+    }
+    let i = ((y as u32 * image.width + x as u32) * 4) as usize;
+    let a = color[3] as u32;
+    for (old, &new) in image.pixels[i..i + 3].iter_mut().zip(&color) {
+        *old = ((new as u32 * a + *old as u32 * (255 - a)) / 255) as u8;
+    }
+    image.pixels[i + 3] = 255;
+}
+
+// fills every pixel in the box whose centre `inside` accepts
+fn fill_where<F: Fn(f32, f32) -> bool>(
+    image: &mut Image,
+    (left, top, right, bottom): (f32, f32, f32, f32),
+    color: Color,
+    inside: F,
+) {
+    for y in top.floor() as i32..=bottom.ceil() as i32 {
+        for x in left.floor() as i32..=right.ceil() as i32 {
+            if inside(x as f32 + 0.5, y as f32 + 0.5) {
+                plot(image, x, y, color);
+            }
+        }
+    }
+}
+
+fn draw_line(image: &mut Image, from: (f32, f32), to: (f32, f32), width: f32, color: Color) {
+    let half = width / 2.0;
+    let bounds = (
+        from.0.min(to.0) - half,
+        from.1.min(to.1) - half,
+        from.0.max(to.0) + half,
+        from.1.max(to.1) + half,
+    );
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return;
+    }
+    // lines have square ends, like SVG's square caps
+    fill_where(image, bounds, color, |x, y| {
+        let along = ((x - from.0) * dx + (y - from.1) * dy) / length;
+        let across = ((x - from.0) * dy - (y - from.1) * dx) / length;
+        along >= -half && along <= length + half && across.abs() <= half
+    });
+}
+
+fn draw_shape(image: &mut Image, shape: &Shape, scale: f32) {
+    let at = |(x, y): (f32, f32)| (x * scale, y * scale);
+    match shape {
+        Shape::Line {
+            from,
+            to,
+            width,
+            color,
+        } => draw_line(image, at(*from), at(*to), width * scale, *color),
+        Shape::Circle {
+            centre,
+            radius,
+            fill,
+            stroke,
+        } => {
+            let (cx, cy) = at(*centre);
+            let r = radius * scale;
+            let half = stroke.map_or(0.0, |(_, width)| width * scale / 2.0);
+            let outer = r + half;
+            let bounds = (cx - outer, cy - outer, cx + outer, cy + outer);
+            let distance = |x: f32, y: f32| ((x - cx).powi(2) + (y - cy).powi(2)).sqrt();
+            if let Some(color) = fill {
+                fill_where(image, bounds, *color, |x, y| distance(x, y) <= r);
+            }
+            if let Some((color, _)) = stroke {
+                fill_where(image, bounds, *color, |x, y| {
+                    (distance(x, y) - r).abs() <= half
+                });
+            }
+        }
+        Shape::Polygon {
+            points,
+            width,
+            color,
+        } => {
+            let half = width * scale / 2.0;
+            for (i, &point) in points.iter().enumerate() {
+                let next = points[(i + 1) % points.len()];
+                draw_line(image, at(point), at(next), width * scale, *color);
+                // round joins fill the gaps at the corners
+                let (cx, cy) = at(point);
+                let bounds = (cx - half, cy - half, cx + half, cy + half);
+                fill_where(image, bounds, *color, |x, y| {
+                    (x - cx).powi(2) + (y - cy).powi(2) <= half * half
+                });
+            }
+        }
+        Shape::Text {
+            centre,
+            size,
+            text,
+            color,
+        } => {
+            let (cx, cy) = at(*centre);
+            let pixel = size * scale / GLYPH_HEIGHT as f32;
+            let width = get_text_width(text, 1) as f32 * pixel;
+            let left = cx - width / 2.0;
+            let top = cy - size * scale / 2.0;
+            for (x, y, _, _) in get_text_rects(text, 0, 0, 1) {
+                let x = left + x as f32 * pixel;
+                let y = top + y as f32 * pixel;
+                let bounds = (x, y, x + pixel, y + pixel);
+                fill_where(image, bounds, *color, |px, py| {
+                    px >= x && px < x + pixel && py >= y && py < y + pixel
+                });
+            }
+        }
+    }
+}
+
+// averages each `factor` by `factor` square of pixels into one
+fn downsample(image: &Image, factor: u32) -> Image {
+    let mut small = Image::new(image.width / factor, image.height / factor);
+    for y in 0..small.height {
+        for x in 0..small.width {
+            let mut sum = [0u32; 4];
+            for j in 0..factor {
+                for i in 0..factor {
+                    let src = (((y * factor + j) * image.width + x * factor + i) * 4) as usize;
+                    for (c, total) in sum.iter_mut().enumerate() {
+                        *total += image.pixels[src + c] as u32;
+                    }
+                }
+            }
+            let dst = ((y * small.width + x) * 4) as usize;
+            for (c, total) in sum.iter().enumerate() {
+                small.pixels[dst + c] = (total / (factor * factor)) as u8;
+            }
+        }
+    }
+    small
+}
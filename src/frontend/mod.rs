@@ -0,0 +1,126 @@
+mod controller;
+mod diagram;
+mod font;
+mod new_game;
+mod options;
+mod practice;
+mod sprite;
+mod theme;
+
+pub use controller::*;
+pub use diagram::*;
+pub use font::*;
+pub use new_game::*;
+pub use options::*;
+pub use practice::*;
+pub use sprite::*;
+pub use theme::*;
+
+#[cfg(test)]
+mod tests;
+
+/// The keys the controller responds to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    Return,
+    Escape,
+    Char(char),
+}
+
+/// Renderer-independent input, already translated from the backend's own
+/// events. Positions are in window pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    MouseMove { x: i32, y: i32 },
+    MouseDown { x: i32, y: i32 },
+    KeyDown(Key),
+    ToggleOverlay,
+    Quit,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Layer {
+    Board,
+    Stones,
+    Overlay,
+    Hud,
+}
+
+pub const LAYERS: [Layer; 4] = [Layer::Board, Layer::Stones, Layer::Overlay, Layer::Hud];
+
+/// Something to draw, positioned in window pixels. Alpha goes from 0
+/// (invisible) to 255 (opaque).
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    Sprite {
+        layer: Layer,
+        sprite: Sprite,
+        x: i32,
+        y: i32,
+        size: u32,
+        alpha: u8,
+    },
+    Rect {
+        layer: Layer,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        color: Color,
+    },
+    /// Text in the built-in font (see [`get_text_rects`]), each font pixel
+    /// drawn `scale` pixels wide.
+    Text {
+        layer: Layer,
+        x: i32,
+        y: i32,
+        scale: u32,
+        text: String,
+        color: Color,
+    },
+}
+
+impl DrawCommand {
+    pub fn get_layer(&self) -> Layer {
+        match self {
+            DrawCommand::Sprite { layer, .. }
+            | DrawCommand::Rect { layer, .. }
+            | DrawCommand::Text { layer, .. } => *layer,
+        }
+    }
+}
+
+/// A windowing/rendering backend for the `GameController`.
+pub trait Frontend {
+    fn poll_events(&mut self) -> Vec<InputEvent>;
+    fn render(&mut self, draw_list: &[DrawCommand]) -> Result<(), String>;
+
+    /// Called every frame with the size the controller wants the window to
+    /// be, so backends that can resize their window should when it changes.
+    fn resize(&mut self, _width: u32, _height: u32) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Feeds one frame of input to the controller, lets a computer player move
+/// and renders the result.
+/// Returns false once the controller has been asked to quit.
+pub fn run_frame<F: Frontend>(
+    controller: &mut GameController,
+    frontend: &mut F,
+) -> Result<bool, String> {
+    for event in frontend.poll_events() {
+        controller.handle_event(event);
+    }
+    controller.update();
+    let (width, height) = controller.get_window_size();
+    frontend.resize(width, height)?;
+    frontend.render(&controller.get_draw_list())?;
+    Ok(controller.is_running())
+}
@@ -0,0 +1,212 @@
+use crate::engine::*;
+use crate::frontend::*;
+use crate::go_core::*;
+
+pub const BOARD_SIZES: [usize; 3] = [9, 13, 19];
+pub const HANDICAPS: [usize; 9] = [0, 2, 3, 4, 5, 6, 7, 8, 9];
+
+const DIALOG_BACKGROUND: Color = [0, 0, 0, 200];
+const DIALOG_TEXT: Color = [255, 255, 255, 255];
+const DIALOG_SELECTED: Color = [255, 215, 0, 255];
+const TEXT_SCALE: u32 = 2;
+const LINE_HEIGHT: i32 = 24;
+
+/// Who plays one of the colours.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PlayerType {
+    Human,
+    Random,
+    Mcts,
+}
+
+pub const PLAYER_TYPES: [PlayerType; 3] = [PlayerType::Human, PlayerType::Random, PlayerType::Mcts];
+
+impl PlayerType {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            PlayerType::Human => "human",
+            PlayerType::Random => "random",
+            PlayerType::Mcts => "mcts",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<PlayerType> {
+        PLAYER_TYPES
+            .iter()
+            .copied()
+            .find(|t| t.get_name().eq_ignore_ascii_case(name))
+    }
+
+    /// The engine that chooses this player's moves, or `None` for a human.
+    pub fn create_engine(&self) -> Option<Box<dyn MoveGenerator>> {
+        match self {
+            PlayerType::Human => None,
+            PlayerType::Random => Some(Box::new(RandomPlayer::new())),
+            PlayerType::Mcts => Some(Box::new(MctsPlayer::new(MctsConfig::default()))),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NewGameSettings {
+    pub size: usize,
+    pub handicap: usize,
+    pub placement: HandicapPlacement,
+    pub rules: RuleSet,
+    pub black: PlayerType,
+    pub white: PlayerType,
+    pub time: TimeControl,
+}
+
+impl Default for NewGameSettings {
+    fn default() -> Self {
+        NewGameSettings {
+            size: 13,
+            handicap: 0,
+            placement: HandicapPlacement::Fixed,
+            rules: RuleSet::default(),
+            black: PlayerType::Human,
+            white: PlayerType::Human,
+            time: TimeControl::Unlimited,
+        }
+    }
+}
+
+impl NewGameSettings {
+    pub fn create_board(&self) -> Result<Board, String> {
+        let mut board = Board::new(self.size, self.rules);
+        board.set_handicap(self.handicap, self.placement)?;
+        Ok(board)
+    }
+
+    pub fn get_player(&self, player: CellState) -> PlayerType {
+        match player {
+            CellState::White => self.white,
+            _ => self.black,
+        }
+    }
+}
+
+pub enum DialogResult {
+    Open,
+    Cancel,
+    Start(NewGameSettings),
+}
+
+/// Picks the board size, handicap, rules and players for the next game. Up
+/// and down choose a setting, left and right change it, return starts the
+/// game and escape goes back to the current one.
+pub struct NewGameDialog {
+    settings: NewGameSettings,
+    selected: usize,
+}
+
+const ROWS: usize = 6;
+
+impl NewGameDialog {
+    pub fn new(settings: NewGameSettings) -> NewGameDialog {
+        NewGameDialog {
+            settings,
+            selected: 0,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> DialogResult {
+        match key {
+            Key::Up => self.selected = (self.selected + ROWS - 1) % ROWS,
+            Key::Down => self.selected = (self.selected + 1) % ROWS,
+            Key::Left => self.change(-1),
+            Key::Right => self.change(1),
+            Key::Return => return DialogResult::Start(self.settings),
+            Key::Escape => return DialogResult::Cancel,
+            _ => {}
+        }
+        DialogResult::Open
+    }
+
+    fn change(&mut self, step: i32) {
+        match self.selected {
+            0 => self.settings.size = cycle(&BOARD_SIZES, self.settings.size, step),
+            1 => self.settings.handicap = cycle(&HANDICAPS, self.settings.handicap, step),
+            2 => {
+                self.settings.placement = match self.settings.placement {
+                    HandicapPlacement::Fixed => HandicapPlacement::Free,
+                    HandicapPlacement::Free => HandicapPlacement::Fixed,
+                }
+            }
+            3 => {
+                let presets = RuleSet::get_presets();
+                let idx = presets
+                    .iter()
+                    .position(|r| r.name == self.settings.rules.name)
+                    .unwrap_or(0) as i32;
+                let len = presets.len() as i32;
+                self.settings.rules = presets[((idx + step + len) % len) as usize];
+            }
+            4 => self.settings.black = cycle_player(self.settings.black, step),
+            _ => self.settings.white = cycle_player(self.settings.white, step),
+        }
+    }
+
+    pub fn draw(&self, width: u32, height: u32, draw_list: &mut Vec<DrawCommand>) {
+        draw_list.push(DrawCommand::Rect {
+            layer: Layer::Hud,
+            x: 0,
+            y: 0,
+            width,
+            height,
+            color: DIALOG_BACKGROUND,
+        });
+
+        let placement = match self.settings.placement {
+            HandicapPlacement::Fixed => "FIXED",
+            HandicapPlacement::Free => "FREE",
+        };
+        let rows = [
+            format!("SIZE      {}X{}", self.settings.size, self.settings.size),
+            format!("HANDICAP  {}", self.settings.handicap),
+            format!("PLACEMENT {}", placement),
+            format!("RULES     {}", self.settings.rules.name),
+            format!("BLACK     {}", self.settings.black.get_name()).to_uppercase(),
+            format!("WHITE     {}", self.settings.white.get_name()).to_uppercase(),
+        ];
+
+        let top = (height as i32 - LINE_HEIGHT * 10) / 2;
+        let left = 16;
+        draw_text(draw_list, left, top, "NEW GAME", DIALOG_TEXT);
+        for (i, row) in rows.iter().enumerate() {
+            let (marker, color) = if i == self.selected {
+                ("> ", DIALOG_SELECTED)
+            } else {
+                ("  ", DIALOG_TEXT)
+            };
+            let y = top + LINE_HEIGHT * (i as i32 + 2);
+            draw_text(draw_list, left, y, &format!("{}{}", marker, row), color);
+        }
+        let y = top + LINE_HEIGHT * 9;
+        draw_text(draw_list, left, y, "RETURN: START", DIALOG_TEXT);
+    }
+}
+
+fn cycle(options: &[usize], current: usize, step: i32) -> usize {
+    let idx = options.iter().position(|&o| o == current).unwrap_or(0) as i32;
+    let len = options.len() as i32;
+    options[((idx + step + len) % len) as usize]
+}
+
+fn cycle_player(current: PlayerType, step: i32) -> PlayerType {
+    let idx = PLAYER_TYPES.iter().position(|&t| t == current).unwrap_or(0) as i32;
+    let len = PLAYER_TYPES.len() as i32;
+    PLAYER_TYPES[((idx + step + len) % len) as usize]
+}
+
+fn draw_text(draw_list: &mut Vec<DrawCommand>, x: i32, y: i32, text: &str, color: Color) {
+    draw_list.push(DrawCommand::Text {
+        layer: Layer::Hud,
+        x,
+        y,
+        scale: TEXT_SCALE,
+        text: text.to_string(),
+        color,
+    });
+}
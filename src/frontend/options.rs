@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use crate::engine::*;
+use crate::frontend::*;
+use crate::go_core::*;
+use crate::net::*;
+use crate::sgf::*;
+
+const BOARD_SIZE: usize = 13;
+
+/// The command line options both front ends take, so that they start the
+/// same games.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub theme: String,
+    pub black: PlayerType,
+    pub white: PlayerType,
+    pub problems: Option<String>,
+    pub review: Option<String>,
+    pub book: Option<String>,
+    pub time: TimeControl,
+    pub connect: Option<String>,
+    pub watch: bool,
+    pub name: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            theme: DEFAULT_THEME.to_string(),
+            black: PlayerType::Human,
+            white: PlayerType::Human,
+            problems: None,
+            review: None,
+            book: None,
+            time: TimeControl::Unlimited,
+            connect: None,
+            watch: false,
+            name: "player".to_string(),
+        }
+    }
+}
+
+impl Options {
+    /// Reads the arguments after the program name: --theme <name>, --black
+    /// <player> and --white <player>, where a player is "human", "random" or
+    /// "mcts", --problems <file.sgf> to practise a problem collection,
+    /// --review <file.sgf> to step through a game record, --book <path> to
+    /// show the moves from an opening book or a directory of games and
+    /// --time <control> to play on the clock, e.g. "byoyomi:600+30x5".
+    /// --connect <host[:port]> plays a game hosted by go-server instead,
+    /// joining as --name <name>, and --watch <host[:port]> follows one as a
+    /// spectator.
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+        let mut parsed = Options::default();
+        while let Some(arg) = args.next() {
+            let value = match arg.as_str() {
+                "--theme" | "--black" | "--white" | "--problems" | "--review" | "--book"
+                | "--time" | "--connect" | "--watch" | "--name" => args
+                    .next()
+                    .ok_or_else(|| format!("{} needs a value", arg))?,
+                _ => return Err(format!("unknown option '{}'", arg)),
+            };
+            match arg.as_str() {
+                "--theme" => parsed.theme = value,
+                "--problems" => parsed.problems = Some(value),
+                "--review" => parsed.review = Some(value),
+                "--book" => parsed.book = Some(value),
+                "--time" => parsed.time = TimeControl::parse(&value)?,
+                "--connect" => parsed.connect = Some(value),
+                "--watch" => {
+                    parsed.connect = Some(value);
+                    parsed.watch = true;
+                }
+                "--name" => parsed.name = value,
+                _ => {
+                    let player = PlayerType::from_name(&value)
+                        .ok_or_else(|| format!("unknown player type '{}'", value))?;
+                    if arg == "--black" {
+                        parsed.black = player;
+                    } else {
+                        parsed.white = player;
+                    }
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// A controller for `tile_size` pixel tiles set up as the options say,
+    /// having loaded their files and connected to their server.
+    pub fn create_controller(&self, tile_size: u32) -> Result<GameController, String> {
+        let mut controller =
+            GameController::new(Board::new(BOARD_SIZE, RuleSet::default()), tile_size);
+        controller.set_player(CellState::Black, self.black);
+        controller.set_player(CellState::White, self.white);
+        controller.set_time_control(self.time);
+        if let Some(book) = &self.book {
+            controller.set_book(OpeningBook::open(Path::new(book))?);
+        }
+        if let Some(problems) = &self.problems {
+            controller.start_practice(Practice::load(Path::new(problems))?);
+        }
+        if let Some(review) = &self.review {
+            controller.start_review(Review::load(Path::new(review))?);
+        }
+        if let Some(address) = &self.connect {
+            let address = if address.contains(':') {
+                address.clone()
+            } else {
+                format!("{}:{}", address, DEFAULT_PORT)
+            };
+            let client = if self.watch {
+                GameClient::watch(&address, &self.name)?
+            } else {
+                GameClient::connect(&address, &self.name, None)?
+            };
+            controller.connect(client);
+        }
+        Ok(controller)
+    }
+}
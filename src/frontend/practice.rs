@@ -0,0 +1,113 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::go_core::*;
+use crate::sgf::*;
+
+/// A session working through an SGF problem collection. Which problems
+/// have been solved is kept in a progress file next to the collection,
+/// `problems.sgf` getting `problems.progress`, listing the solved problem
+/// numbers one per line.
+pub struct Practice {
+    problems: Vec<Problem>,
+    solved: Vec<bool>,
+    progress_path: PathBuf,
+    index: usize,
+}
+
+impl Practice {
+    pub fn load(path: &Path) -> Result<Practice, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        let mut problems = Vec::new();
+        for (i, root) in parse_sgf(&text)?.into_iter().enumerate() {
+            let problem = Problem::new(root).map_err(|e| format!("problem {}: {}", i + 1, e))?;
+            problems.push(problem);
+        }
+
+        let progress_path = path.with_extension("progress");
+        let mut solved = vec![false; problems.len()];
+        // no progress file just means nothing has been solved yet
+        if let Ok(progress) = fs::read_to_string(&progress_path) {
+            for number in progress
+                .lines()
+                .filter_map(|l| l.trim().parse::<usize>().ok())
+            {
+                if number >= 1 && number <= solved.len() {
+                    solved[number - 1] = true;
+                }
+            }
+        }
+
+        // start with the first problem that hasn't been solved
+        let index = solved.iter().position(|s| !s).unwrap_or(0);
+        Ok(Practice {
+            problems,
+            solved,
+            progress_path,
+            index,
+        })
+    }
+
+    pub fn get_problem(&self) -> &Problem {
+        &self.problems[self.index]
+    }
+
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get_count(&self) -> usize {
+        self.problems.len()
+    }
+
+    pub fn is_solved(&self, index: usize) -> bool {
+        self.solved[index]
+    }
+
+    pub fn get_solved_count(&self) -> usize {
+        self.solved.iter().filter(|s| **s).count()
+    }
+
+    /// Plays the player's move in the current problem, and records it in
+    /// the progress file if that solves the problem.
+    pub fn play(&mut self, mv: Option<Point>) -> Result<(), String> {
+        let problem = &mut self.problems[self.index];
+        problem.play(mv)?;
+        if problem.get_status() == ProblemStatus::Solved && !self.solved[self.index] {
+            self.solved[self.index] = true;
+            self.save_progress()?;
+        }
+        Ok(())
+    }
+
+    /// Starts the current problem again.
+    pub fn retry(&mut self) {
+        self.problems[self.index].reset();
+    }
+
+    /// Moves on to the next problem, wrapping around, and starts it.
+    pub fn next(&mut self) {
+        self.go_to((self.index + 1) % self.problems.len());
+    }
+
+    /// Goes back to the previous problem, wrapping around, and starts it.
+    pub fn previous(&mut self) {
+        let count = self.problems.len();
+        self.go_to((self.index + count - 1) % count);
+    }
+
+    fn go_to(&mut self, index: usize) {
+        self.index = index;
+        self.retry();
+    }
+
+    fn save_progress(&self) -> Result<(), String> {
+        let mut text = String::new();
+        for (i, _) in self.solved.iter().enumerate().filter(|(_, s)| **s) {
+            text += &format!("{}\n", i + 1);
+        }
+        fs::write(&self.progress_path, text)
+            .map_err(|e| format!("couldn't write {}: {}", self.progress_path.display(), e))
+    }
+}
@@ -0,0 +1,114 @@
+// The order matches the 4x4 layout of resources/board_lines.png, so a
+// sprite's index is also its tile index in that sheet.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sprite {
+    Cross,
+    CrossDot,
+    South,
+    North,
+
+    East,
+    West,
+    Northwest,
+    Southwest,
+
+    Southeast,
+    Northeast,
+    RedDot,
+    BlueDot,
+
+    GreenDot,
+    PinkDot,
+    White,
+    Black,
+}
+
+pub const SPRITES: [Sprite; 16] = [
+    Sprite::Cross,
+    Sprite::CrossDot,
+    Sprite::South,
+    Sprite::North,
+    Sprite::East,
+    Sprite::West,
+    Sprite::Northwest,
+    Sprite::Southwest,
+    Sprite::Southeast,
+    Sprite::Northeast,
+    Sprite::RedDot,
+    Sprite::BlueDot,
+    Sprite::GreenDot,
+    Sprite::PinkDot,
+    Sprite::White,
+    Sprite::Black,
+];
+
+impl Sprite {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Sprite::Cross => "cross",
+            Sprite::CrossDot => "cross_dot",
+            Sprite::South => "south",
+            Sprite::North => "north",
+            Sprite::East => "east",
+            Sprite::West => "west",
+            Sprite::Northwest => "northwest",
+            Sprite::Southwest => "southwest",
+            Sprite::Southeast => "southeast",
+            Sprite::Northeast => "northeast",
+            Sprite::RedDot => "red_dot",
+            Sprite::BlueDot => "blue_dot",
+            Sprite::GreenDot => "green_dot",
+            Sprite::PinkDot => "pink_dot",
+            Sprite::White => "white",
+            Sprite::Black => "black",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Sprite> {
+        SPRITES.iter().copied().find(|s| s.get_name() == name)
+    }
+
+    pub fn is_board_line(&self) -> bool {
+        (*self as usize) <= Sprite::Northeast as usize
+    }
+
+    /// The board line sprite drawn under the intersection `(x, y)`.
+    pub fn for_intersection(size: usize, x: usize, y: usize) -> Sprite {
+        let last = size - 1;
+        if x == 0 && y == 0 {
+            Sprite::Northwest
+        } else if x == 0 && y == last {
+            Sprite::Southwest
+        } else if x == last && y == 0 {
+            Sprite::Northeast
+        } else if x == last && y == last {
+            Sprite::Southeast
+        } else if x == 0 {
+            Sprite::West
+        } else if x == last {
+            Sprite::East
+        } else if y == 0 {
+            Sprite::North
+        } else if y == last {
+            Sprite::South
+        } else if is_star_point(size, x, y) {
+            Sprite::CrossDot
+        } else {
+            Sprite::Cross
+        }
+    }
+}
+
+pub fn is_star_point(size: usize, x: usize, y: usize) -> bool {
+    if size == 19 {
+        (x == 3 || x == 9 || x == 15) && (y == 3 || y == 9 || y == 15)
+    } else if size == 13 {
+        ((x == 3 || x == 9) && (y == 3 || y == 9)) || (x == 6 && y == 6)
+    } else if size == 9 {
+        (x == 2 || x == 6) && (y == 2 || y == 6)
+    } else if size == 5 {
+        x == 2 && y == 2
+    } else {
+        false
+    }
+}
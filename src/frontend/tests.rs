@@ -0,0 +1,300 @@
+use crate::frontend::*;
+use crate::go_core::*;
+use crate::sgf::*;
+
+#[test]
+fn test_diagram() {
+    let mut board = Board::new(9, RuleSet::japanese());
+    board.place(Point::new(2, 2));
+    board.place(Point::new(6, 6));
+    board.place(Point::new(6, 2));
+
+    let mut diagram = Diagram::new(&board);
+    diagram.number_moves_from(2);
+    diagram.add_mark(Point::new(2, 2), Mark::Triangle);
+    diagram.add_mark(Point::new(4, 4), Mark::Label("A".to_string()));
+    diagram.add_mark(Point::new(3, 3), Mark::Circle);
+    let svg = diagram.to_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    // three stones, the circle, the one star point left uncovered and the
+    // cut-out under the label
+    assert_eq!(svg.matches("<circle").count(), 3 + 1 + 1 + 1);
+    assert_eq!(svg.matches("<polygon").count(), 1);
+    assert!(svg.contains(">A</text>"));
+    assert!(svg.contains(">J</text>"));
+
+    // the middle of the board, without coordinates: the lines run on where
+    // the board does, and only the second and third moves are numbered
+    diagram
+        .set_region(Point::new(6, 6), Point::new(2, 2))
+        .unwrap();
+    diagram.set_coordinates(false);
+    let svg = diagram.to_svg();
+    assert!(!svg.contains(">J</text>"));
+    assert!(svg.contains(">A</text>"));
+    assert!(svg.contains(">2</text>"));
+    assert!(svg.contains(">3</text>"));
+    assert!(!svg.contains(">1</text>"));
+    assert_eq!(diagram.get_size(), (4.0 * 24.0 + 36.0, 4.0 * 24.0 + 36.0));
+    assert!(diagram
+        .set_region(Point::new(0, 0), Point::new(9, 9))
+        .is_err());
+
+    // a record's circles and crosses are drawn too
+    let lines = svg.matches("<line").count();
+    let circles = svg.matches("<circle").count();
+    diagram.add_markup(&Markup {
+        circles: vec![Point::new(5, 3)],
+        crosses: vec![Point::new(3, 5)],
+        ..Markup::default()
+    });
+    let svg = diagram.to_svg();
+    assert_eq!(svg.matches("<circle").count(), circles + 1);
+    assert_eq!(svg.matches("<line").count(), lines + 2);
+
+    let image = diagram.to_image();
+    assert_eq!((image.width, image.height), (132, 132));
+    let pixel = |x: u32, y: u32| {
+        let i = ((y * image.width + x) * 4) as usize;
+        [
+            image.pixels[i],
+            image.pixels[i + 1],
+            image.pixels[i + 2],
+            image.pixels[i + 3],
+        ]
+    };
+    let style = DiagramStyle::default();
+    assert_eq!(pixel(1, 1), style.background);
+    // the white stone at G3, with its number in black
+    let (x, y) = (18 + 4 * 24, 18 + 4 * 24);
+    assert_eq!(pixel(x - 9, y), style.white);
+    assert!((x - 4..x + 4)
+        .flat_map(|x| (y - 4..y + 4).map(move |y| (x, y)))
+        .any(|(x, y)| pixel(x, y) == style.line));
+}
+
+#[test]
+fn test_theme() {
+    let dir = std::env::temp_dir().join(format!("go-theme-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // a sheet of two 8x8 tiles, red then blue
+    let mut sheet = Image::new(16, 8);
+    for (i, px) in sheet.pixels.chunks_mut(4).enumerate() {
+        let color = if i % 16 < 8 {
+            [255, 0, 0, 255]
+        } else {
+            [0, 0, 255, 255]
+        };
+        px.copy_from_slice(&color);
+    }
+    sheet.save_png(&dir.join("sheet.png")).unwrap();
+    let manifest = dir.join("test.theme");
+    std::fs::write(
+        &manifest,
+        "tile_size = 8\nbackground = 12 x 34 56\n\
+         black = sheet.png 1 0\nred_dot = sheet.png\nwhite = missing.png\n",
+    )
+    .unwrap();
+
+    let theme = Theme::load(manifest.to_str().unwrap());
+    assert_eq!(theme.tile_size, 8);
+    // the background can't be read, so the default is kept
+    assert_eq!(theme.background, [220, 179, 92, 255]);
+    assert_eq!(
+        theme.get_image(Sprite::Black).pixels,
+        [0, 0, 255, 255].repeat(64)
+    );
+    assert_eq!(
+        theme.get_image(Sprite::RedDot).pixels,
+        [255, 0, 0, 255].repeat(64)
+    );
+    // the missing sheet and the sprites the manifest leaves out are drawn
+    let white = theme.get_image(Sprite::White);
+    assert_eq!((white.width, white.height), (8, 8));
+    assert_eq!(white.pixels[..4], [0, 0, 0, 0]);
+    let centre = (4 * 8 + 4) * 4;
+    assert_eq!(white.pixels[centre..centre + 4], [240, 240, 240, 255]);
+    let cross = theme.get_image(Sprite::Cross);
+    assert_eq!((cross.width, cross.height), (8, 8));
+    assert!(cross.pixels.chunks(4).all(|px| px[3] == 255));
+
+    // tile sizes are kept to something drawable, and tiles far off the sheet
+    // are drawn rather than overflowing
+    std::fs::write(
+        &manifest,
+        "tile_size = 0
+black = sheet.png 4294967295 0
+",
+    )
+    .unwrap();
+    let theme = Theme::load(manifest.to_str().unwrap());
+    assert_eq!(theme.tile_size, 8);
+    let black = theme.get_image(Sprite::Black);
+    assert_eq!((black.width, black.height), (8, 8));
+    assert_eq!(black.pixels[..4], [0, 0, 0, 0]);
+    std::fs::write(
+        &manifest,
+        "tile_size = 100000
+",
+    )
+    .unwrap();
+    assert_eq!(Theme::load(manifest.to_str().unwrap()).tile_size, 256);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// the stone sprites drawn on `p` with a 32 pixel tile, and their alphas
+fn get_stones_at(draw_list: &[DrawCommand], p: Point) -> Vec<(Sprite, u8)> {
+    draw_list
+        .iter()
+        .filter_map(|command| match command {
+            DrawCommand::Sprite {
+                layer: Layer::Stones,
+                sprite,
+                x,
+                y,
+                alpha,
+                ..
+            } if (*x, *y) == ((p.x + 1) * 32, (p.y + 1) * 32) => Some((*sprite, *alpha)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_controller() {
+    let mut controller = GameController::new(Board::new(9, RuleSet::japanese()), 32);
+    assert_eq!(controller.get_window_size(), (11 * 32, 11 * 32));
+
+    // the board starts a tile in from the edge of the window
+    assert_eq!(controller.point_at(10, 10), None);
+    assert_eq!(controller.point_at(32, 32), Some(Point::new(0, 0)));
+    assert_eq!(
+        controller.point_at(4 * 32 + 31, 2 * 32),
+        Some(Point::new(3, 1))
+    );
+    assert_eq!(controller.point_at(10 * 32, 5 * 32), None);
+    assert_eq!(controller.point_at(-5, 40), None);
+
+    // hovering shows a translucent stone for whoever is to play
+    let d4 = Point::new(3, 3);
+    controller.handle_event(InputEvent::MouseMove {
+        x: 4 * 32 + 5,
+        y: 4 * 32 + 5,
+    });
+    let draw_list = controller.get_draw_list();
+    assert_eq!(get_stones_at(&draw_list, d4), [(Sprite::Black, 160)]);
+    let stones = draw_list
+        .iter()
+        .filter(|command| command.get_layer() == Layer::Stones)
+        .count();
+    assert_eq!(stones, 1);
+
+    controller.handle_event(InputEvent::MouseDown {
+        x: 4 * 32 + 5,
+        y: 4 * 32 + 5,
+    });
+    assert_eq!(controller.get_game().get(d4), CellState::Black);
+    assert_eq!(controller.get_game().get_turn(), CellState::White);
+    // the stone is played and the point is no longer free for a ghost
+    let draw_list = controller.get_draw_list();
+    assert_eq!(get_stones_at(&draw_list, d4), [(Sprite::Black, 255)]);
+
+    // clicking the stone again, or off the board, does nothing
+    controller.handle_event(InputEvent::MouseDown {
+        x: 4 * 32 + 5,
+        y: 4 * 32 + 5,
+    });
+    controller.handle_event(InputEvent::MouseDown { x: 5, y: 5 });
+    assert_eq!(controller.get_game().get_moves().len(), 1);
+    assert_eq!(controller.get_game().get_turn(), CellState::White);
+
+    // White can't play in the corner Black has taken away
+    let mut board = Board::new(9, RuleSet::japanese());
+    board.place(Point::new(1, 0));
+    board.place(Point::new(5, 5));
+    board.place(Point::new(0, 1));
+    let mut controller = GameController::new(board, 32);
+    controller.handle_event(InputEvent::MouseMove { x: 32, y: 32 });
+    let corner = Point::new(0, 0);
+    assert!(get_stones_at(&controller.get_draw_list(), corner).is_empty());
+    controller.handle_event(InputEvent::MouseDown { x: 32, y: 32 });
+    assert_eq!(controller.get_game().get(corner), CellState::None);
+    assert_eq!(controller.get_game().get_moves().len(), 3);
+
+    controller.handle_event(InputEvent::KeyDown(Key::Char('p')));
+    assert_eq!(
+        controller.get_game().get_moves().last(),
+        Some(&(CellState::White, None))
+    );
+    assert!(controller.is_running());
+    controller.handle_event(InputEvent::KeyDown(Key::Escape));
+    assert!(!controller.is_running());
+}
+
+#[test]
+fn test_review_markup() {
+    let text = "(;SZ[9];B[cc]CR[ee]MA[gg])";
+    let mut review = Review::new(parse_sgf(text).unwrap().remove(0)).unwrap();
+    review.forward();
+    let mut controller = GameController::new(Board::new(9, RuleSet::japanese()), 32);
+    controller.start_review(review);
+
+    let draw_list = controller.get_draw_list();
+    let covers = |x: i32, y: i32| {
+        draw_list.iter().any(|command| match *command {
+            DrawCommand::Rect {
+                layer: Layer::Overlay,
+                x: left,
+                y: top,
+                width,
+                height,
+                ..
+            } => {
+                (left..left + width as i32).contains(&x) && (top..top + height as i32).contains(&y)
+            }
+            _ => false,
+        })
+    };
+    // the circle is a ring around its point and the cross runs through it
+    let centre = |p: Point| ((p.x + 1) * 32 + 16, (p.y + 1) * 32 + 16);
+    let (x, y) = centre(Point::new(4, 4));
+    assert!(!covers(x, y));
+    assert!(covers(x - 7, y) && covers(x + 7, y) && covers(x, y - 7) && covers(x, y + 7));
+    let (x, y) = centre(Point::new(6, 6));
+    assert!(covers(x, y));
+    assert!(covers(x - 7, y - 7) && covers(x + 6, y - 7));
+    assert!(!covers(x, y - 7));
+
+    // tiles too small for the marks or a line of the comment still draw
+    let text = "(;SZ[9];B[cc]CR[ee]MA[gg]TR[dd]SQ[ff]C[a comment])";
+    let mut review = Review::new(parse_sgf(text).unwrap().remove(0)).unwrap();
+    review.forward();
+    for tile_size in 0..4 {
+        let mut controller = GameController::new(Board::new(9, RuleSet::japanese()), tile_size);
+        controller.start_review(review.clone());
+        assert!(!controller.get_draw_list().is_empty());
+    }
+}
+
+#[test]
+fn test_options() {
+    let args = |line: &str| {
+        line.split_whitespace()
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(Options::parse(args("").into_iter()), Ok(Options::default()));
+
+    let options = Options::parse(args("--white mcts --watch localhost --name kim").into_iter());
+    let options = options.unwrap();
+    assert_eq!(options.black, PlayerType::Human);
+    assert_eq!(options.white, PlayerType::Mcts);
+    assert_eq!(options.connect, Some("localhost".to_string()));
+    assert!(options.watch);
+    assert_eq!(options.name, "kim");
+
+    assert!(Options::parse(args("--black").into_iter()).is_err());
+    assert!(Options::parse(args("--black god").into_iter()).is_err());
+    assert!(Options::parse(args("--board 9").into_iter()).is_err());
+}
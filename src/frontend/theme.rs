@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::frontend::{Sprite, SPRITES};
+
+pub type Color = [u8; 4];
+
+pub const DEFAULT_THEME: &str = "default";
+const DEFAULT_TILE_SIZE: u32 = 32;
+const MIN_TILE_SIZE: u32 = 8;
+const MAX_TILE_SIZE: u32 = 256;
+const DEFAULT_BACKGROUND: Color = [220, 179, 92, 255];
+const LINE: Color = [0, 0, 0, 255];
+
+/// An 8-bit RGBA image held in memory, row by row.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    pub fn load_png(path: &Path) -> Result<Image, String> {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut decoder = png::Decoder::new(file);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder
+            .read_info()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buf)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        let mut image = Image::new(info.width, info.height);
+        let channels = info.color_type.samples();
+        for (i, px) in buf[..info.buffer_size()].chunks(channels).enumerate() {
+            let rgba = match info.color_type {
+                png::ColorType::Rgba => [px[0], px[1], px[2], px[3]],
+                png::ColorType::Rgb => [px[0], px[1], px[2], 255],
+                png::ColorType::GrayscaleAlpha => [px[0], px[0], px[0], px[1]],
+                _ => [px[0], px[0], px[0], 255],
+            };
+            image.pixels[i * 4..i * 4 + 4].copy_from_slice(&rgba);
+        }
+        Ok(image)
+    }
+
+    pub fn save_png(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Option<Image> {
+        let fits = |start: u32, len: u32, end| start.checked_add(len).is_some_and(|e| e <= end);
+        if !fits(x, width, self.width) || !fits(y, height, self.height) {
+            return None;
+        }
+        let mut image = Image::new(width, height);
+        for row in 0..height {
+            let src = (((y + row) * self.width + x) * 4) as usize;
+            let dst = (row * width * 4) as usize;
+            let len = (width * 4) as usize;
+            image.pixels[dst..dst + len].copy_from_slice(&self.pixels[src..src + len]);
+        }
+        Some(image)
+    }
+
+    fn put(&mut self, x: i32, y: i32, color: Color) {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 4) as usize;
+        self.pixels[i..i + 4].copy_from_slice(&color);
+    }
+
+    fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        for j in y..y + height {
+            for i in x..x + width {
+                self.put(i, j, color);
+            }
+        }
+    }
+
+    fn fill_circle(&mut self, cx: f32, cy: f32, radius: f32, color: Color) {
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let dx = x as f32 + 0.5 - cx;
+                let dy = y as f32 + 0.5 - cy;
+                if dx * dx + dy * dy <= radius * radius {
+                    self.put(x, y, color);
+                }
+            }
+        }
+    }
+
+    // Draws this image over `color` using the alpha channel.
+    fn flatten(&mut self, color: Color) {
+        for px in self.pixels.chunks_mut(4) {
+            let a = px[3] as u32;
+            for c in 0..3 {
+                px[c] = ((px[c] as u32 * a + color[c] as u32 * (255 - a)) / 255) as u8;
+            }
+            px[3] = 255;
+        }
+    }
+}
+
+/// Board, stone and marker sprites, either cut from PNG sprite sheets named
+/// in a theme manifest or drawn procedurally when an asset is missing.
+pub struct Theme {
+    pub tile_size: u32,
+    pub background: Color,
+    images: Vec<Image>,
+}
+
+impl Theme {
+    /// Loads a theme by name (`resources/<name>.theme`) or by manifest path.
+    /// This never fails: anything that can't be loaded is drawn instead.
+    pub fn load(name: &str) -> Theme {
+        let manifest = find_manifest(name);
+        let mut tile_size = DEFAULT_TILE_SIZE;
+        let mut background = DEFAULT_BACKGROUND;
+        let mut images: HashMap<PathBuf, Option<Image>> = HashMap::new();
+        let mut sprites: Vec<Option<Image>> = SPRITES.iter().map(|_| None).collect();
+
+        match &manifest {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(text) => {
+                    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+                    let entries = parse_manifest(&text);
+                    if let Some(size) = entries.get("tile_size").and_then(|v| v.parse().ok()) {
+                        tile_size = u32::clamp(size, MIN_TILE_SIZE, MAX_TILE_SIZE);
+                    }
+                    if let Some(color) = entries.get("background").and_then(|v| parse_color(v)) {
+                        background = color;
+                    }
+                    for (key, value) in &entries {
+                        if let Some(spr) = Sprite::from_name(key) {
+                            sprites[spr as usize] = load_sprite(dir, value, tile_size, &mut images);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("couldn't read theme {}: {}", path.display(), e),
+            },
+            None => eprintln!("couldn't find theme '{}', using fallback graphics", name),
+        }
+
+        // board lines are opaque so that themes can leave the wood out
+        let images = SPRITES
+            .iter()
+            .zip(sprites)
+            .map(|(&spr, image)| {
+                let mut image = image.unwrap_or_else(|| draw_sprite(spr, tile_size, background));
+                if spr.is_board_line() {
+                    image.flatten(background);
+                }
+                image
+            })
+            .collect();
+
+        Theme {
+            tile_size,
+            background,
+            images,
+        }
+    }
+
+    pub fn get_image(&self, spr: Sprite) -> &Image {
+        &self.images[spr as usize]
+    }
+}
+
+fn parse_manifest(text: &str) -> HashMap<String, String> {
+    let mut entries = HashMap::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if let Some(idx) = line.find('=') {
+            let key = line[..idx].trim().to_string();
+            let value = line[idx + 1..].trim().to_string();
+            entries.insert(key, value);
+        }
+    }
+    entries
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    let parts: Vec<u8> = value
+        .split_whitespace()
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    match parts[..] {
+        [r, g, b] => Some([r, g, b, 255]),
+        [r, g, b, a] => Some([r, g, b, a]),
+        _ => None,
+    }
+}
+
+// A sprite entry looks like `board_lines.png 2 3`: the sheet, then the
+// column and row of the tile within it.
+fn load_sprite(
+    dir: &Path,
+    value: &str,
+    tile_size: u32,
+    images: &mut HashMap<PathBuf, Option<Image>>,
+) -> Option<Image> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let (file, col, row) = match parts[..] {
+        [file] => (file, 0u32, 0u32),
+        [file, col, row] => (file, col.parse().ok()?, row.parse().ok()?),
+        _ => {
+            eprintln!("bad sprite entry '{}'", value);
+            return None;
+        }
+    };
+    let path = dir.join(file);
+    let sheet = images.entry(path.clone()).or_insert_with(|| {
+        Image::load_png(&path)
+            .map_err(|e| eprintln!("couldn't load {}", e))
+            .ok()
+    });
+    sheet.as_ref()?.crop(
+        col.checked_mul(tile_size)?,
+        row.checked_mul(tile_size)?,
+        tile_size,
+        tile_size,
+    )
+}
+
+fn draw_sprite(spr: Sprite, size: u32, background: Color) -> Image {
+    let mut image = Image::new(size, size);
+    let s = size as i32;
+    let c = s / 2;
+    let (north, south, east, west) = match spr {
+        Sprite::Cross | Sprite::CrossDot => (true, true, true, true),
+        Sprite::North => (false, true, true, true),
+        Sprite::South => (true, false, true, true),
+        Sprite::East => (true, true, false, true),
+        Sprite::West => (true, true, true, false),
+        Sprite::Northwest => (false, true, true, false),
+        Sprite::Northeast => (false, true, false, true),
+        Sprite::Southwest => (true, false, true, false),
+        Sprite::Southeast => (true, false, false, true),
+        _ => (false, false, false, false),
+    };
+
+    let radius = size as f32 / 2.0;
+    match spr {
+        Sprite::White => {
+            image.fill_circle(radius, radius, radius - 1.0, LINE);
+            image.fill_circle(radius, radius, radius - 2.0, [240, 240, 240, 255]);
+        }
+        Sprite::Black => image.fill_circle(radius, radius, radius - 1.0, LINE),
+        Sprite::RedDot => image.fill_circle(radius, radius, radius / 3.0, [255, 0, 0, 255]),
+        Sprite::BlueDot => image.fill_circle(radius, radius, radius / 3.0, [0, 0, 255, 255]),
+        Sprite::GreenDot => image.fill_circle(radius, radius, radius / 3.0, [0, 255, 0, 255]),
+        Sprite::PinkDot => image.fill_circle(radius, radius, radius / 3.0, [255, 105, 180, 255]),
+        _ => {
+            image.fill_rect(0, 0, s, s, background);
+            if north {
+                image.fill_rect(c, 0, 1, c + 1, LINE);
+            }
+            if south {
+                image.fill_rect(c, c, 1, s - c, LINE);
+            }
+            if west {
+                image.fill_rect(0, c, c + 1, 1, LINE);
+            }
+            if east {
+                image.fill_rect(c, c, s - c, 1, LINE);
+            }
+            if spr == Sprite::CrossDot {
+                image.fill_circle(c as f32 + 0.5, c as f32 + 0.5, 3.0, LINE);
+            }
+        }
+    }
+    image
+}
+
+// Resources are looked up next to the executable first (and in its parent
+// directories, which covers `target/debug`), then in the working directory.
+pub fn find_resource_dir() -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Ok(exe) = std::env::current_exe() {
+        candidates.extend(exe.ancestors().skip(1).take(4).map(|d| d.join("resources")));
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        candidates.push(cwd.join("resources"));
+    }
+    candidates.into_iter().find(|d| d.is_dir())
+}
+
+fn find_manifest(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if path.extension().is_some() || path.components().count() > 1 {
+        return if path.is_file() {
+            Some(path.to_path_buf())
+        } else {
+            None
+        };
+    }
+    let manifest = find_resource_dir()?.join(format!("{}.theme", name));
+    if manifest.is_file() {
+        Some(manifest)
+    } else {
+        None
+    }
+}
@@ -1,16 +1,32 @@
 use crate::go_core::*;
 
+/// A square Go board together with the rules, whose turn it is, the
+/// captures so far, the current ko point and the history of the game.
+///
+/// Points are zero-based `(x, y)` coordinates with `(0, 0)` in the top left.
+#[derive(Clone)]
 pub struct Board {
     cells: Vec<Vec<CellState>>,
     turn: CellState,
     size: usize,
     captured_stones: Vec<i32>,
     ko: Option<Point>,
-    pub allow_suicide: bool,
+    setup_stones: Vec<(CellState, Point)>,
+    moves: Vec<(CellState, Option<Point>)>,
+    handicap: usize,
+    handicap_remaining: usize,
+    rules: RuleSet,
+    komi: f32,
+    hash: u64,
+    // every position so far with the player to move, for superko
+    history: Vec<(u64, CellState)>,
+    forfeit: Option<Forfeit>,
 }
 
 impl Board {
-    pub fn new(size: usize) -> Board {
+    /// Creates an empty `size` x `size` board with Black to play under
+    /// `rules`, using their default komi.
+    pub fn new(size: usize, rules: RuleSet) -> Board {
         let mut cells: Vec<Vec<CellState>> = Vec::new();
         for _ in 0..size {
             let mut row: Vec<CellState> = Vec::new();
@@ -28,10 +44,22 @@ impl Board {
             captured_stones,
             turn: CellState::Black,
             ko: None,
-            allow_suicide: false,
+            setup_stones: Vec::new(),
+            moves: Vec::new(),
+            handicap: 0,
+            handicap_remaining: 0,
+            rules,
+            komi: rules.komi,
+            hash: 0,
+            history: vec![(0, CellState::Black)],
+            forfeit: None,
         }
     }
 
+    /// Parses a board from rows of characters, one row per line: `B`/`b` is
+    /// a black stone, `W`/`w` a white stone and anything else is empty.
+    /// Surrounding whitespace is ignored. The board uses the default rules;
+    /// see [`Board::set_rules`].
     pub fn from_str(s: &str, turn: CellState) -> Board {
         let mut cells = Vec::new();
 
@@ -46,40 +74,313 @@ impl Board {
             }
             cells.push(row);
         }
-        let size = cells.len() as usize;
+        let size = cells.len();
         let captured_stones = vec![0, 0, 0];
 
+        let mut setup_stones = Vec::new();
+        let mut hash = 0;
+        for (y, row) in cells.iter().enumerate() {
+            for (x, &state) in row.iter().enumerate() {
+                if state != CellState::None {
+                    let p = Point::new(x as i32, y as i32);
+                    setup_stones.push((state, p));
+                    hash ^= get_zobrist_key(p, state);
+                }
+            }
+        }
+        let rules = RuleSet::default();
+
         Board {
             turn,
             size,
             cells,
             captured_stones,
             ko: None,
-            allow_suicide: false,
+            setup_stones,
+            moves: Vec::new(),
+            handicap: 0,
+            handicap_remaining: 0,
+            rules,
+            komi: rules.komi,
+            hash,
+            history: vec![(hash, turn)],
+            forfeit: None,
+        }
+    }
+
+    /// Creates a board with `stones` already on it and `turn` to play, as
+    /// for a problem or a game record's setup. The stones count as setup
+    /// stones, not moves, and nothing is captured.
+    pub fn from_setup(
+        size: usize,
+        rules: RuleSet,
+        stones: &[(CellState, Point)],
+        turn: CellState,
+    ) -> Result<Board, String> {
+        let mut board = Board::new(size, rules);
+        for &(state, p) in stones {
+            if p.x < 0 || p.y < 0 || p.x as usize >= size || p.y as usize >= size {
+                return Err(format!("the setup stone at {:?} is off the board", p));
+            }
+            if state == CellState::None || board.get(p) != CellState::None {
+                continue;
+            }
+            board.set(p, state);
+            board.setup_stones.push((state, p));
+        }
+        board.turn = turn;
+        board.history = vec![(board.hash, turn)];
+        Ok(board)
+    }
+
+    /// Like [`Board::from_setup`], for a position part way through a game:
+    /// `captures` is how many stones Black and White have captured and `ko`
+    /// the point the player to move can't retake at.
+    pub fn from_position(
+        size: usize,
+        rules: RuleSet,
+        stones: &[(CellState, Point)],
+        turn: CellState,
+        captures: (i32, i32),
+        ko: Option<Point>,
+    ) -> Result<Board, String> {
+        let mut board = Board::from_setup(size, rules, stones, turn)?;
+        if let Some(p) = ko {
+            if p.x < 0 || p.y < 0 || p.x as usize >= size || p.y as usize >= size {
+                return Err(format!("the ko point {:?} is off the board", p));
+            }
+        }
+        board.captured_stones[CellState::Black as usize] = captures.0;
+        board.captured_stones[CellState::White as usize] = captures.1;
+        board.ko = ko;
+        Ok(board)
+    }
+
+    /// The same game seen through `transform`: the setup stones, the moves,
+    /// the captures, the ko point and whose turn it is are all mapped. The
+    /// moves are replayed, so superko sees the transformed history too.
+    pub fn transform(&self, transform: Transform) -> Board {
+        let size = self.size;
+        let mut board = Board::new(size, self.rules);
+        board.komi = self.komi;
+        for &(state, p) in &self.setup_stones {
+            let (state, p) = (transform.apply_state(state), transform.apply_point(p, size));
+            board.set(p, state);
+            board.setup_stones.push((state, p));
+        }
+        board.handicap = self.handicap;
+        board.handicap_remaining = self.handicap_remaining;
+        let first = self.moves.first().map_or(self.turn, |&(player, _)| player);
+        board.turn = transform.apply_state(first);
+        board.history = vec![(board.hash, board.turn)];
+        for &(player, mv) in &self.moves {
+            board.set_turn(transform.apply_state(player));
+            board.play(mv.map(|p| transform.apply_point(p, size)));
+        }
+
+        // the rules may have changed part way through the game, so the
+        // replay is only trusted for the history and the rest is copied
+        board.moves = self
+            .moves
+            .iter()
+            .map(|&(player, mv)| {
+                let mv = mv.map(|p| transform.apply_point(p, size));
+                (transform.apply_state(player), mv)
+            })
+            .collect();
+        for y in 0..size {
+            for x in 0..size {
+                let p = Point::new(x as i32, y as i32);
+                let state = transform.apply_state(self.get(p));
+                board.set(transform.apply_point(p, size), state);
+            }
+        }
+        for &player in [CellState::Black, CellState::White].iter() {
+            board.captured_stones[transform.apply_state(player) as usize] =
+                self.captured_stones[player as usize];
         }
+        board.turn = transform.apply_state(self.turn);
+        board.ko = self.ko.map(|p| transform.apply_point(p, size));
+        board.forfeit = self.forfeit.map(|forfeit| Forfeit {
+            loser: transform.apply_state(forfeit.loser),
+            ..forfeit
+        });
+        board
     }
 
+    /// The number of lines along each side of the board.
     pub fn get_size(&self) -> usize {
-        return self.size;
+        self.size
     }
 
+    /// The colour that plays next.
     pub fn get_turn(&self) -> CellState {
-        return self.turn;
+        self.turn
+    }
+
+    pub fn get_rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// Changes the rules. The komi stays as it is.
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        self.rules = rules;
+    }
+
+    pub fn get_komi(&self) -> f32 {
+        self.komi
     }
 
+    pub fn set_komi(&mut self, komi: f32) {
+        self.komi = komi;
+    }
+
+    /// A Zobrist hash of the stones on the board (not whose turn it is).
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// The stones that were on the board before the first move, including
+    /// any handicap stones.
+    pub fn get_setup_stones(&self) -> &[(CellState, Point)] {
+        &self.setup_stones
+    }
+
+    /// The moves played so far, in order, not counting handicap stones.
+    /// A pass is `None`.
+    pub fn get_moves(&self) -> &[(CellState, Option<Point>)] {
+        &self.moves
+    }
+
+    /// The point the player to move can't play at because of a simple ko.
+    pub fn get_ko(&self) -> Option<Point> {
+        self.ko
+    }
+
+    /// The number of handicap stones Black was given (0 for an even game).
+    pub fn get_handicap(&self) -> usize {
+        self.handicap
+    }
+
+    /// Whether Black is still placing free handicap stones.
+    pub fn is_placing_handicap(&self) -> bool {
+        self.handicap_remaining > 0
+    }
+
+    /// How many free handicap stones Black still has to place.
+    pub fn get_handicap_remaining(&self) -> usize {
+        self.handicap_remaining
+    }
+
+    /// Gives Black `stones` handicap stones. This must be done before the
+    /// first move. Fixed handicaps are placed on the star points straight
+    /// away (see [`get_fixed_handicap_points`]); with free placement Black's
+    /// next `stones` calls to [`Board::place`] put down the handicap. Either
+    /// way White plays first once the handicap is on the board.
+    ///
+    /// A handicap of 0 removes any handicap.
+    pub fn set_handicap(
+        &mut self,
+        stones: usize,
+        placement: HandicapPlacement,
+    ) -> Result<(), String> {
+        if !self.moves.is_empty() || (self.handicap == 0 && !self.setup_stones.is_empty()) {
+            return Err("the handicap must be set on an empty board".to_string());
+        }
+        if stones != 0 && !(MIN_HANDICAP..=MAX_HANDICAP).contains(&stones) {
+            return Err(format!(
+                "a handicap must be between {} and {} stones",
+                MIN_HANDICAP, MAX_HANDICAP
+            ));
+        }
+        let points = match placement {
+            HandicapPlacement::Fixed if stones > 0 => get_fixed_handicap_points(self.size, stones)
+                .ok_or_else(|| {
+                    format!(
+                        "no fixed handicap positions for a {}x{} board",
+                        self.size, self.size
+                    )
+                })?,
+            _ => Vec::new(),
+        };
+
+        // take back any handicap stones that are already down
+        for (_, p) in self.setup_stones.clone() {
+            self.set(p, CellState::None);
+        }
+        self.setup_stones.clear();
+
+        self.handicap = stones;
+        self.handicap_remaining = 0;
+        for p in points {
+            self.set(p, CellState::Black);
+            self.setup_stones.push((CellState::Black, p));
+        }
+        if placement == HandicapPlacement::Free {
+            self.handicap_remaining = stones;
+        }
+        self.turn = if stones > 0 && self.handicap_remaining == 0 {
+            CellState::White
+        } else {
+            CellState::Black
+        };
+        self.history = vec![(self.hash, self.turn)];
+        Ok(())
+    }
+
+    /// Counts the setup stones as a handicap of `stones`, as a game record's
+    /// `HA` does, without moving any stones or changing whose turn it is.
+    /// This must be done before the first move.
+    pub fn set_handicap_count(&mut self, stones: usize) -> Result<(), String> {
+        if !self.moves.is_empty() || self.is_placing_handicap() {
+            return Err("the handicap must be set before the first move".to_string());
+        }
+        if stones != 0 && !(MIN_HANDICAP..=MAX_HANDICAP).contains(&stones) {
+            return Err(format!(
+                "a handicap must be between {} and {} stones",
+                MIN_HANDICAP, MAX_HANDICAP
+            ));
+        }
+        self.handicap = stones;
+        Ok(())
+    }
+
+    /// Clears the board and starts again with Black to play and no handicap.
     pub fn reset(&mut self) {
         self.turn = CellState::Black;
         self.captured_stones = vec![0, 0, 0];
         self.ko = None;
+        self.setup_stones.clear();
+        self.moves.clear();
+        self.handicap = 0;
+        self.handicap_remaining = 0;
         for j in 0..self.size {
             for i in 0..self.size {
                 self.set(Point::new(i as i32, j as i32), CellState::None);
             }
         }
+        self.history = vec![(self.hash, self.turn)];
+        self.forfeit = None;
     }
 
+    /// Plays a stone for the current player at `p`, removing any captured
+    /// stones and passing the turn. Illegal moves are ignored; check them
+    /// with [`Board::can_place`].
+    ///
+    /// While free handicap stones are being placed this puts down the next
+    /// one instead, and the turn only passes to White after the last.
     pub fn place(&mut self, p: Point) {
-        if self.can_place(p) {
+        if self.is_placing_handicap() {
+            if self.can_place(p) {
+                self.set(p, CellState::Black);
+                self.setup_stones.push((CellState::Black, p));
+                self.handicap_remaining -= 1;
+                if self.handicap_remaining == 0 {
+                    self.turn = CellState::White;
+                    self.history = vec![(self.hash, self.turn)];
+                }
+            }
+        } else if self.can_place(p) {
             //println!("Placing {} stone at {}", self.turn, p);
             self.set(p, self.turn);
             let captured_stones = self.find_captured_stones(p);
@@ -90,33 +391,106 @@ impl Board {
                 self.captured_stones[owner.get_other_player() as usize] += 1;
                 self.set(*q, CellState::None);
             }
-            if num_captured_stones == 1 && self.is_in_atari(p) {
+            // a ko is a single stone capturing a single stone and being left
+            // in atari, so that it could be taken straight back
+            let mut group = Vec::new();
+            self.get_group(self.turn, p, &mut group);
+            if num_captured_stones == 1 && group.len() == 1 && self.is_in_atari(p) {
                 self.ko = Some(captured_stones[0]);
             } else {
                 self.ko = None;
             }
+            self.moves.push((self.turn, Some(p)));
             self.turn = self.turn.get_other_player();
+            self.history.push((self.hash, self.turn));
             //self.print();
             //println!();
         }
     }
 
+    /// Plays `mv` for the current player: a stone, or a pass for `None`.
+    pub fn play(&mut self, mv: Option<Point>) {
+        match mv {
+            Some(p) => self.place(p),
+            None => self.pass(),
+        }
+    }
+
+    /// Passes the turn. Under rules with pass stones the opponent gets a
+    /// prisoner. Black can't pass while placing free handicap stones.
+    pub fn pass(&mut self) {
+        if self.is_placing_handicap() || self.forfeit.is_some() {
+            return;
+        }
+        let other_player = self.turn.get_other_player();
+        if self.rules.pass_stones {
+            self.captured_stones[other_player as usize] += 1;
+        }
+        self.ko = None;
+        self.moves.push((self.turn, None));
+        self.turn = other_player;
+        self.history.push((self.hash, self.turn));
+    }
+
+    /// Gives the move to `player` without a pass, for games whose moves
+    /// don't alternate, such as a controller setting up stones with several
+    /// moves in a row. Nothing is recorded and the ko is lifted.
+    pub fn set_turn(&mut self, player: CellState) {
+        if player == self.turn || player == CellState::None || self.is_placing_handicap() {
+            return;
+        }
+        self.ko = None;
+        self.turn = player;
+        self.history.push((self.hash, self.turn));
+    }
+
+    /// Lifts the ko ban without a move, as when both sides have played a ko
+    /// threat elsewhere. Nothing is recorded, superko history included.
+    pub fn clear_ko(&mut self) {
+        self.ko = None;
+    }
+
+    /// Whether the game has ended, with two passes in a row or a forfeit.
+    /// Under rules with pass stones White must have passed last.
+    pub fn is_game_over(&self) -> bool {
+        if self.forfeit.is_some() {
+            return true;
+        }
+        let n = self.moves.len();
+        if n < 2 || self.moves[n - 1].1.is_some() || self.moves[n - 2].1.is_some() {
+            return false;
+        }
+        !self.rules.pass_stones || self.moves[n - 1].0 == CellState::White
+    }
+
+    /// Ends the game with a win for `player`'s opponent, who either resigned
+    /// or ran out of time. No more moves can be played afterwards.
+    pub fn forfeit(&mut self, player: CellState, reason: ForfeitReason) {
+        if self.forfeit.is_none() {
+            self.forfeit = Some(Forfeit {
+                loser: player,
+                reason,
+            });
+        }
+    }
+
+    /// How the game was forfeited, if it was.
+    pub fn get_forfeit(&self) -> Option<Forfeit> {
+        self.forfeit
+    }
+
     fn find_captured_stones(&self, p: Point) -> Vec<Point> {
         let target = self.turn.get_other_player();
 
         let mut captures = Vec::new();
         for q in self.get_adjacent(p) {
-            if self.get(q) == target {
-                if captures.iter().any(|&x| x == q) == false {
-                    if self.count_liberties(q) == 0 {
-                        captures.push(q);
-                        self.get_group(target, q, &mut captures);
-                    }
-                }
+            if self.get(q) == target && !captures.contains(&q) && self.count_liberties(q) == 0 {
+                captures.push(q);
+                self.get_group(target, q, &mut captures);
             }
         }
 
-        if captures.len() == 0 {
+        if captures.is_empty() {
             // suicide?
             if self.count_liberties(p) == 0 {
                 self.get_group(self.turn, p, &mut captures);
@@ -126,6 +500,7 @@ impl Board {
         captures
     }
 
+    /// The orthogonal neighbours of `p` that are on the board.
     pub fn get_adjacent(&self, p: Point) -> Vec<Point> {
         let mut adjacent = Vec::new();
         if p.x >= 1 {
@@ -143,8 +518,12 @@ impl Board {
         adjacent
     }
 
+    /// Whether the current player may play at `p`: the point must be on the
+    /// board, empty and not the ko point, and must not be suicide or repeat
+    /// an earlier position if the rules forbid it. Nothing can be played
+    /// once the game has been forfeited.
     pub fn can_place(&self, p: Point) -> bool {
-        if p.x as usize >= self.size || p.y as usize >= self.size {
+        if self.forfeit.is_some() || p.x as usize >= self.size || p.y as usize >= self.size {
             return false;
         }
 
@@ -156,28 +535,96 @@ impl Board {
 
         match self.get(p) {
             CellState::None => {
-                if self.allow_suicide {
+                if self.is_placing_handicap() {
                     true
+                } else if !self.rules.allow_suicide && self.is_move_suicidal(p) {
+                    false
                 } else {
-                    !self.is_move_suicidal(p)
+                    !self.is_superko(p)
                 }
             }
             _ => false,
         }
     }
 
+    fn is_superko(&self, p: Point) -> bool {
+        let next = self.turn.get_other_player();
+        match self.rules.ko {
+            KoRule::Simple => false,
+            KoRule::PositionalSuperko => {
+                let hash = self.get_hash_after(p);
+                self.history.iter().any(|&(h, _)| h == hash)
+            }
+            KoRule::SituationalSuperko => {
+                let hash = self.get_hash_after(p);
+                self.history.iter().any(|&(h, t)| h == hash && t == next)
+            }
+        }
+    }
+
+    // the hash of the position after the current player plays at `p`,
+    // worked out without touching the board
+    fn get_hash_after(&self, p: Point) -> u64 {
+        let target = self.turn.get_other_player();
+        let mut hash = self.hash ^ get_zobrist_key(p, self.turn);
+
+        let mut captures = Vec::new();
+        for q in self.get_adjacent(p) {
+            if self.get(q) == target && !captures.contains(&q) && self.count_liberties(q) == 1 {
+                captures.push(q);
+                self.get_group(target, q, &mut captures);
+            }
+        }
+        for q in &captures {
+            hash ^= get_zobrist_key(*q, target);
+        }
+
+        if captures.is_empty() && self.is_move_suicidal(p) {
+            hash ^= get_zobrist_key(p, self.turn);
+            let mut group = Vec::new();
+            for q in self.get_adjacent(p) {
+                if self.get(q) == self.turn && !group.contains(&q) {
+                    let mut g = Vec::new();
+                    self.get_group(self.turn, q, &mut g);
+                    group.extend(g);
+                }
+            }
+            for q in &group {
+                hash ^= get_zobrist_key(*q, self.turn);
+            }
+        }
+        hash
+    }
+
+    /// The number of stones `p` has captured.
     pub fn get_captured_stones(&self, p: CellState) -> i32 {
-        return self.captured_stones[p as usize];
+        self.captured_stones[p as usize]
     }
 
+    /// Every empty point on the board.
+    pub fn get_empty_points(&self) -> Vec<Point> {
+        let mut points = Vec::new();
+        for (y, row) in self.cells.iter().enumerate() {
+            for (x, &state) in row.iter().enumerate() {
+                if state == CellState::None {
+                    points.push(Point::new(x as i32, y as i32));
+                }
+            }
+        }
+        points
+    }
+
+    /// The contents of the point `p`, which must be on the board.
     pub fn get(&self, p: Point) -> CellState {
         self.cells[p.y as usize][p.x as usize]
     }
 
     fn set(&mut self, p: Point, state: CellState) {
+        self.hash ^= get_zobrist_key(p, self.get(p)) ^ get_zobrist_key(p, state);
         self.cells[p.y as usize][p.x as usize] = state;
     }
 
+    /// The empty points adjacent to the group containing `p`.
     pub fn get_liberties(&self, p: Point) -> Vec<Point> {
         let mut liberties = Vec::new();
         let player = self.get(p);
@@ -188,10 +635,8 @@ impl Board {
             //println!("the group has {} points", group.len());
             for g in group {
                 for q in self.get_adjacent(g) {
-                    if self.get(q) == CellState::None {
-                        if liberties.iter().any(|&x| x == q) == false {
-                            liberties.push(q);
-                        }
+                    if self.get(q) == CellState::None && !liberties.contains(&q) {
+                        liberties.push(q);
                     }
                 }
             }
@@ -200,28 +645,30 @@ impl Board {
         liberties
     }
 
+    /// Collects the stones of colour `start` connected to `p` into `group`.
+    /// Nothing is added if `p` is empty.
     pub fn get_group(&self, start: CellState, p: Point, group: &mut Vec<Point>) {
         //println!("getting group of {} at {}", start, p);
         if self.get(p) == CellState::None {
             return;
         }
-        if group.len() == 0 {
+        if group.is_empty() {
             group.push(p);
         }
         for q in self.get_adjacent(p) {
-            if self.get(q) == start {
-                if group.iter().any(|&x| x == q) == false {
-                    group.push(q);
-                    self.get_group(start, q, group);
-                }
+            if self.get(q) == start && !group.contains(&q) {
+                group.push(q);
+                self.get_group(start, q, group);
             }
         }
     }
 
+    /// The number of liberties of the group containing `p`.
     pub fn count_liberties(&self, p: Point) -> usize {
         self.get_liberties(p).len()
     }
 
+    /// Whether the group containing `p` has exactly one liberty.
     pub fn is_in_atari(&self, p: Point) -> bool {
         self.count_liberties(p) == 1
     }
@@ -233,17 +680,18 @@ impl Board {
         // within the adjacent points
         for q in adjacents {
             let stone = self.get(q);
-            if stone == CellState::None {
-                return false;
-            } else if stone == other_player && self.is_in_atari(q) {
-                return false;
-            } else if stone == self.turn && !self.is_in_atari(q) {
+            if stone == CellState::None
+                || (stone == other_player && self.is_in_atari(q))
+                || (stone == self.turn && !self.is_in_atari(q))
+            {
                 return false;
             }
         }
-        return true;
+        true
     }
 
+    /// The connected region of empty points containing `p`, or nothing if
+    /// `p` holds a stone.
     pub fn get_territory(&self, p: Point) -> Vec<Point> {
         let mut boundaries = Vec::new();
         if self.get(p) == CellState::None {
@@ -255,28 +703,105 @@ impl Board {
 
     fn _get_territory(&self, p: Point, boundaries: &mut Vec<Point>) {
         for q in self.get_adjacent(p) {
-            if self.get(q) == CellState::None {
-                if boundaries.iter().any(|&x| x == q) == false {
-                    boundaries.push(q);
-                    self._get_territory(q, boundaries);
-                }
+            if self.get(q) == CellState::None && !boundaries.contains(&q) {
+                boundaries.push(q);
+                self._get_territory(q, boundaries);
             }
         }
     }
 
+    /// The points of the empty region containing `p` that border something
+    /// other than that region.
     pub fn get_boundary(&self, p: Point) -> Vec<Point> {
         let territory = self.get_territory(p);
         let mut boundary = Vec::new();
         for t in &territory {
-            if self
-                .get_adjacent(*t)
-                .iter()
-                .all(|&x| territory.iter().any(|&y| y == x))
-                == false
-            {
+            if !self.get_adjacent(*t).iter().all(|x| territory.contains(x)) {
                 boundary.push(*t);
             }
         }
         boundary
     }
+
+    /// The colour that owns an empty region (see [`Board::get_territory`]):
+    /// the colour of every stone bordering it, or `None` if it touches both
+    /// colours or no stones at all.
+    pub fn get_territory_owner(&self, region: &[Point]) -> CellState {
+        let mut owner = CellState::None;
+        for p in region {
+            for q in self.get_adjacent(*p) {
+                let stone = self.get(q);
+                if stone == CellState::None {
+                    continue;
+                }
+                if owner == CellState::None {
+                    owner = stone;
+                } else if owner != stone {
+                    return CellState::None;
+                }
+            }
+        }
+        owner
+    }
+
+    /// Counts the game under the board's rules with `dead_stones` taken off
+    /// the board as prisoners first. Area scoring counts stones and
+    /// surrounded points, territory scoring counts surrounded points and
+    /// prisoners. The liberties shared by groups in seki are neutral, and
+    /// under territory scoring so are the seki groups' eyes. White gets the
+    /// komi and, under area scoring, any handicap compensation.
+    pub fn score(&self, dead_stones: &[Point]) -> Score {
+        let mut board = self.clone();
+        for p in dead_stones {
+            let owner = board.get(*p);
+            if owner != CellState::None {
+                board.captured_stones[owner.get_other_player() as usize] += 1;
+                board.set(*p, CellState::None);
+            }
+        }
+
+        let mut neutral = Vec::new();
+        for seki in board.get_seki() {
+            neutral.extend(seki.shared_liberties);
+            if self.rules.scoring == ScoringMethod::Territory {
+                neutral.extend(seki.eyes);
+            }
+        }
+
+        let mut points = [0.0, 0.0, 0.0];
+        let mut counted = vec![vec![false; self.size]; self.size];
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let p = Point::new(x as i32, y as i32);
+                let state = board.get(p);
+                if state != CellState::None {
+                    if self.rules.scoring == ScoringMethod::Area {
+                        points[state as usize] += 1.0;
+                    }
+                } else if !counted[y][x] {
+                    let region = board.get_territory(p);
+                    let owner = board.get_territory_owner(&region);
+                    for q in &region {
+                        counted[q.y as usize][q.x as usize] = true;
+                    }
+                    if !region.iter().any(|q| neutral.contains(q)) {
+                        points[owner as usize] += region.len() as f32;
+                    }
+                }
+            }
+        }
+
+        let mut white = points[CellState::White as usize] + self.komi;
+        let mut black = points[CellState::Black as usize];
+        match self.rules.scoring {
+            ScoringMethod::Area => {
+                white += self.rules.handicap_compensation.get_points(self.handicap);
+            }
+            ScoringMethod::Territory => {
+                white += board.captured_stones[CellState::White as usize] as f32;
+                black += board.captured_stones[CellState::Black as usize] as f32;
+            }
+        }
+        Score { black, white }
+    }
 }
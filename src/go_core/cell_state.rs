@@ -1,13 +1,21 @@
 use core::fmt::{Display, Formatter, Result};
 
+/// The contents of a point on the board, also used to name a player.
 #[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum CellState {
+    #[cfg_attr(feature = "serde", serde(rename = "empty"))]
     None,
     White,
     Black,
 }
 
 impl CellState {
+    /// The opponent of this player (`None` stays `None`).
     pub fn get_other_player(&self) -> Self {
         match self {
             CellState::Black => CellState::White,
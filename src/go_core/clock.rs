@@ -0,0 +1,378 @@
+use std::time::{Duration, Instant};
+
+use crate::go_core::*;
+
+/// How much thinking time each player gets.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimeControl {
+    Unlimited,
+    /// `main` for the whole game.
+    Absolute {
+        main: Duration,
+    },
+    /// `main` to start with and `increment` more after every move.
+    Fischer {
+        main: Duration,
+        increment: Duration,
+    },
+    /// After the main time, `periods` periods of `period` each. A move made
+    /// inside a period keeps it, and letting one run out uses it up.
+    ByoYomi {
+        main: Duration,
+        period: Duration,
+        periods: u32,
+    },
+    /// After the main time, `stones` moves have to be played in every
+    /// `period`. A period of no stones is taken as one.
+    Canadian {
+        main: Duration,
+        period: Duration,
+        stones: u32,
+    },
+}
+
+impl TimeControl {
+    /// Parses a time control from its name and times in seconds:
+    /// `absolute:MAIN`, `fischer:MAIN+INCREMENT`,
+    /// `byoyomi:MAIN+PERIODxPERIODS`, `canadian:MAIN+PERIOD/STONES` or
+    /// `none`.
+    pub fn parse(s: &str) -> Result<TimeControl, String> {
+        let invalid = || format!("invalid time control '{}'", s);
+        let seconds = |t: &str| {
+            t.parse::<f64>()
+                .ok()
+                .and_then(|t| Duration::try_from_secs_f64(t).ok())
+                .ok_or_else(invalid)
+        };
+        let count = |n: &str| n.parse::<u32>().ok().filter(|&n| n > 0).ok_or_else(invalid);
+        let (name, times) = s.split_once(':').unwrap_or((s, ""));
+        let (main, overtime) = times.split_once('+').unwrap_or((times, ""));
+        match name.to_lowercase().as_str() {
+            "none" | "unlimited" => Ok(TimeControl::Unlimited),
+            "absolute" => Ok(TimeControl::Absolute {
+                main: seconds(main)?,
+            }),
+            "fischer" => Ok(TimeControl::Fischer {
+                main: seconds(main)?,
+                increment: seconds(overtime)?,
+            }),
+            "byoyomi" => {
+                let (period, periods) = overtime.split_once('x').ok_or_else(invalid)?;
+                Ok(TimeControl::ByoYomi {
+                    main: seconds(main)?,
+                    period: seconds(period)?,
+                    periods: count(periods)?,
+                })
+            }
+            "canadian" => {
+                let (period, stones) = overtime.split_once('/').ok_or_else(invalid)?;
+                Ok(TimeControl::Canadian {
+                    main: seconds(main)?,
+                    period: seconds(period)?,
+                    stones: count(stones)?,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// What is left on one player's clock.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PlayerClock {
+    pub main: Duration,
+    /// What is left of the current overtime period.
+    pub period: Duration,
+    /// The byo-yomi periods left, or the moves still to be played in the
+    /// current Canadian period.
+    pub count: u32,
+    /// Whether the player has run out of time.
+    pub flagged: bool,
+}
+
+impl PlayerClock {
+    pub fn new(control: TimeControl) -> PlayerClock {
+        let (main, period, count) = match control {
+            TimeControl::Unlimited => (Duration::ZERO, Duration::ZERO, 0),
+            TimeControl::Absolute { main } | TimeControl::Fischer { main, .. } => {
+                (main, Duration::ZERO, 0)
+            }
+            TimeControl::ByoYomi {
+                main,
+                period,
+                periods,
+            } => (main, period, periods),
+            TimeControl::Canadian {
+                main,
+                period,
+                stones,
+            } => (main, period, stones.max(1)),
+        };
+        PlayerClock {
+            main,
+            period,
+            count,
+            flagged: false,
+        }
+    }
+
+    /// Whether the main time has gone and the player is in byo-yomi or a
+    /// Canadian period.
+    pub fn is_in_overtime(&self, control: TimeControl) -> bool {
+        match control {
+            TimeControl::ByoYomi { .. } | TimeControl::Canadian { .. } => {
+                self.main == Duration::ZERO
+            }
+            _ => false,
+        }
+    }
+
+    /// Takes `elapsed` thinking time off the clock, returning false if
+    /// that runs it out.
+    pub fn spend(&mut self, control: TimeControl, elapsed: Duration) -> bool {
+        if self.flagged || control == TimeControl::Unlimited {
+            return !self.flagged;
+        }
+        if elapsed <= self.main {
+            self.main -= elapsed;
+            return true;
+        }
+        let mut elapsed = elapsed - self.main;
+        self.main = Duration::ZERO;
+        match control {
+            TimeControl::ByoYomi { period, .. } => {
+                // every period that runs out is used up
+                while elapsed >= self.period && self.count > 0 {
+                    elapsed -= self.period;
+                    self.count -= 1;
+                    self.period = period;
+                }
+                if self.count == 0 {
+                    self.period = Duration::ZERO;
+                    self.flagged = true;
+                } else {
+                    self.period -= elapsed;
+                }
+            }
+            TimeControl::Canadian { .. } if elapsed < self.period => self.period -= elapsed,
+            _ => {
+                self.period = Duration::ZERO;
+                self.flagged = true;
+            }
+        }
+        !self.flagged
+    }
+
+    /// Updates the clock for a move having been made: the Fischer
+    /// increment is added, a byo-yomi period starts again and a Canadian
+    /// period counts the move.
+    pub fn complete_move(&mut self, control: TimeControl) {
+        if self.flagged {
+            return;
+        }
+        match control {
+            TimeControl::Fischer { increment, .. } => {
+                self.main = self.main.saturating_add(increment)
+            }
+            TimeControl::ByoYomi { period, .. } if self.is_in_overtime(control) => {
+                self.period = period
+            }
+            TimeControl::Canadian { period, stones, .. } if self.is_in_overtime(control) => {
+                self.count = self.count.saturating_sub(1);
+                if self.count == 0 {
+                    self.period = period;
+                    self.count = stones.max(1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The clock as it would be shown, e.g. `4:59`, `0:27 (3)` for a
+    /// byo-yomi period with three left or `2:41/7` for a Canadian period
+    /// with seven moves to go.
+    pub fn format(&self, control: TimeControl) -> String {
+        let minutes = |t: Duration| {
+            // rounded up, so that a clock showing 0:00 has run out
+            let seconds = t.as_secs() + u64::from(t.subsec_nanos() > 0);
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        };
+        match control {
+            TimeControl::Unlimited => "-".to_string(),
+            TimeControl::ByoYomi { .. } if self.is_in_overtime(control) => {
+                format!("{} ({})", minutes(self.period), self.count)
+            }
+            TimeControl::Canadian { .. } if self.is_in_overtime(control) => {
+                format!("{}/{}", minutes(self.period), self.count)
+            }
+            _ => minutes(self.main),
+        }
+    }
+}
+
+/// A clock for both players that runs for whoever is to move and ends the
+/// game when a player runs out of time.
+#[derive(Clone, Debug)]
+pub struct GameClock {
+    control: TimeControl,
+    black: PlayerClock,
+    white: PlayerClock,
+    // whose clock is running and when it was last charged
+    running: Option<(CellState, Instant)>,
+}
+
+impl GameClock {
+    pub fn new(control: TimeControl) -> GameClock {
+        GameClock {
+            control,
+            black: PlayerClock::new(control),
+            white: PlayerClock::new(control),
+            running: None,
+        }
+    }
+
+    pub fn get_control(&self) -> TimeControl {
+        self.control
+    }
+
+    pub fn get_clock(&self, player: CellState) -> &PlayerClock {
+        match player {
+            CellState::White => &self.white,
+            _ => &self.black,
+        }
+    }
+
+    fn get_clock_mut(&mut self, player: CellState) -> &mut PlayerClock {
+        match player {
+            CellState::White => &mut self.white,
+            _ => &mut self.black,
+        }
+    }
+
+    /// Sets what is left on `player`'s clock, as reported by GTP
+    /// `time_left`: `stones` is zero in the main time, and otherwise the
+    /// time is what is left of the current overtime period and `stones`
+    /// the byo-yomi periods or Canadian moves left.
+    pub fn set_time_left(&mut self, player: CellState, time: Duration, stones: u32) {
+        let control = self.control;
+        let clock = self.get_clock_mut(player);
+        *clock = PlayerClock::new(control);
+        if stones == 0
+            || !matches!(
+                control,
+                TimeControl::ByoYomi { .. } | TimeControl::Canadian { .. }
+            )
+        {
+            clock.main = time;
+        } else {
+            clock.main = Duration::ZERO;
+            clock.period = time;
+            clock.count = stones;
+        }
+    }
+
+    /// Takes `elapsed` off `player`'s clock for a move they made, for
+    /// keeping time without a board, returning false if their time ran
+    /// out.
+    pub fn record_move(&mut self, player: CellState, elapsed: Duration) -> bool {
+        let control = self.control;
+        let clock = self.get_clock_mut(player);
+        let in_time = clock.spend(control, elapsed);
+        clock.complete_move(control);
+        in_time
+    }
+
+    /// The player whose clock is running.
+    pub fn get_running(&self) -> Option<CellState> {
+        self.running.map(|(player, _)| player)
+    }
+
+    /// Starts `player`'s clock at `now`, stopping the other one.
+    pub fn start(&mut self, player: CellState, now: Instant) {
+        self.update(now);
+        self.running = Some((player, now));
+    }
+
+    pub fn stop(&mut self, now: Instant) {
+        self.update(now);
+        self.running = None;
+    }
+
+    /// Charges the running clock for the time since it was last charged,
+    /// returning the player whose time that runs out.
+    pub fn update(&mut self, now: Instant) -> Option<CellState> {
+        let (player, since) = self.running?;
+        let control = self.control;
+        let elapsed = now.saturating_duration_since(since);
+        self.running = Some((player, now));
+        if self.get_clock_mut(player).spend(control, elapsed) {
+            None
+        } else {
+            self.running = None;
+            Some(player)
+        }
+    }
+
+    /// Charges the running clock (see [`GameClock::update`]) and forfeits
+    /// the game on time if it runs out, returning whether it did.
+    pub fn check(&mut self, board: &mut Board, now: Instant) -> bool {
+        match self.update(now) {
+            Some(player) => {
+                board.forfeit(player, ForfeitReason::Time);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Plays `mv` on `board` for the player to move at `now`, then starts
+    /// the opponent's clock. If the mover's time has already run out the
+    /// game is forfeited on time instead and `false` is returned.
+    pub fn play(&mut self, board: &mut Board, mv: Option<Point>, now: Instant) -> bool {
+        if self.check(board, now) || board.is_game_over() {
+            return false;
+        }
+        let player = board.get_turn();
+        if self.get_clock(player).flagged {
+            board.forfeit(player, ForfeitReason::Time);
+            return false;
+        }
+        let moves = board.get_moves().len();
+        board.play(mv);
+        if board.get_moves().len() == moves {
+            return false;
+        }
+        let control = self.control;
+        self.get_clock_mut(player).complete_move(control);
+        if board.is_game_over() {
+            self.stop(now);
+        } else {
+            self.start(board.get_turn(), now);
+        }
+        true
+    }
+
+    /// How long `player` should think about their next move: a share of
+    /// the main time plus most of whatever overtime a move gets.
+    pub fn get_move_budget(&self, player: CellState) -> Option<Duration> {
+        let clock = self.get_clock(player);
+        // the moves the main time should last for; times can be as long as
+        // a `Duration` goes, so the sums saturate
+        let share = clock.main / 20;
+        let most = |t: Duration| t / 5 * 4;
+        match self.control {
+            TimeControl::Unlimited => None,
+            TimeControl::Absolute { .. } => Some(clock.main / 30),
+            TimeControl::Fischer { increment, .. } => {
+                Some(share.saturating_add(most(increment)).min(clock.main / 2))
+            }
+            TimeControl::ByoYomi { .. } => Some(share.saturating_add(most(clock.period))),
+            TimeControl::Canadian { .. } if clock.is_in_overtime(self.control) => {
+                Some(most(clock.period) / clock.count.max(1))
+            }
+            TimeControl::Canadian { period, stones, .. } => {
+                Some(share.saturating_add(most(period) / stones.max(1)))
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::go_core::*;
+
+pub const MIN_HANDICAP: usize = 2;
+pub const MAX_HANDICAP: usize = 9;
+
+/// How handicap stones are put on the board.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HandicapPlacement {
+    /// On the standard star points, all at once.
+    Fixed,
+    /// Black chooses where, playing every stone before White's first move.
+    Free,
+}
+
+/// The star points used for `stones` fixed handicap stones, or `None` if
+/// the board size or number of stones isn't supported.
+///
+/// Only 9x9, 13x13 and 19x19 boards are supported. The stones go in the
+/// traditional order: upper right and lower left, then lower right, upper
+/// left, the sides and finally tengen for odd handicaps from five up.
+pub fn get_fixed_handicap_points(size: usize, stones: usize) -> Option<Vec<Point>> {
+    let edge = match size {
+        9 => 2,
+        13 | 19 => 3,
+        _ => return None,
+    };
+    if !(MIN_HANDICAP..=MAX_HANDICAP).contains(&stones) {
+        return None;
+    }
+
+    let lo = edge;
+    let mid = (size / 2) as i32;
+    let hi = (size - 1) as i32 - edge;
+
+    let mut points = vec![Point::new(hi, lo), Point::new(lo, hi)];
+    if stones >= 3 {
+        points.push(Point::new(hi, hi));
+    }
+    if stones >= 4 {
+        points.push(Point::new(lo, lo));
+    }
+    if stones >= 6 {
+        points.push(Point::new(lo, mid));
+        points.push(Point::new(hi, mid));
+    }
+    if stones >= 8 {
+        points.push(Point::new(mid, lo));
+        points.push(Point::new(mid, hi));
+    }
+    if stones % 2 == 1 && stones >= 5 {
+        points.push(Point::new(mid, mid));
+    }
+    Some(points)
+}
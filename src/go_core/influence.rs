@@ -0,0 +1,157 @@
+use crate::go_core::*;
+
+// the influence a stone starts with, before dilation and erosion
+const STONE_INFLUENCE: i32 = 128;
+// Bouzy's 5/21 keeps only secure territory, so the estimate erodes less
+// to count moyos too, which is closer to how a position will end up
+const ESTIMATE_DILATIONS: usize = 5;
+const ESTIMATE_EROSIONS: usize = 10;
+// the influence at which a point counts as certainly owned
+const OWNERSHIP_SCALE: f32 = 4.0;
+
+/// Who a position looks like it belongs to and what the score would be.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Estimate {
+    /// For each point, indexed `[y][x]`, from -1 (White's) to 1 (Black's).
+    pub ownership: Vec<Vec<f32>>,
+    /// The score if every point went to whoever is more likely to own it,
+    /// under the board's rules.
+    pub score: Score,
+}
+
+impl Board {
+    /// Bouzy's influence function: every stone starts at 128 (positive for
+    /// Black and negative for White), then the influence spreads out into
+    /// empty points with `dilations` rounds of dilation and is worn back at
+    /// its edges by `erosions` rounds of erosion. Bouzy's own 5/21 leaves
+    /// just the territory. The result is indexed `[y][x]`.
+    pub fn get_influence(&self, dilations: usize, erosions: usize) -> Vec<Vec<i32>> {
+        self.find_influence(&[], dilations, erosions)
+    }
+
+    // the influence with the stones in `ignored` taken off the board
+    fn find_influence(
+        &self,
+        ignored: &[Point],
+        dilations: usize,
+        erosions: usize,
+    ) -> Vec<Vec<i32>> {
+        let size = self.get_size();
+        let mut influence = vec![vec![0; size]; size];
+        for (y, row) in influence.iter_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                let p = Point::new(x as i32, y as i32);
+                if ignored.contains(&p) {
+                    continue;
+                }
+                *value = match self.get(p) {
+                    CellState::Black => STONE_INFLUENCE,
+                    CellState::White => -STONE_INFLUENCE,
+                    CellState::None => 0,
+                };
+            }
+        }
+        for _ in 0..dilations {
+            influence = self.dilate(&influence);
+        }
+        for _ in 0..erosions {
+            influence = self.erode(&influence);
+        }
+        influence
+    }
+
+    // each point not touching the other side's influence gains one for
+    // every neighbour on its side
+    fn dilate(&self, influence: &[Vec<i32>]) -> Vec<Vec<i32>> {
+        let mut next = influence.to_vec();
+        for (y, row) in next.iter_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                let v = influence[y][x];
+                let neighbours: Vec<i32> = self
+                    .get_adjacent(Point::new(x as i32, y as i32))
+                    .iter()
+                    .map(|q| influence[q.y as usize][q.x as usize])
+                    .collect();
+                if v >= 0 && neighbours.iter().all(|&n| n >= 0) {
+                    *value += neighbours.iter().filter(|&&n| n > 0).count() as i32;
+                }
+                if v <= 0 && neighbours.iter().all(|&n| n <= 0) {
+                    *value -= neighbours.iter().filter(|&&n| n < 0).count() as i32;
+                }
+            }
+        }
+        next
+    }
+
+    // each point loses one for every neighbour that isn't on its side,
+    // stopping at zero
+    fn erode(&self, influence: &[Vec<i32>]) -> Vec<Vec<i32>> {
+        let mut next = influence.to_vec();
+        for (y, row) in next.iter_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                let v = influence[y][x];
+                let adjacent = self.get_adjacent(Point::new(x as i32, y as i32));
+                let neighbours = adjacent
+                    .iter()
+                    .map(|q| influence[q.y as usize][q.x as usize]);
+                if v > 0 {
+                    *value = (v - neighbours.filter(|&n| n <= 0).count() as i32).max(0);
+                } else if v < 0 {
+                    *value = (v + neighbours.filter(|&n| n >= 0).count() as i32).min(0);
+                }
+            }
+        }
+        next
+    }
+
+    /// Estimates who owns each point and the final score mid-game. The
+    /// stones [`Board::get_dead_stones`] suggests are taken off first and
+    /// count as prisoners, then the influence decides the rest.
+    pub fn get_estimate(&self) -> Estimate {
+        let size = self.get_size();
+        let dead = self.get_dead_stones();
+        let influence = self.find_influence(&dead, ESTIMATE_DILATIONS, ESTIMATE_EROSIONS);
+
+        let rules = self.get_rules();
+        let mut points = [0.0, 0.0, 0.0];
+        let mut ownership = vec![vec![0.0; size]; size];
+        for y in 0..size {
+            for x in 0..size {
+                let p = Point::new(x as i32, y as i32);
+                let value = influence[y][x];
+                ownership[y][x] = (value as f32 / OWNERSHIP_SCALE).clamp(-1.0, 1.0);
+                let is_dead = dead.contains(&p);
+                if is_dead && rules.scoring == ScoringMethod::Territory {
+                    points[self.get(p).get_other_player() as usize] += 1.0;
+                }
+                let owner = if value > 0 {
+                    CellState::Black
+                } else if value < 0 {
+                    CellState::White
+                } else {
+                    continue;
+                };
+                if rules.scoring == ScoringMethod::Area || is_dead || self.get(p) == CellState::None
+                {
+                    points[owner as usize] += 1.0;
+                }
+            }
+        }
+
+        let mut black = points[CellState::Black as usize];
+        let mut white = points[CellState::White as usize] + self.get_komi();
+        match rules.scoring {
+            ScoringMethod::Area => {
+                white += rules.handicap_compensation.get_points(self.get_handicap());
+            }
+            ScoringMethod::Territory => {
+                black += self.get_captured_stones(CellState::Black) as f32;
+                white += self.get_captured_stones(CellState::White) as f32;
+            }
+        }
+        Estimate {
+            ownership,
+            score: Score { black, white },
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::go_core::*;
+
+/// The outcome of reading out a ladder (shicho).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ladder {
+    /// Whether the group is captured however it runs.
+    pub works: bool,
+    /// The main line, starting with the defender's first move: the longest
+    /// resistance if the ladder works, or the escape if it doesn't.
+    pub moves: Vec<(CellState, Point)>,
+    /// The stone already on the board that the group escapes through when
+    /// the ladder fails: a friendly stone it runs into or an attacking stone
+    /// it captures.
+    pub breaker: Option<Point>,
+}
+
+// what one side achieves from a position and the line that shows it
+struct Reading {
+    works: bool,
+    moves: Vec<(CellState, Point)>,
+}
+
+impl Board {
+    /// Reads out the ladder against the group at `p`, which must be in
+    /// atari, with its owner to move. The defender either extends from
+    /// atari or captures an attacking stone; the attacker answers every
+    /// two-liberty position by taking away one of the liberties.
+    ///
+    /// Returns `None` if there's no group at `p` or it isn't in atari.
+    pub fn read_ladder(&self, p: Point) -> Option<Ladder> {
+        let defender = self.get(p);
+        if defender == CellState::None || !self.is_in_atari(p) {
+            return None;
+        }
+
+        let mut board = self.clone();
+        if board.get_turn() != defender {
+            board.pass();
+        }
+        let limit = self.get_size() * self.get_size();
+        let reading = defend(&board, p, limit);
+
+        let breaker = if reading.works {
+            None
+        } else {
+            let mut end = board;
+            for &(_, q) in &reading.moves {
+                end.place(q);
+            }
+            self.find_ladder_breaker(&end, p, &reading.moves)
+        };
+        Some(Ladder {
+            works: reading.works,
+            moves: reading.moves,
+            breaker,
+        })
+    }
+
+    // the stone that let the group at `p` out, looking first for friendly
+    // stones it joined, then for attacking stones it captured and finally
+    // for friendly stones next to its liberties
+    fn find_ladder_breaker(
+        &self,
+        end: &Board,
+        p: Point,
+        moves: &[(CellState, Point)],
+    ) -> Option<Point> {
+        let defender = self.get(p);
+        let mut original = Vec::new();
+        self.get_group(defender, p, &mut original);
+        let played = |q: &Point| moves.iter().any(|&(_, m)| m == *q);
+
+        let mut group = Vec::new();
+        end.get_group(defender, p, &mut group);
+        if let Some(&q) = group
+            .iter()
+            .find(|q| self.get(**q) == defender && !original.contains(q) && !played(q))
+        {
+            return Some(q);
+        }
+
+        let attacker = defender.get_other_player();
+        for y in 0..self.get_size() {
+            for x in 0..self.get_size() {
+                let q = Point::new(x as i32, y as i32);
+                if self.get(q) == attacker && end.get(q) == CellState::None {
+                    return Some(q);
+                }
+            }
+        }
+
+        for liberty in end.get_liberties(p) {
+            if let Some(q) = end
+                .get_adjacent(liberty)
+                .into_iter()
+                .find(|q| end.get(*q) == defender && !group.contains(q))
+            {
+                return Some(q);
+            }
+        }
+        None
+    }
+}
+
+// the defender of the group at `p` is to move and in atari
+fn defend(board: &Board, p: Point, depth: usize) -> Reading {
+    let defender = board.get(p);
+    let attacker = defender.get_other_player();
+
+    // running comes first so that it's the main line when nothing works
+    let mut options = board.get_liberties(p);
+    let mut group = Vec::new();
+    board.get_group(defender, p, &mut group);
+    for &stone in &group {
+        for q in board.get_adjacent(stone) {
+            if board.get(q) == attacker && board.is_in_atari(q) {
+                for liberty in board.get_liberties(q) {
+                    if !options.contains(&liberty) {
+                        options.push(liberty);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut best: Option<Reading> = None;
+    for mv in options {
+        if !board.can_place(mv) {
+            continue;
+        }
+        let mut next = board.clone();
+        next.place(mv);
+        let liberties = next.count_liberties(p);
+        let mut reading = if liberties >= 3 || depth == 0 {
+            Reading {
+                works: false,
+                moves: Vec::new(),
+            }
+        } else if liberties == 2 {
+            attack(&next, p, depth - 1)
+        } else {
+            // running into atari, so the attacker takes the group
+            Reading {
+                works: true,
+                moves: next
+                    .get_liberties(p)
+                    .into_iter()
+                    .map(|q| (attacker, q))
+                    .collect(),
+            }
+        };
+        reading.moves.insert(0, (defender, mv));
+        if !reading.works {
+            return reading;
+        }
+        if best
+            .as_ref()
+            .is_none_or(|b| reading.moves.len() > b.moves.len())
+        {
+            best = Some(reading);
+        }
+    }
+    // with nowhere to go the group is simply captured
+    best.unwrap_or(Reading {
+        works: true,
+        moves: Vec::new(),
+    })
+}
+
+// the attacker is to move against the group at `p`, which has two
+// liberties
+fn attack(board: &Board, p: Point, depth: usize) -> Reading {
+    let attacker = board.get(p).get_other_player();
+    let mut best: Option<Reading> = None;
+    for mv in board.get_liberties(p) {
+        if !board.can_place(mv) {
+            continue;
+        }
+        let mut next = board.clone();
+        next.place(mv);
+        if !next.is_in_atari(p) {
+            continue;
+        }
+        let mut reading = defend(&next, p, depth);
+        reading.moves.insert(0, (attacker, mv));
+        if reading.works {
+            return reading;
+        }
+        if best
+            .as_ref()
+            .is_none_or(|b| reading.moves.len() > b.moves.len())
+        {
+            best = Some(reading);
+        }
+    }
+    best.unwrap_or(Reading {
+        works: false,
+        moves: Vec::new(),
+    })
+}
@@ -0,0 +1,313 @@
+use crate::go_core::*;
+
+// enclosed regions this big or bigger can usually be split into two eyes
+const TWO_EYE_SPACE: usize = 7;
+
+/// A rough judgement of a group's life.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GroupStatus {
+    Alive,
+    Dead,
+    Unsettled,
+    /// Alive without two eyes by sharing liberties with an opposing group
+    /// (see [`Board::get_seki`]).
+    Seki,
+}
+
+impl Board {
+    /// Whether a one point eye of `player` at `p` is false: the opponent
+    /// holds enough of its diagonals to cut it off, two in the middle of the
+    /// board or one on the edge.
+    pub fn is_false_eye(&self, p: Point, player: CellState) -> bool {
+        let size = self.get_size() as i32;
+        let opponent = player.get_other_player();
+        let mut diagonals = 0;
+        let mut opposing = 0;
+        for (dx, dy) in [(-1, -1), (1, -1), (-1, 1), (1, 1)].iter() {
+            let q = p + Point::new(*dx, *dy);
+            if q.x >= 0 && q.y >= 0 && q.x < size && q.y < size {
+                diagonals += 1;
+                if self.get(q) == opponent {
+                    opposing += 1;
+                }
+            }
+        }
+        if diagonals == 4 {
+            opposing >= 2
+        } else {
+            opposing >= 1
+        }
+    }
+
+    /// Every group of `player`'s stones.
+    pub fn get_chains(&self, player: CellState) -> Vec<Vec<Point>> {
+        self.get_components(|state| state == player)
+    }
+
+    /// The connected regions of points that aren't `player`'s stones, i.e.
+    /// empty points and the opponent's stones, as used by Benson's
+    /// algorithm. Every region is bordered only by `player` and the edge.
+    pub fn get_regions(&self, player: CellState) -> Vec<Vec<Point>> {
+        self.get_components(|state| state != player)
+    }
+
+    // the connected components of the points whose contents match
+    fn get_components<F: Fn(CellState) -> bool>(&self, matches: F) -> Vec<Vec<Point>> {
+        let size = self.get_size();
+        let mut seen = vec![false; size * size];
+        let mut components = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                if seen[y * size + x] || !matches(self.get(Point::new(x as i32, y as i32))) {
+                    continue;
+                }
+                seen[y * size + x] = true;
+                let mut component = vec![Point::new(x as i32, y as i32)];
+                let mut next = 0;
+                while next < component.len() {
+                    for q in self.get_adjacent(component[next]) {
+                        let idx = q.y as usize * size + q.x as usize;
+                        if !seen[idx] && matches(self.get(q)) {
+                            seen[idx] = true;
+                            component.push(q);
+                        }
+                    }
+                    next += 1;
+                }
+                components.push(component);
+            }
+        }
+        components
+    }
+
+    /// The regions `player` has enclosed well enough to make eyes in:
+    /// smaller than half the board, so not open board, and with most of
+    /// their empty points closer to `player`'s stones than the opponent's.
+    /// Dead opposing stones stay part of the eye space.
+    pub fn get_eye_spaces(&self, player: CellState) -> Vec<Vec<Point>> {
+        let size = self.get_size();
+        let own = self.get_distances(player);
+        let opposing = self.get_distances(player.get_other_player());
+        self.get_regions(player)
+            .into_iter()
+            .filter(|region| {
+                let empty: Vec<usize> = region
+                    .iter()
+                    .filter(|p| self.get(**p) == CellState::None)
+                    .map(|p| p.y as usize * size + p.x as usize)
+                    .collect();
+                let closer = empty.iter().filter(|&&i| own[i] < opposing[i]).count();
+                region.len() * 2 < size * size && closer * 2 > empty.len()
+            })
+            .collect()
+    }
+
+    // how many steps each point is from the nearest of `player`'s stones
+    fn get_distances(&self, player: CellState) -> Vec<usize> {
+        let size = self.get_size();
+        let mut distances = vec![usize::MAX; size * size];
+        let mut queue = Vec::new();
+        for chain in self.get_chains(player) {
+            for p in chain {
+                distances[p.y as usize * size + p.x as usize] = 0;
+                queue.push(p);
+            }
+        }
+        let mut next = 0;
+        while next < queue.len() {
+            let p = queue[next];
+            let d = distances[p.y as usize * size + p.x as usize];
+            for q in self.get_adjacent(p) {
+                let idx = q.y as usize * size + q.x as usize;
+                if distances[idx] == usize::MAX {
+                    distances[idx] = d + 1;
+                    queue.push(q);
+                }
+            }
+            next += 1;
+        }
+        distances
+    }
+
+    /// Roughly how many eyes the group at `p` has: one for each eye space
+    /// it borders, or two for a big one, leaving out false eyes.
+    pub fn count_eyes(&self, p: Point) -> usize {
+        let player = self.get(p);
+        if player == CellState::None {
+            return 0;
+        }
+        let mut group = Vec::new();
+        self.get_group(player, p, &mut group);
+        self.count_eyes_in(&group, player, &self.get_eye_spaces(player))
+    }
+
+    fn count_eyes_in(&self, group: &[Point], player: CellState, spaces: &[Vec<Point>]) -> usize {
+        let mut eyes = 0;
+        for space in spaces {
+            let borders_group = space
+                .iter()
+                .any(|q| self.get_adjacent(*q).iter().any(|r| group.contains(r)));
+            if !borders_group {
+                continue;
+            }
+            if space.len() >= TWO_EYE_SPACE {
+                eyes += 2;
+            } else if space.len() > 1 || !self.is_false_eye(space[0], player) {
+                eyes += 1;
+            }
+        }
+        eyes
+    }
+
+    // Benson's algorithm: the chains that can't be captured even if
+    // `player` passes every move, with the regions that keep them alive.
+    // A region is vital to a chain when every empty point in it is one of
+    // the chain's liberties.
+    fn find_unconditional_life(&self, player: CellState) -> (Vec<Vec<Point>>, Vec<Vec<Point>>) {
+        let chains = self.get_chains(player);
+        let regions = self.get_regions(player);
+
+        // which chains border each region, and which of those it's vital to
+        let mut borders = Vec::new();
+        let mut vital = Vec::new();
+        for region in &regions {
+            let mut bordering = Vec::new();
+            let mut vital_to = Vec::new();
+            for (c, chain) in chains.iter().enumerate() {
+                let touches = |q: &Point| self.get_adjacent(*q).iter().any(|r| chain.contains(r));
+                if !region.iter().any(touches) {
+                    continue;
+                }
+                bordering.push(c);
+                if region
+                    .iter()
+                    .filter(|q| self.get(**q) == CellState::None)
+                    .all(touches)
+                {
+                    vital_to.push(c);
+                }
+            }
+            borders.push(bordering);
+            vital.push(vital_to);
+        }
+
+        let mut alive = vec![true; chains.len()];
+        let mut healthy = vec![true; regions.len()];
+        loop {
+            let mut changed = false;
+            for (c, chain_alive) in alive.iter_mut().enumerate() {
+                let vital_regions = (0..regions.len())
+                    .filter(|&r| healthy[r] && vital[r].contains(&c))
+                    .count();
+                if *chain_alive && vital_regions < 2 {
+                    *chain_alive = false;
+                    changed = true;
+                }
+            }
+            for r in 0..regions.len() {
+                if healthy[r] && borders[r].iter().any(|&c| !alive[c]) {
+                    healthy[r] = false;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let alive_chains = chains
+            .into_iter()
+            .enumerate()
+            .filter(|(c, _)| alive[*c])
+            .map(|(_, chain)| chain)
+            .collect();
+        let vital_regions = regions
+            .into_iter()
+            .enumerate()
+            .filter(|(r, _)| healthy[*r] && vital[*r].iter().any(|&c| alive[c]))
+            .map(|(_, region)| region)
+            .collect();
+        (alive_chains, vital_regions)
+    }
+
+    /// The stones of `player` that are alive whatever the opponent does,
+    /// by Benson's algorithm.
+    pub fn get_unconditionally_alive(&self, player: CellState) -> Vec<Point> {
+        self.find_unconditional_life(player)
+            .0
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// The points inside the regions that keep `player`'s unconditionally
+    /// alive groups alive. Nobody gains anything by playing there.
+    pub fn get_unconditional_territory(&self, player: CellState) -> Vec<Point> {
+        self.find_unconditional_life(player)
+            .1
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// A heuristic status for the group at `p`, or `None` for an empty
+    /// point. Groups that are unconditionally alive or have two eyes are
+    /// alive; groups in seki are in seki; groups with fewer eyes sitting in
+    /// the opponent's eye space are dead; everything else is unsettled.
+    pub fn get_group_status(&self, p: Point) -> Option<GroupStatus> {
+        let player = self.get(p);
+        if player == CellState::None {
+            return None;
+        }
+        self.get_group_statuses(player)
+            .into_iter()
+            .find(|(chain, _)| chain.contains(&p))
+            .map(|(_, status)| status)
+    }
+
+    /// Every group of `player`'s with its heuristic status.
+    pub fn get_group_statuses(&self, player: CellState) -> Vec<(Vec<Point>, GroupStatus)> {
+        let alive = self.get_unconditionally_alive(player);
+        let spaces = self.get_eye_spaces(player);
+        let opposing_spaces = self.get_eye_spaces(player.get_other_player());
+        let seki: Vec<Point> = self
+            .get_seki()
+            .into_iter()
+            .flat_map(|seki| seki.stones)
+            .collect();
+        self.get_chains(player)
+            .into_iter()
+            .map(|chain| {
+                let status = if alive.contains(&chain[0])
+                    || self.count_eyes_in(&chain, player, &spaces) >= 2
+                {
+                    GroupStatus::Alive
+                } else if seki.contains(&chain[0]) {
+                    GroupStatus::Seki
+                } else if opposing_spaces
+                    .iter()
+                    .any(|space| space.contains(&chain[0]))
+                {
+                    GroupStatus::Dead
+                } else {
+                    GroupStatus::Unsettled
+                };
+                (chain, status)
+            })
+            .collect()
+    }
+
+    /// Every stone in a group that [`Board::get_group_status`] thinks is
+    /// dead, as a suggestion for the dead stones when the game is scored.
+    pub fn get_dead_stones(&self) -> Vec<Point> {
+        let mut dead = Vec::new();
+        for player in [CellState::Black, CellState::White].iter() {
+            for (chain, status) in self.get_group_statuses(*player) {
+                if status == GroupStatus::Dead {
+                    dead.extend(chain);
+                }
+            }
+        }
+        dead
+    }
+}
@@ -1,8 +1,38 @@
+//! The rules engine: [`Board`], [`Point`] and [`CellState`].
+
 mod board;
 mod cell_state;
+mod clock;
+mod handicap;
+mod influence;
+mod ladder;
+mod life;
 mod point;
+mod rules;
+#[cfg(feature = "serde")]
+mod schema;
+mod score;
+mod seki;
+mod symmetry;
+mod tsumego;
+mod zobrist;
+
+#[cfg(test)]
 mod tests;
 
 pub use board::*;
 pub use cell_state::*;
+pub use clock::*;
+pub use handicap::*;
+pub use influence::*;
+pub use ladder::*;
+pub use life::*;
 pub use point::*;
+pub use rules::*;
+#[cfg(feature = "serde")]
+pub use schema::*;
+pub use score::*;
+pub use seki::*;
+pub use symmetry::*;
+pub use tsumego::*;
+pub use zobrist::*;
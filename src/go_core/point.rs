@@ -1,7 +1,9 @@
 use core::fmt::{Display, Formatter, Result};
 use std::ops::Add;
 
+/// A zero-based board coordinate, `x` across and `y` down.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     pub x: i32,
     pub y: i32,
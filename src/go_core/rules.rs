@@ -0,0 +1,270 @@
+/// Which repeated positions are forbidden.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KoRule {
+    /// Only the immediate recapture of a single stone is forbidden.
+    Simple,
+    /// No move may recreate any earlier board position.
+    PositionalSuperko,
+    /// No move may recreate an earlier board position with the same player
+    /// to move.
+    SituationalSuperko,
+}
+
+/// How the final score is counted.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScoringMethod {
+    /// Stones on the board plus surrounded empty points.
+    Area,
+    /// Surrounded empty points plus prisoners.
+    Territory,
+}
+
+/// The extra points White gets in a handicap game under area scoring.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HandicapCompensation {
+    None,
+    /// One point per handicap stone.
+    Full,
+    /// One point per handicap stone after the first.
+    OneLess,
+}
+
+impl KoRule {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            KoRule::Simple => "simple",
+            KoRule::PositionalSuperko => "positional-superko",
+            KoRule::SituationalSuperko => "situational-superko",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<KoRule> {
+        [
+            KoRule::Simple,
+            KoRule::PositionalSuperko,
+            KoRule::SituationalSuperko,
+        ]
+        .iter()
+        .copied()
+        .find(|k| k.get_name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl ScoringMethod {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ScoringMethod::Area => "area",
+            ScoringMethod::Territory => "territory",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ScoringMethod> {
+        [ScoringMethod::Area, ScoringMethod::Territory]
+            .iter()
+            .copied()
+            .find(|s| s.get_name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl HandicapCompensation {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            HandicapCompensation::None => "none",
+            HandicapCompensation::Full => "full",
+            HandicapCompensation::OneLess => "one-less",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<HandicapCompensation> {
+        [
+            HandicapCompensation::None,
+            HandicapCompensation::Full,
+            HandicapCompensation::OneLess,
+        ]
+        .iter()
+        .copied()
+        .find(|c| c.get_name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn get_points(&self, handicap: usize) -> f32 {
+        match self {
+            HandicapCompensation::None => 0.0,
+            HandicapCompensation::Full => handicap as f32,
+            HandicapCompensation::OneLess => handicap.saturating_sub(1) as f32,
+        }
+    }
+}
+
+/// Everything that differs between the common rule sets.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RuleSet {
+    pub name: &'static str,
+    /// Whether moves that leave their own group without liberties are legal.
+    /// Such moves remove the suicided stones.
+    pub allow_suicide: bool,
+    pub ko: KoRule,
+    pub scoring: ScoringMethod,
+    /// Whether passing hands the opponent a prisoner, in which case White
+    /// must also be the last to pass (AGA).
+    pub pass_stones: bool,
+    pub komi: f32,
+    pub handicap_compensation: HandicapCompensation,
+}
+
+impl RuleSet {
+    pub fn japanese() -> RuleSet {
+        RuleSet {
+            name: "Japanese",
+            allow_suicide: false,
+            ko: KoRule::Simple,
+            scoring: ScoringMethod::Territory,
+            pass_stones: false,
+            komi: 6.5,
+            handicap_compensation: HandicapCompensation::None,
+        }
+    }
+
+    pub fn chinese() -> RuleSet {
+        RuleSet {
+            name: "Chinese",
+            allow_suicide: false,
+            ko: KoRule::PositionalSuperko,
+            scoring: ScoringMethod::Area,
+            pass_stones: false,
+            komi: 7.5,
+            handicap_compensation: HandicapCompensation::Full,
+        }
+    }
+
+    pub fn aga() -> RuleSet {
+        RuleSet {
+            name: "AGA",
+            allow_suicide: false,
+            ko: KoRule::SituationalSuperko,
+            scoring: ScoringMethod::Area,
+            pass_stones: true,
+            komi: 7.5,
+            handicap_compensation: HandicapCompensation::OneLess,
+        }
+    }
+
+    pub fn new_zealand() -> RuleSet {
+        RuleSet {
+            name: "NZ",
+            allow_suicide: true,
+            ko: KoRule::SituationalSuperko,
+            scoring: ScoringMethod::Area,
+            pass_stones: false,
+            komi: 7.0,
+            handicap_compensation: HandicapCompensation::None,
+        }
+    }
+
+    pub fn tromp_taylor() -> RuleSet {
+        RuleSet {
+            name: "Tromp-Taylor",
+            allow_suicide: true,
+            ko: KoRule::PositionalSuperko,
+            scoring: ScoringMethod::Area,
+            pass_stones: false,
+            komi: 7.5,
+            handicap_compensation: HandicapCompensation::None,
+        }
+    }
+
+    /// All of the presets, in the order the GUI offers them.
+    pub fn get_presets() -> Vec<RuleSet> {
+        vec![
+            RuleSet::japanese(),
+            RuleSet::chinese(),
+            RuleSet::aga(),
+            RuleSet::new_zealand(),
+            RuleSet::tromp_taylor(),
+        ]
+    }
+
+    /// Looks a preset up by name, ignoring case, e.g. `"chinese"` or `"nz"`.
+    pub fn from_name(name: &str) -> Option<RuleSet> {
+        let name = name.to_lowercase();
+        RuleSet::get_presets().into_iter().find(|r| {
+            r.name.to_lowercase() == name
+                || (name == "new-zealand" && r.name == "NZ")
+                || (name == "tromp_taylor" && r.name == "Tromp-Taylor")
+        })
+    }
+
+    /// The rules as one word: the preset's name if they are a preset, or
+    /// the name followed by every field, e.g.
+    /// `Chinese,suicide=yes,ko=positional-superko,scoring=area,pass-stones=no,komi=7.5,compensation=full`.
+    pub fn to_spec(&self) -> String {
+        if RuleSet::from_name(self.name).as_ref() == Some(self) {
+            return self.name.to_string();
+        }
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_whitespace() || c == ',' || c == '=' {
+                    '-'
+                } else {
+                    c
+                }
+            })
+            .collect();
+        format!(
+            "{},suicide={},ko={},scoring={},pass-stones={},komi={},compensation={}",
+            name,
+            yes_no(self.allow_suicide),
+            self.ko.get_name(),
+            self.scoring.get_name(),
+            yes_no(self.pass_stones),
+            self.komi,
+            self.handicap_compensation.get_name()
+        )
+    }
+
+    /// Reads the rules written by [`RuleSet::to_spec`]. Fields left out are
+    /// taken from the named preset, so a bare preset name also reads, and
+    /// rules under any other name are called `"Custom"`.
+    pub fn from_spec(spec: &str) -> Option<RuleSet> {
+        let mut parts = spec.split(',');
+        let name = parts.next()?;
+        let mut rules = RuleSet::from_name(name).unwrap_or_else(|| RuleSet {
+            name: "Custom",
+            ..RuleSet::default()
+        });
+        let mut fields = 0;
+        for part in parts {
+            let (key, value) = part.split_once('=')?;
+            let yes_no = || match value {
+                "yes" => Some(true),
+                "no" => Some(false),
+                _ => None,
+            };
+            match key {
+                "suicide" => rules.allow_suicide = yes_no()?,
+                "ko" => rules.ko = KoRule::from_name(value)?,
+                "scoring" => rules.scoring = ScoringMethod::from_name(value)?,
+                "pass-stones" => rules.pass_stones = yes_no()?,
+                "komi" => rules.komi = value.parse().ok()?,
+                "compensation" => {
+                    rules.handicap_compensation = HandicapCompensation::from_name(value)?
+                }
+                _ => return None,
+            }
+            fields += 1;
+        }
+        // a name that isn't a preset only makes sense with the rules spelt out
+        if rules.name == "Custom" && fields < 6 {
+            return None;
+        }
+        Some(rules)
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::japanese()
+    }
+}
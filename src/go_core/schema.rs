@@ -0,0 +1,173 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::go_core::*;
+
+/// The version written with every serialized [`Board`]. It goes up whenever
+/// the format changes in a way older readers would misread, and boards with
+/// a version newer than this are refused rather than half read.
+pub const BOARD_SCHEMA_VERSION: u32 = 1;
+
+/// The stones on `board` as one string: the rows from the top, separated by
+/// `/`, with `B` for a black stone, `W` for a white one and `.` for an
+/// empty point, e.g. `.B./.W./...`.
+pub fn encode_stones(board: &Board) -> String {
+    let size = board.get_size();
+    let mut rows = Vec::with_capacity(size);
+    for y in 0..size {
+        let row: String = (0..size)
+            .map(|x| match board.get(Point::new(x as i32, y as i32)) {
+                CellState::Black => 'B',
+                CellState::White => 'W',
+                CellState::None => '.',
+            })
+            .collect();
+        rows.push(row);
+    }
+    rows.join("/")
+}
+
+/// Reads the stones written by [`encode_stones`] for a `size` board.
+pub fn decode_stones(stones: &str, size: usize) -> Result<Vec<(CellState, Point)>, String> {
+    let rows: Vec<&str> = stones.split('/').collect();
+    if rows.len() != size {
+        return Err(format!("expected {} rows, found {}", size, rows.len()));
+    }
+    let mut decoded = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        if row.chars().count() != size {
+            return Err(format!("row {} isn't {} points long", y + 1, size));
+        }
+        for (x, c) in row.chars().enumerate() {
+            let state = match c {
+                'B' => CellState::Black,
+                'W' => CellState::White,
+                '.' => continue,
+                _ => return Err(format!("invalid point '{}' in row {}", c, y + 1)),
+            };
+            decoded.push((state, Point::new(x as i32, y as i32)));
+        }
+    }
+    Ok(decoded)
+}
+
+#[derive(Serialize, Deserialize)]
+struct Captures {
+    black: i32,
+    white: i32,
+}
+
+// every field of the rules, so that rules changed from a preset read back
+// as they were
+#[derive(Serialize, Deserialize)]
+struct RulesRecord {
+    name: String,
+    allow_suicide: bool,
+    ko: String,
+    scoring: String,
+    pass_stones: bool,
+    komi: f32,
+    handicap_compensation: String,
+}
+
+// a board as it's written: the stones, who is to play, the captures, the
+// ko and the rules. The moves that led there aren't, and nor is what they
+// leave behind: the positions superko forbids, the passes in a row, the
+// handicap and any forfeit
+#[derive(Serialize, Deserialize)]
+struct BoardRecord {
+    version: u32,
+    size: usize,
+    stones: String,
+    turn: CellState,
+    captures: Captures,
+    ko: Option<Point>,
+    rules: RulesRecord,
+    komi: f32,
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardRecord {
+            version: BOARD_SCHEMA_VERSION,
+            size: self.get_size(),
+            stones: encode_stones(self),
+            turn: self.get_turn(),
+            captures: Captures {
+                black: self.get_captured_stones(CellState::Black),
+                white: self.get_captured_stones(CellState::White),
+            },
+            ko: self.get_ko(),
+            rules: write_rules(self.get_rules()),
+            komi: self.get_komi(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Board, D::Error> {
+        let record = BoardRecord::deserialize(deserializer)?;
+        read_record(record).map_err(serde::de::Error::custom)
+    }
+}
+
+fn read_record(record: BoardRecord) -> Result<Board, String> {
+    if record.version == 0 || record.version > BOARD_SCHEMA_VERSION {
+        return Err(format!(
+            "unsupported board schema version {}",
+            record.version
+        ));
+    }
+    if !(2..=25).contains(&record.size) {
+        return Err(format!("unsupported board size {}", record.size));
+    }
+    if record.turn == CellState::None {
+        return Err("nobody is to play".to_string());
+    }
+    let rules = read_rules(record.rules)?;
+    let stones = decode_stones(&record.stones, record.size)?;
+    let captures = (record.captures.black, record.captures.white);
+    let mut board = Board::from_position(
+        record.size,
+        rules,
+        &stones,
+        record.turn,
+        captures,
+        record.ko,
+    )?;
+    board.set_komi(record.komi);
+    Ok(board)
+}
+
+fn write_rules(rules: &RuleSet) -> RulesRecord {
+    RulesRecord {
+        name: rules.name.to_string(),
+        allow_suicide: rules.allow_suicide,
+        ko: rules.ko.get_name().to_string(),
+        scoring: rules.scoring.get_name().to_string(),
+        pass_stones: rules.pass_stones,
+        komi: rules.komi,
+        handicap_compensation: rules.handicap_compensation.get_name().to_string(),
+    }
+}
+
+// rules that aren't named after a preset are called "Custom"
+fn read_rules(record: RulesRecord) -> Result<RuleSet, String> {
+    Ok(RuleSet {
+        name: RuleSet::from_name(&record.name).map_or("Custom", |r| r.name),
+        allow_suicide: record.allow_suicide,
+        ko: KoRule::from_name(&record.ko)
+            .ok_or_else(|| format!("unknown ko rule '{}'", record.ko))?,
+        scoring: ScoringMethod::from_name(&record.scoring)
+            .ok_or_else(|| format!("unknown scoring method '{}'", record.scoring))?,
+        pass_stones: record.pass_stones,
+        komi: record.komi,
+        handicap_compensation: HandicapCompensation::from_name(&record.handicap_compensation)
+            .ok_or_else(|| {
+                format!(
+                    "unknown handicap compensation '{}'",
+                    record.handicap_compensation
+                )
+            })?,
+    })
+}
@@ -0,0 +1,71 @@
+use crate::go_core::*;
+
+/// The final points for each player, komi and compensation included.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Score {
+    pub black: f32,
+    pub white: f32,
+}
+
+impl Score {
+    pub fn get_winner(&self) -> CellState {
+        if self.black > self.white {
+            CellState::Black
+        } else if self.white > self.black {
+            CellState::White
+        } else {
+            CellState::None
+        }
+    }
+
+    /// How many points the winner won by.
+    pub fn get_margin(&self) -> f32 {
+        (self.black - self.white).abs()
+    }
+}
+
+impl core::fmt::Display for Score {
+    // the usual result notation, e.g. `B+3.5`, `W+0.5` or `0` for a draw
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.get_winner() {
+            CellState::Black => write!(f, "B+{}", self.get_margin()),
+            CellState::White => write!(f, "W+{}", self.get_margin()),
+            CellState::None => write!(f, "0"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ForfeitReason {
+    Resignation,
+    Time,
+}
+
+/// A game that ended without being scored, because `loser` resigned or ran
+/// out of time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Forfeit {
+    pub loser: CellState,
+    pub reason: ForfeitReason,
+}
+
+impl Forfeit {
+    pub fn get_winner(&self) -> CellState {
+        self.loser.get_other_player()
+    }
+}
+
+impl core::fmt::Display for Forfeit {
+    // the result notation, `B+R` for a resignation or `W+T` on time
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let winner = match self.get_winner() {
+            CellState::Black => "B",
+            _ => "W",
+        };
+        let reason = match self.reason {
+            ForfeitReason::Resignation => "R",
+            ForfeitReason::Time => "T",
+        };
+        write!(f, "{}+{}", winner, reason)
+    }
+}
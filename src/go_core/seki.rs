@@ -0,0 +1,172 @@
+use crate::go_core::*;
+
+// an eye bigger than this gives the opponent room to play inside it, so
+// the group isn't simply sitting in seki
+const SEKI_EYE_SIZE: usize = 3;
+
+/// Groups of both colours that live together in seki (mutual life): they
+/// share liberties that neither side can fill without being captured.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Seki {
+    /// Every stone of every group in the seki.
+    pub stones: Vec<Point>,
+    /// The liberties the groups share, which belong to nobody.
+    pub shared_liberties: Vec<Point>,
+    /// The groups' own eyes, which don't count as territory under territory
+    /// scoring.
+    pub eyes: Vec<Point>,
+}
+
+impl Board {
+    /// Finds the seki on the board. A group is in seki when it isn't
+    /// unconditionally alive, has at most one small eye, and every other
+    /// liberty is shared with opposing groups that are also in seki and is
+    /// a self-atari for whoever fills it.
+    pub fn get_seki(&self) -> Vec<Seki> {
+        let mut chains = Vec::new();
+        for player in [CellState::Black, CellState::White].iter() {
+            let alive = self.get_unconditionally_alive(*player);
+            for chain in self.get_chains(*player) {
+                if !alive.contains(&chain[0]) {
+                    chains.push(chain);
+                }
+            }
+        }
+
+        // each chain's shared liberties and eyes, or `None` once it can't be
+        // in seki
+        let mut liberties: Vec<Option<(Vec<Point>, Vec<Point>)>> = chains
+            .iter()
+            .map(|chain| self.classify_seki_liberties(chain))
+            .collect();
+        let chain_at = |p: Point| chains.iter().position(|chain| chain.contains(&p));
+
+        // a chain is only in seki if the groups it shares liberties with are
+        loop {
+            let mut changed = false;
+            for c in 0..chains.len() {
+                let keep = match &liberties[c] {
+                    Some((shared, _)) => shared.iter().all(|&l| {
+                        self.get_adjacent(l).into_iter().all(|q| {
+                            self.get(q) == CellState::None
+                                || chain_at(q).is_some_and(|o| liberties[o].is_some())
+                        })
+                    }),
+                    None => true,
+                };
+                if !keep {
+                    liberties[c] = None;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // chains sharing a liberty make up one seki
+        let mut seki = Vec::new();
+        let mut seen = vec![false; chains.len()];
+        for start in 0..chains.len() {
+            if seen[start] || liberties[start].is_none() {
+                continue;
+            }
+            seen[start] = true;
+            let mut members = vec![start];
+            let mut next = 0;
+            while next < members.len() {
+                let (shared, _) = liberties[members[next]].as_ref().unwrap();
+                for &l in shared {
+                    for q in self.get_adjacent(l) {
+                        if let Some(o) = chain_at(q) {
+                            if !seen[o] {
+                                seen[o] = true;
+                                members.push(o);
+                            }
+                        }
+                    }
+                }
+                next += 1;
+            }
+
+            let mut found = Seki {
+                stones: Vec::new(),
+                shared_liberties: Vec::new(),
+                eyes: Vec::new(),
+            };
+            for &c in &members {
+                let (shared, eyes) = liberties[c].as_ref().unwrap();
+                found.stones.extend(&chains[c]);
+                for &l in shared {
+                    if !found.shared_liberties.contains(&l) {
+                        found.shared_liberties.push(l);
+                    }
+                }
+                found.eyes.extend(eyes);
+            }
+            seki.push(found);
+        }
+        seki
+    }
+
+    /// Whether the stone at `p` is part of a seki.
+    pub fn is_in_seki(&self, p: Point) -> bool {
+        self.get_seki().iter().any(|seki| seki.stones.contains(&p))
+    }
+
+    // splits a chain's liberties into the shared ones and those in its eye,
+    // or `None` if it has an outside liberty, more than one eye or nothing
+    // shared
+    fn classify_seki_liberties(&self, chain: &[Point]) -> Option<(Vec<Point>, Vec<Point>)> {
+        let player = self.get(chain[0]);
+        let mut shared = Vec::new();
+        let mut eyes: Vec<Point> = Vec::new();
+        let mut eye_count = 0;
+        for l in self.get_liberties(chain[0]) {
+            if eyes.contains(&l) {
+                continue;
+            }
+            let region = self.get_territory(l);
+            let enclosed = region.iter().all(|q| {
+                self.get_adjacent(*q)
+                    .iter()
+                    .all(|r| self.get(*r) == CellState::None || chain.contains(r))
+            });
+            if enclosed && region.len() <= SEKI_EYE_SIZE {
+                eye_count += 1;
+                eyes.extend(region);
+            } else if self
+                .get_adjacent(l)
+                .iter()
+                .any(|q| self.get(*q) == player.get_other_player())
+                && !self.can_approach(l, CellState::Black)
+                && !self.can_approach(l, CellState::White)
+            {
+                shared.push(l);
+            } else {
+                return None;
+            }
+        }
+        if shared.is_empty() || eye_count > 1 {
+            None
+        } else {
+            Some((shared, eyes))
+        }
+    }
+
+    // whether `player` can fill `p` without being left in atari, either
+    // because it captures something or because the stone keeps two
+    // liberties
+    fn can_approach(&self, p: Point, player: CellState) -> bool {
+        let mut board = self.clone();
+        if board.get_turn() != player {
+            board.pass();
+        }
+        if !board.can_place(p) {
+            return false;
+        }
+        let captured = board.get_captured_stones(player);
+        board.place(p);
+        board.get_captured_stones(player) > captured || board.count_liberties(p) >= 2
+    }
+}
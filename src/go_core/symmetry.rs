@@ -0,0 +1,223 @@
+use crate::go_core::*;
+
+/// One of the eight rotations and reflections of a square board. Rotations
+/// are clockwise as the board is drawn, with `(0, 0)` in the top left.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirrors left and right.
+    FlipHorizontal,
+    /// Mirrors top and bottom.
+    FlipVertical,
+    /// Mirrors along the diagonal from the top left, swapping `x` and `y`.
+    Transpose,
+    /// Mirrors along the diagonal from the top right.
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    /// Where the point `p` ends up on a `size` x `size` board.
+    pub fn apply(&self, p: Point, size: usize) -> Point {
+        let m = size as i32 - 1;
+        match self {
+            Symmetry::Identity => p,
+            Symmetry::Rotate90 => Point::new(m - p.y, p.x),
+            Symmetry::Rotate180 => Point::new(m - p.x, m - p.y),
+            Symmetry::Rotate270 => Point::new(p.y, m - p.x),
+            Symmetry::FlipHorizontal => Point::new(m - p.x, p.y),
+            Symmetry::FlipVertical => Point::new(p.x, m - p.y),
+            Symmetry::Transpose => Point::new(p.y, p.x),
+            Symmetry::AntiTranspose => Point::new(m - p.y, m - p.x),
+        }
+    }
+
+    /// Where an offset between two points ends up, which doesn't depend on
+    /// the size of the board.
+    pub fn apply_offset(&self, d: Point) -> Point {
+        match self {
+            Symmetry::Identity => d,
+            Symmetry::Rotate90 => Point::new(-d.y, d.x),
+            Symmetry::Rotate180 => Point::new(-d.x, -d.y),
+            Symmetry::Rotate270 => Point::new(d.y, -d.x),
+            Symmetry::FlipHorizontal => Point::new(-d.x, d.y),
+            Symmetry::FlipVertical => Point::new(d.x, -d.y),
+            Symmetry::Transpose => Point::new(d.y, d.x),
+            Symmetry::AntiTranspose => Point::new(-d.y, -d.x),
+        }
+    }
+
+    /// The symmetry that undoes this one.
+    pub fn inverse(&self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            s => *s,
+        }
+    }
+}
+
+/// A symmetry of the board, optionally with the colours swapped as well.
+/// Swapping the colours also swaps whose turn it is and the captures, so a
+/// transformed position is always equivalent to the original.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Transform {
+    pub symmetry: Symmetry,
+    pub swap_colours: bool,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        symmetry: Symmetry::Identity,
+        swap_colours: false,
+    };
+
+    pub fn new(symmetry: Symmetry, swap_colours: bool) -> Transform {
+        Transform {
+            symmetry,
+            swap_colours,
+        }
+    }
+
+    pub fn apply_point(&self, p: Point, size: usize) -> Point {
+        self.symmetry.apply(p, size)
+    }
+
+    pub fn apply_state(&self, state: CellState) -> CellState {
+        if self.swap_colours {
+            state.get_other_player()
+        } else {
+            state
+        }
+    }
+
+    /// The transform that undoes this one, e.g. to map a move found on a
+    /// canonical board back onto the original.
+    pub fn inverse(&self) -> Transform {
+        Transform::new(self.symmetry.inverse(), self.swap_colours)
+    }
+
+    /// The transform that applies this one and then `next`.
+    pub fn then(&self, next: Transform) -> Transform {
+        // a symmetry is decided by where it sends two offsets
+        let apply = |d: Point| next.symmetry.apply_offset(self.symmetry.apply_offset(d));
+        let (x, y) = (apply(Point::new(1, 0)), apply(Point::new(0, 1)));
+        let symmetry = *Symmetry::ALL
+            .iter()
+            .find(|s| {
+                s.apply_offset(Point::new(1, 0)) == x && s.apply_offset(Point::new(0, 1)) == y
+            })
+            .unwrap();
+        Transform::new(symmetry, self.swap_colours != next.swap_colours)
+    }
+}
+
+impl Point {
+    /// Where this point ends up under `symmetry` on a `size` x `size` board.
+    pub fn transform(&self, symmetry: Symmetry, size: usize) -> Point {
+        symmetry.apply(*self, size)
+    }
+}
+
+impl Board {
+    /// The symmetries that leave the stones where they are, always
+    /// including [`Symmetry::Identity`]. Moves they map onto each other are
+    /// equivalent.
+    pub fn get_symmetries(&self) -> Vec<Symmetry> {
+        let size = self.get_size();
+        Symmetry::ALL
+            .iter()
+            .copied()
+            .filter(|s| {
+                (0..size * size).all(|i| {
+                    let p = Point::new((i % size) as i32, (i / size) as i32);
+                    self.get(s.apply(p, size)) == self.get(p)
+                })
+            })
+            .collect()
+    }
+
+    /// The board's canonical form: of the position under every symmetry
+    /// (and, if `swap_colours` is set, with the colours swapped too), the
+    /// one that comes first comparing whose turn it is and then the points
+    /// row by row. Equivalent positions have the same canonical form, so
+    /// its hash can key positions from different games. Returns the
+    /// canonical board and the transform that produced it; with colour
+    /// swaps allowed the canonical board always has Black to play.
+    pub fn get_canonical_form(&self, swap_colours: bool) -> (Board, Transform) {
+        let transform = self.find_canonical_transform(swap_colours);
+        (self.transform(transform), transform)
+    }
+
+    /// The hash of the canonical form (see [`Board::get_canonical_form`])
+    /// and the transform that produces it, without building the board.
+    pub fn get_canonical_hash(&self, swap_colours: bool) -> (u64, Transform) {
+        let transform = self.find_canonical_transform(swap_colours);
+        let size = self.get_size();
+        let mut hash = 0;
+        for y in 0..size {
+            for x in 0..size {
+                let p = Point::new(x as i32, y as i32);
+                hash ^= get_zobrist_key(
+                    transform.apply_point(p, size),
+                    transform.apply_state(self.get(p)),
+                );
+            }
+        }
+        (hash, transform)
+    }
+
+    fn find_canonical_transform(&self, swap_colours: bool) -> Transform {
+        let mut transforms: Vec<Transform> = Symmetry::ALL
+            .iter()
+            .map(|&s| Transform::new(s, false))
+            .collect();
+        if swap_colours {
+            transforms.extend(Symmetry::ALL.iter().map(|&s| Transform::new(s, true)));
+        }
+        // the first of equal keys wins, so a position that is already
+        // canonical keeps the identity
+        let mut best: Option<(Vec<u8>, Transform)> = None;
+        for transform in transforms {
+            let key = self.get_transformed_key(transform);
+            if best.as_ref().is_none_or(|(b, _)| key < *b) {
+                best = Some((key, transform));
+            }
+        }
+        best.unwrap().1
+    }
+
+    // whose turn it is followed by every point, row by row, after the
+    // transform
+    fn get_transformed_key(&self, transform: Transform) -> Vec<u8> {
+        let code = |state: CellState| match transform.apply_state(state) {
+            CellState::None => 0,
+            CellState::Black => 1,
+            CellState::White => 2,
+        };
+        let size = self.get_size();
+        let mut key = vec![0; size * size + 1];
+        key[0] = code(self.get_turn());
+        for y in 0..size {
+            for x in 0..size {
+                let p = Point::new(x as i32, y as i32);
+                let q = transform.apply_point(p, size);
+                key[1 + q.y as usize * size + q.x as usize] = code(self.get(p));
+            }
+        }
+        key
+    }
+}
@@ -1,8 +1,10 @@
+use std::time::{Duration, Instant};
+
 use crate::go_core::*;
 
 #[test]
 fn test_get_adjacent() {
-    let mut b = Board::new(19);
+    let mut b = Board::new(19, RuleSet::default());
 
     b.place(Point::new(0, 0));
     assert_eq!(b.get_adjacent(Point::new(0, 0)).len(), 2);
@@ -46,7 +48,7 @@ fn test_get_group() {
 
 #[test]
 fn test_suicide() {
-    let mut b = Board::new(19);
+    let mut b = Board::new(19, RuleSet::default());
     b.place(Point::new(1, 0));
     b.place(Point::new(5, 5));
     b.place(Point::new(0, 1));
@@ -73,3 +75,863 @@ fn test_capture() {
     assert_eq!(b.get(Point::new(2, 2)), CellState::None);
     assert_eq!(b.get(Point::new(3, 2)), CellState::None);
 }
+
+#[test]
+fn test_fixed_handicap() {
+    let mut b = Board::new(19, RuleSet::default());
+    b.set_handicap(4, HandicapPlacement::Fixed).unwrap();
+
+    for p in [
+        Point::new(3, 3),
+        Point::new(15, 3),
+        Point::new(3, 15),
+        Point::new(15, 15),
+    ]
+    .iter()
+    {
+        assert_eq!(b.get(*p), CellState::Black);
+    }
+    assert_eq!(b.get(Point::new(9, 9)), CellState::None);
+    assert_eq!(b.get_turn(), CellState::White);
+    assert_eq!(b.get_handicap(), 4);
+
+    for size in [9, 13, 19].iter() {
+        for stones in MIN_HANDICAP..=MAX_HANDICAP {
+            let points = get_fixed_handicap_points(*size, stones).unwrap();
+            assert_eq!(points.len(), stones);
+        }
+    }
+    assert!(get_fixed_handicap_points(9, 5)
+        .unwrap()
+        .contains(&Point::new(4, 4)));
+    assert!(get_fixed_handicap_points(15, 2).is_none());
+    assert!(Board::new(15, RuleSet::default())
+        .set_handicap(2, HandicapPlacement::Fixed)
+        .is_err());
+}
+
+#[test]
+fn test_free_handicap() {
+    let mut b = Board::new(9, RuleSet::default());
+    b.set_handicap(2, HandicapPlacement::Free).unwrap();
+    assert!(b.is_placing_handicap());
+
+    b.place(Point::new(0, 0));
+    assert_eq!(b.get_turn(), CellState::Black);
+    b.place(Point::new(8, 8));
+    assert_eq!(b.get_turn(), CellState::White);
+    assert!(!b.is_placing_handicap());
+    assert_eq!(b.get(Point::new(8, 8)), CellState::Black);
+    assert_eq!(b.get_setup_stones().len(), 2);
+    assert!(b.get_moves().is_empty());
+
+    b.place(Point::new(4, 4));
+    assert_eq!(b.get(Point::new(4, 4)), CellState::White);
+    assert!(b.set_handicap(3, HandicapPlacement::Fixed).is_err());
+}
+
+fn with_rules(s: &str, turn: CellState, rules: RuleSet) -> Board {
+    let mut b = Board::from_str(s, turn);
+    b.set_rules(rules);
+    b.set_komi(rules.komi);
+    b
+}
+
+#[test]
+fn test_suicide_rules() {
+    let corner = "
+    .W...
+    W....
+    .....
+    .....
+    .....
+    ";
+    for rules in [RuleSet::japanese(), RuleSet::chinese(), RuleSet::aga()].iter() {
+        let b = with_rules(corner, CellState::Black, *rules);
+        assert!(!b.can_place(Point::new(0, 0)), "{}", rules.name);
+    }
+
+    // New Zealand allows any suicide...
+    let mut b = with_rules(corner, CellState::Black, RuleSet::new_zealand());
+    b.place(Point::new(0, 0));
+    assert_eq!(b.get(Point::new(0, 0)), CellState::None);
+    assert_eq!(b.get_captured_stones(CellState::White), 1);
+    assert_eq!(b.get_turn(), CellState::White);
+
+    // ...but under Tromp-Taylor a single stone suicide repeats the position
+    let b = with_rules(corner, CellState::Black, RuleSet::tromp_taylor());
+    assert!(!b.can_place(Point::new(0, 0)));
+
+    let mut b = with_rules(
+        "
+    B.W..
+    WW...
+    .....
+    .....
+    .....
+    ",
+        CellState::Black,
+        RuleSet::tromp_taylor(),
+    );
+    b.place(Point::new(1, 0));
+    assert_eq!(b.get(Point::new(0, 0)), CellState::None);
+    assert_eq!(b.get(Point::new(1, 0)), CellState::None);
+    assert_eq!(b.get_captured_stones(CellState::White), 2);
+}
+
+#[test]
+fn test_ko() {
+    let ko = "
+    .BW..
+    BW.W.
+    .BW..
+    .....
+    .....
+    ";
+    let mut b = with_rules(ko, CellState::Black, RuleSet::japanese());
+    b.place(Point::new(2, 1));
+    assert_eq!(b.get(Point::new(1, 1)), CellState::None);
+    assert!(!b.can_place(Point::new(1, 1)));
+
+    // after both players pass the ko can be retaken under simple ko...
+    b.pass();
+    b.pass();
+    assert!(b.can_place(Point::new(1, 1)));
+
+    // ...but that repeats the starting position, which superko forbids
+    for rules in [RuleSet::chinese(), RuleSet::aga(), RuleSet::tromp_taylor()].iter() {
+        let mut b = with_rules(ko, CellState::Black, *rules);
+        b.place(Point::new(2, 1));
+        b.pass();
+        b.pass();
+        assert!(!b.can_place(Point::new(1, 1)), "{}", rules.name);
+    }
+}
+
+#[test]
+fn test_pass_stones() {
+    let mut b = Board::new(9, RuleSet::aga());
+    b.pass();
+    assert_eq!(b.get_captured_stones(CellState::White), 1);
+    b.pass();
+    assert_eq!(b.get_captured_stones(CellState::Black), 1);
+    assert!(b.is_game_over());
+
+    // White has to pass last under AGA rules
+    let mut b = Board::new(9, RuleSet::aga());
+    b.place(Point::new(4, 4));
+    b.pass();
+    b.pass();
+    assert!(!b.is_game_over());
+    b.pass();
+    assert!(b.is_game_over());
+
+    let mut b = Board::new(9, RuleSet::japanese());
+    b.place(Point::new(4, 4));
+    b.pass();
+    b.pass();
+    assert!(b.is_game_over());
+    assert_eq!(b.get_captured_stones(CellState::White), 0);
+}
+
+#[test]
+fn test_scoring() {
+    // Black has filled in one point of their own territory
+    let position = "
+    .BW..
+    .BW..
+    BBW..
+    .BW..
+    .BW..
+    ";
+
+    let b = with_rules(position, CellState::Black, RuleSet::chinese());
+    let score = b.score(&[]);
+    assert_eq!(score.black, 10.0);
+    assert_eq!(score.white, 22.5);
+    assert_eq!(score.get_winner(), CellState::White);
+
+    let b = with_rules(position, CellState::Black, RuleSet::japanese());
+    let score = b.score(&[]);
+    assert_eq!(score.black, 4.0);
+    assert_eq!(score.white, 16.5);
+    assert_eq!(format!("{}", score), "W+12.5");
+
+    // a dead white stone in Black's area is a prisoner under territory rules
+    let dead = "
+    .BW..
+    WBW..
+    .BW..
+    .BW..
+    .BW..
+    ";
+    let b = with_rules(dead, CellState::Black, RuleSet::japanese());
+    assert_eq!(b.score(&[Point::new(0, 1)]).black, 6.0);
+    let b = with_rules(dead, CellState::Black, RuleSet::chinese());
+    assert_eq!(b.score(&[Point::new(0, 1)]).black, 10.0);
+}
+
+#[test]
+fn test_handicap_compensation() {
+    let expected = [
+        (RuleSet::chinese(), 2.5),
+        (RuleSet::aga(), 1.5),
+        (RuleSet::new_zealand(), 0.5),
+    ];
+    for (rules, white) in expected.iter() {
+        let mut b = Board::new(9, *rules);
+        b.set_komi(0.5);
+        b.set_handicap(2, HandicapPlacement::Fixed).unwrap();
+        let score = b.score(&[]);
+        assert_eq!(score.black, 81.0, "{}", rules.name);
+        assert_eq!(score.white, *white, "{}", rules.name);
+    }
+
+    assert_eq!(Board::new(19, RuleSet::japanese()).get_komi(), 6.5);
+    assert_eq!(Board::new(19, RuleSet::new_zealand()).get_komi(), 7.0);
+    assert_eq!(
+        RuleSet::from_name("tromp-taylor"),
+        Some(RuleSet::tromp_taylor())
+    );
+
+    // presets are written by name and anything else field by field
+    for rules in RuleSet::get_presets() {
+        assert_eq!(rules.to_spec(), rules.name);
+        assert_eq!(RuleSet::from_spec(&rules.to_spec()), Some(rules));
+    }
+    let mut rules = RuleSet::japanese();
+    rules.komi = 0.5;
+    rules.scoring = ScoringMethod::Area;
+    assert_eq!(
+        rules.to_spec(),
+        "Japanese,suicide=no,ko=simple,scoring=area,pass-stones=no,komi=0.5,compensation=none"
+    );
+    assert_eq!(RuleSet::from_spec(&rules.to_spec()), Some(rules));
+    let custom = RuleSet::from_spec(
+        "House,suicide=yes,ko=simple,scoring=territory,pass-stones=yes,komi=5,compensation=one-less",
+    )
+    .unwrap();
+    assert_eq!(custom.name, "Custom");
+    assert!(custom.allow_suicide && custom.pass_stones);
+    assert_eq!(custom.handicap_compensation, HandicapCompensation::OneLess);
+    assert_eq!(RuleSet::from_spec("House"), None);
+    assert_eq!(RuleSet::from_spec("Chinese,ko=none"), None);
+}
+
+#[test]
+fn test_ladder() {
+    // white runs diagonally towards the bottom right corner
+    let board = Board::from_str(
+        "
+        .........
+        ..b......
+        .bwb.....
+        .b.......
+        .........
+        .........
+        .........
+        .........
+        .........
+        ",
+        CellState::White,
+    );
+    let ladder = board.read_ladder(Point::new(2, 2)).unwrap();
+    assert!(ladder.works);
+    assert_eq!(ladder.breaker, None);
+    assert_eq!(ladder.moves[0], (CellState::White, Point::new(2, 3)));
+    assert_eq!(ladder.moves[1], (CellState::Black, Point::new(2, 4)));
+    assert!(ladder.moves.len() > 10);
+
+    // a white stone in the way breaks it
+    let board = Board::from_str(
+        "
+        .........
+        ..b......
+        .bwb.....
+        .b.......
+        .........
+        .........
+        ......w..
+        .........
+        .........
+        ",
+        CellState::White,
+    );
+    let ladder = board.read_ladder(Point::new(2, 2)).unwrap();
+    assert!(!ladder.works);
+    assert_eq!(ladder.breaker, Some(Point::new(6, 6)));
+
+    // only groups in atari have a ladder
+    assert_eq!(board.read_ladder(Point::new(6, 6)), None);
+}
+
+#[test]
+fn test_unconditional_life() {
+    let board = Board::from_str(
+        "
+        .b.b.....
+        bbbb.....
+        .....w...
+        .........
+        .........
+        .........
+        .........
+        .......b.
+        ......b.b
+        ",
+        CellState::White,
+    );
+    let alive = board.get_unconditionally_alive(CellState::Black);
+    assert_eq!(alive.len(), 6);
+    assert!(alive.contains(&Point::new(0, 1)));
+    let mut territory = board.get_unconditional_territory(CellState::Black);
+    territory.sort_by_key(|p| p.x);
+    assert_eq!(territory, vec![Point::new(0, 0), Point::new(2, 0)]);
+
+    // one eye isn't enough
+    assert!(!alive.contains(&Point::new(7, 7)));
+    assert!(board.get_unconditionally_alive(CellState::White).is_empty());
+}
+
+#[test]
+fn test_eyes() {
+    let board = Board::from_str(
+        "
+        .b.b.....
+        bbbbw....
+        .b.bw....
+        .wbww....
+        ..www....
+        .........
+        .........
+        .........
+        .........
+        ",
+        CellState::White,
+    );
+    // the eye at (2, 2) is false because of the white stones at (1, 3) and
+    // (3, 3)
+    assert_eq!(board.count_eyes(Point::new(1, 1)), 2);
+    assert_eq!(board.count_eyes(Point::new(4, 4)), 0);
+}
+
+#[test]
+fn test_group_status() {
+    // white's left side has a dead black stone in it and black's right
+    // side a dead white stone
+    let board = Board::from_str(
+        "
+        ...wb....
+        ...wb....
+        ...wb....
+        ...wb....
+        .b.wb....
+        ...wb....
+        ...wb.w..
+        ...wb....
+        ...wb....
+        ",
+        CellState::Black,
+    );
+    assert_eq!(
+        board.get_group_status(Point::new(3, 0)),
+        Some(GroupStatus::Alive)
+    );
+    assert_eq!(
+        board.get_group_status(Point::new(4, 0)),
+        Some(GroupStatus::Alive)
+    );
+    assert_eq!(
+        board.get_group_status(Point::new(1, 4)),
+        Some(GroupStatus::Dead)
+    );
+    assert_eq!(
+        board.get_group_status(Point::new(6, 6)),
+        Some(GroupStatus::Dead)
+    );
+    assert_eq!(board.get_group_status(Point::new(0, 0)), None);
+
+    let mut dead = board.get_dead_stones();
+    dead.sort_by_key(|p| p.x);
+    assert_eq!(dead, vec![Point::new(1, 4), Point::new(6, 6)]);
+
+    let score = board.score(&dead);
+    assert_eq!(score.get_winner(), CellState::Black);
+
+    // early on nothing is settled
+    let board = Board::from_str(
+        "
+        .........
+        .........
+        ..b......
+        .........
+        ....w....
+        .........
+        .........
+        .........
+        .........
+        ",
+        CellState::Black,
+    );
+    assert_eq!(
+        board.get_group_status(Point::new(2, 2)),
+        Some(GroupStatus::Unsettled)
+    );
+    assert!(board.get_dead_stones().is_empty());
+}
+
+#[test]
+fn test_seki() {
+    // two groups without eyes sharing two liberties at the top
+    let board = Board::from_str(
+        "
+        bw.bw....
+        bw.bw....
+        bwbbw....
+        bbwww....
+        bbw......
+        .bw......
+        bbw......
+        .bw......
+        bbw......
+        ",
+        CellState::Black,
+    );
+    let seki = board.get_seki();
+    assert_eq!(seki.len(), 1);
+    assert_eq!(seki[0].stones.len(), 7);
+    let mut shared = seki[0].shared_liberties.clone();
+    shared.sort_by_key(|p| p.y);
+    assert_eq!(shared, vec![Point::new(2, 0), Point::new(2, 1)]);
+    assert!(seki[0].eyes.is_empty());
+    assert_eq!(
+        board.get_group_status(Point::new(1, 0)),
+        Some(GroupStatus::Seki)
+    );
+    assert_eq!(
+        board.get_group_status(Point::new(3, 0)),
+        Some(GroupStatus::Seki)
+    );
+    assert!(board.get_dead_stones().is_empty());
+
+    // one eye each and one shared liberty
+    let position = "
+        .ww.b.bw.
+        wwwbbbbw.
+        bbbwwwww.
+        bbbw.....
+        .bbw.....
+        bbbw.....
+        .bbw.....
+        bbbw.....
+        .bbw.....
+        ";
+    let board = with_rules(position, CellState::Black, RuleSet::japanese());
+    let seki = board.get_seki();
+    assert_eq!(seki.len(), 1);
+    assert_eq!(seki[0].shared_liberties, vec![Point::new(3, 0)]);
+    let mut eyes = seki[0].eyes.clone();
+    eyes.sort_by_key(|p| p.x);
+    assert_eq!(eyes, vec![Point::new(0, 0), Point::new(5, 0)]);
+    assert!(board.is_in_seki(Point::new(4, 0)));
+    assert!(!board.is_in_seki(Point::new(0, 2)));
+
+    // the eyes in seki aren't territory
+    let score = board.score(&board.get_dead_stones());
+    assert_eq!(score.black, 3.0);
+    assert_eq!(score.white, 39.5);
+
+    // but they are area
+    let board = with_rules(position, CellState::Black, RuleSet::chinese());
+    let score = board.score(&[]);
+    assert_eq!(score.black, 28.0);
+    assert_eq!(score.white, 59.5);
+
+    // an eye beats no eye, so this isn't seki
+    let board = Board::from_str(
+        "
+        .ww.bbbw.
+        wwwbbbbw.
+        bbbwwwww.
+        bbbw.....
+        .bbw.....
+        bbbw.....
+        .bbw.....
+        bbbw.....
+        .bbw.....
+        ",
+        CellState::Black,
+    );
+    assert!(board.get_seki().is_empty());
+}
+
+#[test]
+fn test_tsumego() {
+    // a straight three in the corner: whoever plays the middle decides it
+    let position = "
+        ...bw....
+        bbbbw....
+        wwwww....
+        .........
+        .........
+        .........
+        .........
+        .........
+        .........
+        ";
+    let region = vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)];
+
+    let board = Board::from_str(position, CellState::White);
+    let problem = Tsumego::new(Point::new(0, 1), TsumegoGoal::Kill, region.clone());
+    let solution = board.solve_tsumego(&problem).unwrap();
+    assert_eq!(solution.result, TsumegoResult::Success);
+    assert_eq!(solution.moves.len(), 1);
+    let first = &solution.moves[0];
+    assert_eq!(first.mv, Some(Point::new(1, 0)));
+    // every answer black has is refuted
+    assert!(first.children.len() >= 3);
+    assert!(first
+        .children
+        .iter()
+        .all(|answer| answer.result == TsumegoResult::Success && answer.children.len() == 1));
+
+    let board = Board::from_str(position, CellState::Black);
+    let problem = Tsumego::new(Point::new(0, 1), TsumegoGoal::Live, region.clone());
+    let solution = board.solve_tsumego(&problem).unwrap();
+    assert_eq!(solution.result, TsumegoResult::Success);
+    assert_eq!(solution.moves[0].mv, Some(Point::new(1, 0)));
+
+    let problem = Tsumego::new(Point::new(0, 1), TsumegoGoal::Kill, region);
+    assert!(board.solve_tsumego(&problem).is_err());
+
+    // white has just taken a ko, so black can only capture the stone that
+    // took it by winning the ko
+    let mut board = Board::from_str(
+        "
+        .wb.b
+        ..wb.
+        .....
+        .....
+        .....
+        ",
+        CellState::White,
+    );
+    board.place(Point::new(3, 0));
+    assert_eq!(board.get_ko(), Some(Point::new(2, 0)));
+    let problem = Tsumego::new(Point::new(3, 0), TsumegoGoal::Kill, vec![Point::new(2, 0)]);
+    let solution = board.solve_tsumego(&problem).unwrap();
+    assert_eq!(solution.result, TsumegoResult::Ko);
+    assert!(solution.moves[0].ko_threat);
+
+    // superko in the game's rules doesn't stop the retake being played: it
+    // captures the target, so nothing follows it
+    board.set_rules(RuleSet::chinese());
+    let solution = board.solve_tsumego(&problem).unwrap();
+    assert_eq!(solution.result, TsumegoResult::Ko);
+    assert_eq!(solution.moves[0].mv, Some(Point::new(2, 0)));
+    assert!(solution.moves[0].children.is_empty());
+}
+
+#[test]
+fn test_influence() {
+    let board = Board::new(9, RuleSet::japanese());
+    let influence = board.get_influence(5, 21);
+    assert!(influence.iter().flatten().all(|&v| v == 0));
+    let estimate = board.get_estimate();
+    assert_eq!(estimate.score.black, 0.0);
+    assert_eq!(estimate.score.white, 6.5);
+
+    // a lone stone in the centre spreads its influence evenly
+    let mut board = Board::new(9, RuleSet::japanese());
+    board.place(Point::new(4, 4));
+    let influence = board.get_influence(5, 0);
+    assert!(influence[4][3] > 0);
+    assert_eq!(influence[4][3], influence[3][4]);
+    assert!(influence[4][3] > influence[4][1]);
+
+    // walls with a dead black stone on White's side
+    let board = Board::from_str(
+        "
+        ...wb....
+        ...wb....
+        ...wb....
+        ...wb....
+        .b.wb....
+        ...wb....
+        ...wb....
+        ...wb....
+        ...wb....
+        ",
+        CellState::Black,
+    );
+    let estimate = board.get_estimate();
+    assert!(estimate.ownership[0][0] < -0.5);
+    assert!(estimate.ownership[4][1] < -0.5);
+    assert!(estimate.ownership[0][8] > 0.5);
+    // the same as counting the finished game
+    assert_eq!(estimate.score, board.score(&board.get_dead_stones()));
+}
+
+#[test]
+fn test_symmetry() {
+    for symmetry in Symmetry::ALL.iter() {
+        for &(x, y) in [(0, 0), (1, 3), (6, 2), (8, 8)].iter() {
+            let p = Point::new(x, y);
+            let q = p.transform(*symmetry, 9);
+            assert!(q.x >= 0 && q.x < 9 && q.y >= 0 && q.y < 9);
+            assert_eq!(q.transform(symmetry.inverse(), 9), p);
+        }
+    }
+    assert_eq!(
+        Point::new(1, 0).transform(Symmetry::Rotate90, 9),
+        Point::new(8, 1)
+    );
+    for &a in Symmetry::ALL.iter() {
+        for &b in Symmetry::ALL.iter() {
+            let (a, b) = (Transform::new(a, false), Transform::new(b, true));
+            let p = Point::new(1, 3);
+            assert_eq!(
+                a.then(b).apply_point(p, 9),
+                b.apply_point(a.apply_point(p, 9), 9)
+            );
+            assert!(a.then(b).swap_colours);
+        }
+    }
+
+    // a game with a capture and a ko, turned around and recoloured
+    let mut board = Board::new(5, RuleSet::chinese());
+    for &(x, y) in [
+        (1, 0),
+        (2, 0),
+        (0, 1),
+        (3, 1),
+        (4, 4),
+        (2, 2),
+        (1, 2),
+        (1, 1),
+    ]
+    .iter()
+    {
+        board.place(Point::new(x, y));
+    }
+    board.place(Point::new(2, 1));
+    assert_eq!(board.get_ko(), Some(Point::new(1, 1)));
+    let transform = Transform::new(Symmetry::Rotate90, true);
+    let turned = board.transform(transform);
+    for y in 0..5 {
+        for x in 0..5 {
+            let p = Point::new(x, y);
+            assert_eq!(
+                turned.get(transform.apply_point(p, 5)),
+                transform.apply_state(board.get(p))
+            );
+        }
+    }
+    assert_eq!(turned.get_turn(), CellState::Black);
+    assert_eq!(turned.get_ko(), Some(Point::new(3, 1)));
+    assert_eq!(turned.get_captured_stones(CellState::Black), 0);
+    assert_eq!(turned.get_captured_stones(CellState::White), 1);
+    assert_eq!(turned.get_moves().len(), board.get_moves().len());
+    assert!(!turned.can_place(Point::new(3, 1)));
+    assert_eq!(
+        turned.transform(transform.inverse()).get_hash(),
+        board.get_hash()
+    );
+
+    // every version of a position has the same canonical form
+    let (canonical, _) = board.get_canonical_form(true);
+    assert_eq!(canonical.get_turn(), CellState::Black);
+    for symmetry in Symmetry::ALL.iter() {
+        for &swap in [false, true].iter() {
+            let other = board.transform(Transform::new(*symmetry, swap));
+            let (hash, found) = other.get_canonical_hash(true);
+            assert_eq!(hash, canonical.get_hash());
+            assert_eq!(other.transform(found).get_hash(), hash);
+        }
+        let other = board.transform(Transform::new(*symmetry, false));
+        assert_eq!(
+            other.get_canonical_hash(false).0,
+            board.get_canonical_hash(false).0
+        );
+    }
+    // without colour swaps the colours stay apart
+    let swapped = board.transform(Transform::new(Symmetry::Identity, true));
+    assert_ne!(
+        swapped.get_canonical_hash(false).0,
+        board.get_canonical_hash(false).0
+    );
+
+    // an empty board is already canonical
+    let empty = Board::new(9, RuleSet::japanese());
+    assert_eq!(empty.get_canonical_hash(true), (0, Transform::IDENTITY));
+    assert_eq!(empty.get_symmetries().len(), 8);
+    let mut corner = empty;
+    corner.place(Point::new(2, 2));
+    assert_eq!(
+        corner.get_symmetries(),
+        vec![Symmetry::Identity, Symmetry::Transpose]
+    );
+}
+
+#[test]
+fn test_clock() {
+    let secs = Duration::from_secs;
+    assert_eq!(
+        TimeControl::parse("byoyomi:600+30x5"),
+        Ok(TimeControl::ByoYomi {
+            main: secs(600),
+            period: secs(30),
+            periods: 5
+        })
+    );
+    assert_eq!(
+        TimeControl::parse("fischer:300+10"),
+        Ok(TimeControl::Fischer {
+            main: secs(300),
+            increment: secs(10)
+        })
+    );
+    assert!(TimeControl::parse("canadian:600+300").is_err());
+    // times too long for a `Duration` are refused rather than panicking,
+    // and the longest it can hold still give a budget
+    assert!(TimeControl::parse("absolute:inf").is_err());
+    assert!(TimeControl::parse("fischer:1e30+5").is_err());
+    assert!(TimeControl::parse("byoyomi:10+-1x3").is_err());
+    let long = TimeControl::parse("fischer:1e19+1e19").unwrap();
+    assert!(GameClock::new(long)
+        .get_move_budget(CellState::Black)
+        .is_some());
+    let mut clock = PlayerClock::new(long);
+    clock.complete_move(long);
+    clock.complete_move(long);
+    assert_eq!(clock.main, Duration::MAX);
+
+    // the increment is added after every move
+    let fischer = TimeControl::parse("fischer:60+5").unwrap();
+    let mut clock = PlayerClock::new(fischer);
+    assert!(clock.spend(fischer, secs(20)));
+    clock.complete_move(fischer);
+    assert_eq!(clock.main, secs(45));
+    assert_eq!(clock.format(fischer), "0:45");
+    assert!(!clock.spend(fischer, secs(46)));
+    assert!(clock.flagged);
+
+    // a move inside a byo-yomi period keeps it, and a period that runs
+    // out is used up
+    let byo_yomi = TimeControl::parse("byoyomi:10+30x3").unwrap();
+    let mut clock = PlayerClock::new(byo_yomi);
+    assert!(clock.spend(byo_yomi, secs(25)));
+    assert_eq!((clock.period, clock.count), (secs(15), 3));
+    clock.complete_move(byo_yomi);
+    assert_eq!(clock.period, secs(30));
+    assert!(clock.spend(byo_yomi, secs(70)));
+    assert_eq!((clock.period, clock.count), (secs(20), 1));
+    assert_eq!(clock.format(byo_yomi), "0:20 (1)");
+    assert!(!clock.spend(byo_yomi, secs(20)));
+
+    // a Canadian period starts again once its moves have been played
+    let canadian = TimeControl::parse("canadian:0+60/2").unwrap();
+    let mut clock = PlayerClock::new(canadian);
+    assert!(clock.spend(canadian, secs(50)));
+    clock.complete_move(canadian);
+    assert_eq!(clock.format(canadian), "0:10/1");
+    clock.complete_move(canadian);
+    assert_eq!((clock.period, clock.count), (secs(60), 2));
+    assert!(!clock.spend(canadian, secs(60)));
+
+    // a Canadian control built without stones counts every move as a period
+    let no_stones = TimeControl::Canadian {
+        main: Duration::ZERO,
+        period: secs(30),
+        stones: 0,
+    };
+    let mut clock = PlayerClock::new(no_stones);
+    assert!(clock.spend(no_stones, secs(20)));
+    clock.complete_move(no_stones);
+    assert_eq!((clock.period, clock.count), (secs(30), 1));
+
+    // running out of time on the game clock forfeits the game
+    let absolute = TimeControl::parse("absolute:10").unwrap();
+    let mut board = Board::new(9, RuleSet::japanese());
+    let mut game_clock = GameClock::new(absolute);
+    let start = Instant::now();
+    game_clock.start(CellState::Black, start);
+    assert!(game_clock.play(&mut board, Some(Point::new(2, 2)), start + secs(4)));
+    assert_eq!(game_clock.get_running(), Some(CellState::White));
+    assert!(!game_clock.check(&mut board, start + secs(13)));
+    assert!(game_clock.play(&mut board, None, start + secs(13)));
+    assert_eq!(game_clock.get_clock(CellState::Black).main, secs(6));
+    assert!(!game_clock.play(&mut board, Some(Point::new(6, 6)), start + secs(20)));
+    assert!(board.is_game_over());
+    assert_eq!(
+        board.get_forfeit(),
+        Some(Forfeit {
+            loser: CellState::Black,
+            reason: ForfeitReason::Time
+        })
+    );
+    assert_eq!(board.get_forfeit().unwrap().to_string(), "W+T");
+    assert!(!board.can_place(Point::new(6, 6)));
+    assert_eq!(board.get_moves().len(), 2);
+    assert_eq!(game_clock.get_running(), None);
+
+    let mut board = Board::new(9, RuleSet::japanese());
+    board.forfeit(CellState::White, ForfeitReason::Resignation);
+    assert_eq!(board.get_forfeit().unwrap().to_string(), "B+R");
+    board.reset();
+    assert!(!board.is_game_over());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    // White takes a black stone in a ko, which Black can't take back at once
+    let mut board = Board::from_str(
+        "
+        .BW..
+        B.BW.
+        .BW..
+        .....
+        .....",
+        CellState::White,
+    );
+    board.set_rules(RuleSet::chinese());
+    board.set_komi(7.5);
+    board.play(Some(Point::new(1, 1)));
+    assert_eq!(board.get_ko(), Some(Point::new(2, 1)));
+
+    let json = serde_json::to_string(&board).unwrap();
+    assert_eq!(
+        json,
+        r#"{"version":1,"size":5,"stones":".BW../BW.W./.BW../...../.....","turn":"black","captures":{"black":0,"white":1},"ko":{"x":2,"y":1},"rules":{"name":"Chinese","allow_suicide":false,"ko":"positional-superko","scoring":"area","pass_stones":false,"komi":7.5,"handicap_compensation":"full"},"komi":7.5}"#
+    );
+    let read: Board = serde_json::from_str(&json).unwrap();
+    assert_eq!(encode_stones(&read), encode_stones(&board));
+    assert_eq!(read.get_turn(), CellState::Black);
+    assert_eq!(read.get_captured_stones(CellState::White), 1);
+    assert_eq!(read.get_ko(), board.get_ko());
+    assert_eq!(read.get_rules().name, "Chinese");
+    assert_eq!(read.get_hash(), board.get_hash());
+    assert!(!read.can_place(Point::new(2, 1)));
+
+    // rules changed from a preset keep their changes
+    let mut rules = RuleSet::chinese();
+    rules.allow_suicide = true;
+    rules.ko = KoRule::SituationalSuperko;
+    board.set_rules(rules);
+    let read: Board = serde_json::from_str(&serde_json::to_string(&board).unwrap()).unwrap();
+    assert_eq!(*read.get_rules(), rules);
+
+    assert_eq!(
+        serde_json::to_string(&CellState::None).unwrap(),
+        r#""empty""#
+    );
+    let p: Point = serde_json::from_str(r#"{"x":3,"y":4}"#).unwrap();
+    assert_eq!(p, Point::new(3, 4));
+
+    // newer versions and malformed boards are refused
+    let newer = json.replace(r#""version":1"#, r#""version":2"#);
+    assert!(serde_json::from_str::<Board>(&newer).is_err());
+    let short = json.replace(".BW../BW.W./", ".BW../BW.W/");
+    assert!(serde_json::from_str::<Board>(&short).is_err());
+}
@@ -0,0 +1,313 @@
+use crate::go_core::*;
+use std::collections::HashMap;
+
+/// What the player to move is trying to do to the target group.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TsumegoGoal {
+    /// Capture the opponent's group.
+    Kill,
+    /// Keep their own group on the board.
+    Live,
+}
+
+/// How a life-and-death problem turns out for the player to move.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TsumegoResult {
+    Failure,
+    /// The goal can only be reached by winning a ko.
+    Ko,
+    Success,
+}
+
+/// A life-and-death problem: the group at `target`, what the player to move
+/// wants for it and the points where either side may play.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tsumego {
+    pub target: Point,
+    pub goal: TsumegoGoal,
+    pub region: Vec<Point>,
+    /// How many moves deep to read before giving up, which counts as a
+    /// failure for the player to move.
+    pub max_depth: usize,
+}
+
+impl Tsumego {
+    pub fn new(target: Point, goal: TsumegoGoal, region: Vec<Point>) -> Tsumego {
+        let max_depth = region.len() * 4;
+        Tsumego {
+            target,
+            goal,
+            region,
+            max_depth,
+        }
+    }
+}
+
+/// A move in a solution tree with how the problem turns out after it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TsumegoNode {
+    pub player: CellState,
+    /// The move, or `None` for a pass (playing elsewhere).
+    pub mv: Option<Point>,
+    /// Exact along the line of best play. For answers that are no better
+    /// than one already found, the search stops as soon as that is certain,
+    /// so their result is only a bound: no worse than shown for the side
+    /// that cut the search short.
+    pub result: TsumegoResult,
+    /// Whether the move retakes a ko after both sides have played a ko
+    /// threat elsewhere.
+    pub ko_threat: bool,
+    /// The best answer after the solver's moves, or every answer after the
+    /// opponent's so that all the refutations are covered.
+    pub children: Vec<TsumegoNode>,
+}
+
+/// The result of a problem and the tree of moves that shows it, starting
+/// with the best first move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TsumegoSolution {
+    pub result: TsumegoResult,
+    pub moves: Vec<TsumegoNode>,
+}
+
+// the position, whose turn it is, the ko point, passes in a row and
+// whether a ko threat has been used
+type Key = (u64, usize, Option<(i32, i32)>, u32, bool);
+
+// what a stored result says about the real one, which a search cut short by
+// its bounds only knows from one side
+#[derive(Copy, Clone, PartialEq)]
+enum Bound {
+    Exact,
+    // the real result is at least this good for the solver
+    Lower,
+    // the real result is at most this good for the solver
+    Upper,
+}
+
+struct Solver<'a> {
+    problem: &'a Tsumego,
+    solver: CellState,
+    defender: CellState,
+    table: HashMap<Key, (TsumegoResult, Bound, Vec<TsumegoNode>)>,
+    // set whenever the depth limit cuts the search short, when the result
+    // depends on the path and can't be stored
+    cut_short: bool,
+}
+
+impl Board {
+    /// Solves a life-and-death problem for the player to move with an
+    /// alpha-beta search over the moves in the problem's region and a
+    /// transposition table. A line is dropped as soon as the other side is
+    /// sure of doing at least as well elsewhere, e.g. once the defender can
+    /// hold the solver to a ko when another first move already wins one.
+    ///
+    /// The target is captured when its stones leave the board, and lives
+    /// when it's unconditionally alive or both sides pass, which covers
+    /// seki. A ko that's been taken can be retaken after a pair of ko
+    /// threats, once per line, and a side that needs to do this only gets
+    /// [`TsumegoResult::Ko`].
+    pub fn solve_tsumego(&self, problem: &Tsumego) -> Result<TsumegoSolution, String> {
+        let defender = self.get(problem.target);
+        if defender == CellState::None {
+            return Err("there's no group at the target".to_string());
+        }
+        let solver = self.get_turn();
+        match problem.goal {
+            TsumegoGoal::Kill if solver == defender => {
+                return Err("the player to move can't kill their own group".to_string());
+            }
+            TsumegoGoal::Live if solver != defender => {
+                return Err("the player to move can only make their own group live".to_string());
+            }
+            _ => {}
+        }
+
+        // ko is handled by the search, so only simple ko is needed
+        let mut board = self.clone();
+        let mut rules = *self.get_rules();
+        rules.ko = KoRule::Simple;
+        rules.pass_stones = false;
+        board.set_rules(rules);
+
+        let mut search = Solver {
+            problem,
+            solver,
+            defender,
+            table: HashMap::new(),
+            cut_short: false,
+        };
+        let (result, moves) = search.search(
+            &board,
+            0,
+            false,
+            problem.max_depth,
+            TsumegoResult::Failure,
+            TsumegoResult::Success,
+        );
+        Ok(TsumegoSolution { result, moves })
+    }
+}
+
+impl<'a> Solver<'a> {
+    // the result of the position for the solver, with the moves from it.
+    // `alpha` is what the solver is already sure of and `beta` what the
+    // defender can already hold them to, so the search stops once it's
+    // outside them
+    fn search(
+        &mut self,
+        board: &Board,
+        passes: u32,
+        threat_used: bool,
+        depth: usize,
+        alpha: TsumegoResult,
+        beta: TsumegoResult,
+    ) -> (TsumegoResult, Vec<TsumegoNode>) {
+        let captured = if self.solver == self.defender {
+            TsumegoResult::Failure
+        } else {
+            TsumegoResult::Success
+        };
+        let lives = if captured == TsumegoResult::Success {
+            TsumegoResult::Failure
+        } else {
+            TsumegoResult::Success
+        };
+
+        let target = self.problem.target;
+        if board.get(target) != self.defender {
+            return (captured, Vec::new());
+        }
+        if passes >= 2
+            || (board.count_liberties(target) >= 2
+                && board
+                    .get_unconditionally_alive(self.defender)
+                    .contains(&target))
+        {
+            return (lives, Vec::new());
+        }
+        if depth == 0 {
+            self.cut_short = true;
+            return (TsumegoResult::Failure, Vec::new());
+        }
+
+        let key = (
+            board.get_hash(),
+            board.get_turn() as usize,
+            board.get_ko().map(|p| (p.x, p.y)),
+            passes,
+            threat_used,
+        );
+        if let Some((result, bound, nodes)) = self.table.get(&key) {
+            let usable = match bound {
+                Bound::Exact => true,
+                Bound::Lower => *result >= beta,
+                Bound::Upper => *result <= alpha,
+            };
+            if usable {
+                return (*result, nodes.clone());
+            }
+        }
+
+        // moves in the region, a ko retake after threats and a pass
+        let mut options = Vec::new();
+        for &p in &self.problem.region {
+            if board.can_place(p) {
+                options.push((Some(p), false));
+            } else if !threat_used && board.get_ko() == Some(p) {
+                options.push((Some(p), true));
+            }
+        }
+        options.push((None, false));
+
+        let player = board.get_turn();
+        let solving = player == self.solver;
+        let was_cut_short = self.cut_short;
+        self.cut_short = false;
+
+        let mut nodes: Vec<TsumegoNode> = Vec::new();
+        let mut result = if solving {
+            TsumegoResult::Failure
+        } else {
+            TsumegoResult::Success
+        };
+        for (mv, ko_threat) in options {
+            let mut next = board.clone();
+            if ko_threat {
+                next.clear_ko();
+            }
+            next.play(mv);
+            // a move the board refused would leave the same side to move
+            if next.get_moves().len() != board.get_moves().len() + 1 {
+                continue;
+            }
+            let next_passes = if mv.is_none() { passes + 1 } else { 0 };
+            // winning a ko is the most either side gets from a retake
+            let (mut low, mut high) = if solving {
+                (alpha.max(result), beta)
+            } else {
+                (alpha, beta.min(result))
+            };
+            if ko_threat {
+                if solving {
+                    high = high.min(TsumegoResult::Ko);
+                } else {
+                    low = low.max(TsumegoResult::Ko);
+                }
+            }
+            let (mut value, children) = self.search(
+                &next,
+                next_passes,
+                threat_used || ko_threat,
+                depth - 1,
+                low,
+                high,
+            );
+            if ko_threat {
+                value = if solving {
+                    value.min(TsumegoResult::Ko)
+                } else {
+                    value.max(TsumegoResult::Ko)
+                };
+            }
+            let node = TsumegoNode {
+                player,
+                mv,
+                result: value,
+                ko_threat,
+                children,
+            };
+
+            if solving {
+                if nodes.is_empty() || value > result {
+                    result = value;
+                    nodes = vec![node];
+                }
+                if value >= beta {
+                    break;
+                }
+            } else if value <= alpha {
+                // one refutation is enough
+                result = value;
+                nodes = vec![node];
+                break;
+            } else {
+                result = result.min(value);
+                nodes.push(node);
+            }
+        }
+
+        if !self.cut_short {
+            let bound = if result >= beta && result != TsumegoResult::Success {
+                Bound::Lower
+            } else if result <= alpha && result != TsumegoResult::Failure {
+                Bound::Upper
+            } else {
+                Bound::Exact
+            };
+            self.table.insert(key, (result, bound, nodes.clone()));
+        }
+        self.cut_short |= was_cut_short;
+        (result, nodes)
+    }
+}
@@ -0,0 +1,20 @@
+use crate::go_core::*;
+
+// Zobrist keys are derived from the point and colour with splitmix64 rather
+// than stored in a table, so boards of any size share them.
+pub fn get_zobrist_key(p: Point, state: CellState) -> u64 {
+    let colour = match state {
+        CellState::None => return 0,
+        CellState::White => 1,
+        CellState::Black => 2,
+    };
+    let idx = ((p.y as u64) << 32 | p.x as u64) * 3 + colour;
+    splitmix64(idx)
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
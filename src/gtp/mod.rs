@@ -0,0 +1,317 @@
+//! The Go Text Protocol (GTP version 2), for playing the engines through
+//! other programs such as GoGui or twogtp.
+
+#[cfg(test)]
+mod tests;
+
+use std::time::{Duration, Instant};
+
+use crate::engine::*;
+use crate::go_core::*;
+
+const COMMANDS: [&str; 17] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "quit",
+    "boardsize",
+    "clear_board",
+    "komi",
+    "play",
+    "genmove",
+    "final_score",
+    "final_status_list",
+    "showboard",
+    "time_settings",
+    "time_left",
+    "kgs-time_settings",
+];
+
+// GTP columns skip I
+const COLUMNS: &str = "ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+/// The GTP vertex of `p` on a board of `size`, e.g. `(3, 15)` on 19x19 is
+/// `D4`. Rows count up from the bottom.
+pub fn point_to_gtp(p: Point, size: usize) -> String {
+    let column = COLUMNS.as_bytes()[p.x as usize] as char;
+    format!("{}{}", column, size as i32 - p.y)
+}
+
+/// Parses a GTP vertex, or `pass` (as `Ok(None)`).
+pub fn gtp_to_point(vertex: &str, size: usize) -> Result<Option<Point>, String> {
+    let vertex = vertex.to_uppercase();
+    if vertex == "PASS" {
+        return Ok(None);
+    }
+    let mut chars = vertex.chars();
+    let x = chars
+        .next()
+        .and_then(|c| COLUMNS.find(c))
+        .ok_or_else(|| format!("invalid vertex '{}'", vertex))?;
+    let row: usize = chars
+        .as_str()
+        .parse()
+        .map_err(|_| format!("invalid vertex '{}'", vertex))?;
+    if x >= size || row == 0 || row > size {
+        return Err(format!("vertex '{}' is off the board", vertex));
+    }
+    Ok(Some(Point::new(x as i32, (size - row) as i32)))
+}
+
+fn parse_color(color: &str) -> Result<CellState, String> {
+    match color.to_lowercase().as_str() {
+        "b" | "black" => Ok(CellState::Black),
+        "w" | "white" => Ok(CellState::White),
+        _ => Err(format!("invalid color '{}'", color)),
+    }
+}
+
+fn parse_seconds(seconds: &str) -> Result<Duration, String> {
+    seconds
+        .parse::<f64>()
+        .ok()
+        .and_then(|t| Duration::try_from_secs_f64(t).ok())
+        .ok_or_else(|| "syntax error".to_string())
+}
+
+/// A GTP session: the game so far and the engine that plays it. The
+/// engine's own clock is kept from how long it thinks and whatever the
+/// controller reports with `time_left`, and decides how long each search
+/// runs for.
+pub struct GtpEngine {
+    board: Board,
+    engine: Box<dyn MoveGenerator>,
+    clock: GameClock,
+    running: bool,
+}
+
+impl GtpEngine {
+    pub fn new(board: Board, engine: Box<dyn MoveGenerator>) -> GtpEngine {
+        GtpEngine {
+            board,
+            engine,
+            clock: GameClock::new(TimeControl::Unlimited),
+            running: true,
+        }
+    }
+
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn get_clock(&self) -> &GameClock {
+        &self.clock
+    }
+
+    /// False once `quit` has been received.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Handles one line of input and returns the full response, blank line
+    /// included, or `None` for empty lines and comments.
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let mut first = words.next()?;
+        let id = match first.parse::<u32>() {
+            Ok(id) => {
+                first = words.next().unwrap_or("");
+                id.to_string()
+            }
+            Err(_) => String::new(),
+        };
+        let args: Vec<&str> = words.collect();
+        let (status, response) = match self.handle_command(first, &args) {
+            Ok(response) => ('=', response),
+            Err(error) => ('?', error),
+        };
+        if response.is_empty() {
+            Some(format!("{}{}\n\n", status, id))
+        } else {
+            Some(format!("{}{} {}\n\n", status, id, response))
+        }
+    }
+
+    fn handle_command(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        let arg = |i: usize| {
+            args.get(i)
+                .copied()
+                .ok_or_else(|| "syntax error".to_string())
+        };
+        match command {
+            "protocol_version" => Ok("2".to_string()),
+            "name" => Ok("go.rs".to_string()),
+            "version" => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            "known_command" => Ok(COMMANDS.contains(&arg(0)?).to_string()),
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "quit" => {
+                self.running = false;
+                Ok(String::new())
+            }
+            "boardsize" => {
+                let size: usize = arg(0)?.parse().map_err(|_| "syntax error")?;
+                if !(2..=COLUMNS.len()).contains(&size) {
+                    return Err("unacceptable size".to_string());
+                }
+                self.new_board(size);
+                Ok(String::new())
+            }
+            "clear_board" => {
+                self.new_board(self.board.get_size());
+                Ok(String::new())
+            }
+            "komi" => {
+                let komi: f32 = arg(0)?.parse().map_err(|_| "syntax error")?;
+                self.board.set_komi(komi);
+                Ok(String::new())
+            }
+            "play" => {
+                let player = parse_color(arg(0)?)?;
+                let mv = gtp_to_point(arg(1)?, self.board.get_size())?;
+                // GTP allows several moves in a row by one colour, which is
+                // how some controllers set up a handicap; an illegal move
+                // leaves the board as it was
+                let mut board = self.board.clone();
+                board.set_turn(player);
+                if let Some(p) = mv {
+                    if !board.can_place(p) {
+                        return Err("illegal move".to_string());
+                    }
+                }
+                board.play(mv);
+                self.board = board;
+                Ok(String::new())
+            }
+            "genmove" => {
+                let player = parse_color(arg(0)?)?;
+                self.board.set_turn(player);
+                if let Some(budget) = self.clock.get_move_budget(player) {
+                    self.engine.set_time_budget(budget);
+                }
+                let start = Instant::now();
+                let mv = self
+                    .engine
+                    .generate_move(&self.board)
+                    .filter(|&p| self.board.can_place(p));
+                self.clock.record_move(player, start.elapsed());
+                self.board.play(mv);
+                Ok(match mv {
+                    Some(p) => point_to_gtp(p, self.board.get_size()),
+                    None => "pass".to_string(),
+                })
+            }
+            "final_score" => Ok(self.board.score(&self.board.get_dead_stones()).to_string()),
+            "final_status_list" => {
+                let dead = self.board.get_dead_stones();
+                let seki: Vec<Point> = self
+                    .board
+                    .get_seki()
+                    .into_iter()
+                    .flat_map(|seki| seki.stones)
+                    .collect();
+                let size = self.board.get_size();
+                let stones: Vec<String> = match arg(0)? {
+                    "dead" => dead.iter().map(|&p| point_to_gtp(p, size)).collect(),
+                    "alive" => [CellState::Black, CellState::White]
+                        .iter()
+                        .flat_map(|&player| self.board.get_chains(player))
+                        .flatten()
+                        .filter(|p| !dead.contains(p) && !seki.contains(p))
+                        .map(|p| point_to_gtp(p, size))
+                        .collect(),
+                    "seki" => seki.iter().map(|&p| point_to_gtp(p, size)).collect(),
+                    _ => return Err("syntax error".to_string()),
+                };
+                Ok(stones.join(" "))
+            }
+            "showboard" => Ok(format!("\n{}", self.draw_board())),
+            "time_settings" => {
+                let main = parse_seconds(arg(0)?)?;
+                let period = parse_seconds(arg(1)?)?;
+                let stones: u32 = arg(2)?.parse().map_err(|_| "syntax error")?;
+                // a period with no stones means no time limit at all
+                let control = if period == Duration::ZERO {
+                    TimeControl::Absolute { main }
+                } else if stones == 0 {
+                    TimeControl::Unlimited
+                } else {
+                    TimeControl::Canadian {
+                        main,
+                        period,
+                        stones,
+                    }
+                };
+                self.clock = GameClock::new(control);
+                Ok(String::new())
+            }
+            "kgs-time_settings" => {
+                let number = |i: usize| -> Result<u32, String> {
+                    arg(i)?.parse().map_err(|_| "syntax error".to_string())
+                };
+                let control = match arg(0)? {
+                    "none" => TimeControl::Unlimited,
+                    "absolute" => TimeControl::Absolute {
+                        main: parse_seconds(arg(1)?)?,
+                    },
+                    "byoyomi" => TimeControl::ByoYomi {
+                        main: parse_seconds(arg(1)?)?,
+                        period: parse_seconds(arg(2)?)?,
+                        periods: number(3)?,
+                    },
+                    "canadian" => TimeControl::Canadian {
+                        main: parse_seconds(arg(1)?)?,
+                        period: parse_seconds(arg(2)?)?,
+                        stones: number(3)?,
+                    },
+                    _ => return Err("syntax error".to_string()),
+                };
+                self.clock = GameClock::new(control);
+                Ok(String::new())
+            }
+            "time_left" => {
+                let player = parse_color(arg(0)?)?;
+                let time = parse_seconds(arg(1)?)?;
+                let stones: u32 = arg(2)?.parse().map_err(|_| "syntax error")?;
+                self.clock.set_time_left(player, time, stones);
+                Ok(String::new())
+            }
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    // a new empty board keeps the komi and time settings the controller
+    // set
+    fn new_board(&mut self, size: usize) {
+        let komi = self.board.get_komi();
+        self.board = Board::new(size, *self.board.get_rules());
+        self.board.set_komi(komi);
+        self.clock = GameClock::new(self.clock.get_control());
+    }
+
+    fn draw_board(&self) -> String {
+        let size = self.board.get_size();
+        let mut text = String::new();
+        for y in 0..size {
+            text += &format!("{:2} ", size - y);
+            for x in 0..size {
+                text.push(match self.board.get(Point::new(x as i32, y as i32)) {
+                    CellState::Black => 'X',
+                    CellState::White => 'O',
+                    CellState::None => '.',
+                });
+                text.push(' ');
+            }
+            text = text.trim_end().to_string();
+            text.push('\n');
+        }
+        text += "   ";
+        for c in COLUMNS.chars().take(size) {
+            text.push(c);
+            text.push(' ');
+        }
+        text.trim_end().to_string()
+    }
+}
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use crate::engine::*;
+use crate::go_core::*;
+use crate::gtp::*;
+
+#[test]
+fn test_vertices() {
+    assert_eq!(point_to_gtp(Point::new(3, 15), 19), "D4");
+    assert_eq!(point_to_gtp(Point::new(8, 0), 9), "J9");
+    assert_eq!(gtp_to_point("d4", 19), Ok(Some(Point::new(3, 15))));
+    assert_eq!(gtp_to_point("J9", 9), Ok(Some(Point::new(8, 0))));
+    assert_eq!(gtp_to_point("pass", 9), Ok(None));
+    assert!(gtp_to_point("I5", 9).is_err());
+    assert!(gtp_to_point("A10", 9).is_err());
+}
+
+#[test]
+fn test_commands() {
+    let engine = Box::new(RandomPlayer::with_seed(1));
+    let mut gtp = GtpEngine::new(Board::new(19, RuleSet::chinese()), engine);
+
+    assert_eq!(
+        gtp.handle_line("protocol_version"),
+        Some("= 2\n\n".to_string())
+    );
+    assert_eq!(gtp.handle_line("   # a comment"), None);
+    assert_eq!(gtp.handle_line("3 boardsize 9"), Some("=3\n\n".to_string()));
+    assert_eq!(gtp.get_board().get_size(), 9);
+    assert_eq!(gtp.handle_line("komi 5.5"), Some("=\n\n".to_string()));
+    assert_eq!(gtp.handle_line("play b e5"), Some("=\n\n".to_string()));
+    assert_eq!(
+        gtp.handle_line("play w e5"),
+        Some("? illegal move\n\n".to_string())
+    );
+
+    let response = gtp.handle_line("genmove w").unwrap();
+    assert!(response.starts_with("= "));
+    assert_eq!(gtp.get_board().get_moves().len(), 2);
+    assert_eq!(gtp.get_board().get_komi(), 5.5);
+
+    // a colour can move twice in a row, without a pass in between
+    let response = gtp.handle_line("genmove w").unwrap();
+    assert!(response.starts_with("= "));
+    assert_eq!(gtp.get_board().get_moves().len(), 3);
+    assert_eq!(gtp.get_board().get_turn(), CellState::Black);
+
+    assert_eq!(gtp.handle_line("quit"), Some("=\n\n".to_string()));
+    assert!(!gtp.is_running());
+}
+
+#[test]
+fn test_consecutive_moves() {
+    // a handicap set up the way some controllers do it, with Black's stones
+    // played one after the other
+    let engine = Box::new(RandomPlayer::with_seed(1));
+    let mut gtp = GtpEngine::new(Board::new(9, RuleSet::aga()), engine);
+    for command in &["play b c3", "play b g7", "play b c7", "play w e5"] {
+        assert_eq!(gtp.handle_line(command), Some("=\n\n".to_string()));
+    }
+    let board = gtp.get_board();
+    assert_eq!(board.get(Point::new(2, 2)), CellState::Black);
+    assert_eq!(board.get(Point::new(4, 4)), CellState::White);
+    assert_eq!(board.get_turn(), CellState::Black);
+    // no passes were played, so nobody has been given a pass stone
+    assert!(board.get_moves().iter().all(|&(_, mv)| mv.is_some()));
+    assert_eq!(board.get_captured_stones(CellState::White), 0);
+    assert_eq!(
+        gtp.handle_line("play w e5"),
+        Some("? illegal move\n\n".to_string())
+    );
+}
+
+#[test]
+fn test_illegal_move_keeps_ko() {
+    // Black takes a ko, and a refused move by Black mustn't hand White the
+    // turn with the ko lifted
+    let engine = Box::new(RandomPlayer::with_seed(1));
+    let mut gtp = GtpEngine::new(Board::new(9, RuleSet::japanese()), engine);
+    for command in &[
+        "play b b9",
+        "play b a8",
+        "play b b7",
+        "play w c9",
+        "play w d8",
+        "play w c7",
+        "play w b8",
+        "play b c8",
+    ] {
+        assert_eq!(gtp.handle_line(command), Some("=\n\n".to_string()));
+    }
+    assert_eq!(gtp.get_board().get_ko(), Some(Point::new(1, 1)));
+    assert_eq!(
+        gtp.handle_line("play b c8"),
+        Some("? illegal move\n\n".to_string())
+    );
+    assert_eq!(gtp.get_board().get_turn(), CellState::White);
+    assert_eq!(gtp.get_board().get_moves().len(), 8);
+    assert_eq!(
+        gtp.handle_line("play w b8"),
+        Some("? illegal move\n\n".to_string())
+    );
+}
+
+#[test]
+fn test_time_settings() {
+    let engine = Box::new(RandomPlayer::with_seed(1));
+    let mut gtp = GtpEngine::new(Board::new(9, RuleSet::chinese()), engine);
+    assert_eq!(gtp.get_clock().get_control(), TimeControl::Unlimited);
+
+    assert_eq!(
+        gtp.handle_line("time_settings 300 30 5"),
+        Some("=\n\n".to_string())
+    );
+    assert_eq!(
+        gtp.get_clock().get_control(),
+        TimeControl::Canadian {
+            main: Duration::from_secs(300),
+            period: Duration::from_secs(30),
+            stones: 5
+        }
+    );
+    assert_eq!(
+        gtp.handle_line("time_left b 1e30 0"),
+        Some("? syntax error\n\n".to_string())
+    );
+    assert_eq!(
+        gtp.handle_line("time_left b 20 3"),
+        Some("=\n\n".to_string())
+    );
+    let clock = gtp.get_clock().get_clock(CellState::Black);
+    assert_eq!(clock.main, Duration::ZERO);
+    assert_eq!(clock.period, Duration::from_secs(20));
+    assert_eq!(clock.count, 3);
+    assert_eq!(
+        gtp.get_clock().get_move_budget(CellState::Black),
+        Some(Duration::from_secs(16) / 3)
+    );
+
+    // the engine's own thinking comes off its clock
+    assert!(gtp.handle_line("genmove b").unwrap().starts_with("= "));
+    let clock = gtp.get_clock().get_clock(CellState::Black);
+    assert_eq!(clock.count, 2);
+    assert!(clock.period < Duration::from_secs(20));
+
+    gtp.handle_line("time_settings 60 0 0");
+    assert_eq!(
+        gtp.get_clock().get_control(),
+        TimeControl::Absolute {
+            main: Duration::from_secs(60)
+        }
+    );
+    gtp.handle_line("time_settings 0 10 0");
+    assert_eq!(gtp.get_clock().get_control(), TimeControl::Unlimited);
+    gtp.handle_line("kgs-time_settings byoyomi 600 30 5");
+    assert_eq!(
+        gtp.get_clock().get_control(),
+        TimeControl::ByoYomi {
+            main: Duration::from_secs(600),
+            period: Duration::from_secs(30),
+            periods: 5
+        }
+    );
+    assert_eq!(
+        gtp.handle_line("kgs-time_settings hourglass 60"),
+        Some("? syntax error\n\n".to_string())
+    );
+    assert_eq!(
+        gtp.handle_line("known_command time_left"),
+        Some("= true\n\n".to_string())
+    );
+}
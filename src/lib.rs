@@ -0,0 +1,30 @@
+//! A Go (weiqi/baduk) rules engine.
+//!
+//! The [`go_core`] module holds the board, stones and rules and has no
+//! dependencies, so it can be used from servers and analysis tools as well
+//! as the GUIs. The `frontend` module, enabled by the `frontend` feature
+//! (which the `sdl` and `bracket` features turn on), is the renderer-agnostic
+//! game controller that both GUI binaries are built on, and the `analysis`
+//! module, enabled by the `analysis` feature, answers KataGo-style JSON
+//! analysis queries.
+//!
+//! ```
+//! use go::{Board, CellState, Point, RuleSet};
+//!
+//! let mut board = Board::new(9, RuleSet::chinese());
+//! board.place(Point::new(2, 2));
+//! assert_eq!(board.get(Point::new(2, 2)), CellState::Black);
+//! assert_eq!(board.get_turn(), CellState::White);
+//! ```
+
+#[cfg(feature = "analysis")]
+pub mod analysis;
+pub mod engine;
+#[cfg(feature = "frontend")]
+pub mod frontend;
+pub mod go_core;
+pub mod gtp;
+pub mod net;
+pub mod sgf;
+
+pub use go_core::{Board, CellState, Point, RuleSet};
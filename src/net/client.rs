@@ -0,0 +1,206 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use crate::go_core::*;
+use crate::net::*;
+
+/// A connection to a [`GameServer`], keeping a copy of the game up to date
+/// from what the server sends. Lines are read on a thread of their own, so
+/// [`GameClient::poll`] never blocks.
+pub struct GameClient {
+    stream: TcpStream,
+    lines: Receiver<String>,
+    connected: bool,
+    colour: Option<CellState>,
+    board: Option<Board>,
+    black: Option<String>,
+    white: Option<String>,
+    chat: Vec<(String, String)>,
+    result: Option<String>,
+}
+
+impl GameClient {
+    /// Connects to the server at `address`, e.g. `192.168.1.20:7007`, and
+    /// asks to join as `name`, playing `colour` or whichever is free.
+    pub fn connect(
+        address: &str,
+        name: &str,
+        colour: Option<CellState>,
+    ) -> Result<GameClient, String> {
+        let mut client = GameClient::open(address)?;
+        let name = get_single_word(name);
+        client.send(ClientMessage::Join { name, colour })?;
+        Ok(client)
+    }
+
+    /// Connects to the server at `address` to watch its game as `name`.
+    pub fn watch(address: &str, name: &str) -> Result<GameClient, String> {
+        let mut client = GameClient::open(address)?;
+        let name = get_single_word(name);
+        client.send(ClientMessage::Watch { name })?;
+        Ok(client)
+    }
+
+    fn open(address: &str) -> Result<GameClient, String> {
+        let stream = TcpStream::connect(address)
+            .map_err(|e| format!("couldn't connect to {}: {}", address, e))?;
+        let reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let sent = line.map(|line| sender.send(line));
+                if !matches!(sent, Ok(Ok(()))) {
+                    break;
+                }
+            }
+        });
+        Ok(GameClient {
+            stream,
+            lines,
+            connected: true,
+            colour: None,
+            board: None,
+            black: None,
+            white: None,
+            chat: Vec::new(),
+            result: None,
+        })
+    }
+
+    pub fn send(&mut self, message: ClientMessage) -> Result<(), String> {
+        let line = format!("{}\n", message.to_line(self.get_size()));
+        self.stream
+            .write_all(line.as_bytes())
+            .map_err(|e| format!("couldn't send to the server: {}", e))
+    }
+
+    /// False once the server has closed the connection.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// The colour the server gave us, once it has welcomed us, which is
+    /// `CellState::None` for a spectator.
+    pub fn get_colour(&self) -> Option<CellState> {
+        self.colour
+    }
+
+    pub fn is_spectator(&self) -> bool {
+        self.colour == Some(CellState::None)
+    }
+
+    /// The game as the server has described it so far.
+    pub fn get_board(&self) -> Option<&Board> {
+        self.board.as_ref()
+    }
+
+    pub fn get_player_name(&self, colour: CellState) -> Option<&str> {
+        match colour {
+            CellState::White => self.white.as_deref(),
+            _ => self.black.as_deref(),
+        }
+    }
+
+    /// Everything said so far, as the name of the speaker and the text.
+    pub fn get_chat(&self) -> &[(String, String)] {
+        &self.chat
+    }
+
+    /// The result, once the server has announced the end of the game.
+    pub fn get_result(&self) -> Option<&str> {
+        self.result.as_deref()
+    }
+
+    // vertices are read and written on the game's board, or a full size one
+    // before the server has said what that is
+    fn get_size(&self) -> usize {
+        self.board.as_ref().map_or(19, |board| board.get_size())
+    }
+
+    /// Applies and returns every message that has arrived, without waiting.
+    /// Lines that can't be read come back as errors.
+    pub fn poll(&mut self) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        loop {
+            match self.lines.try_recv() {
+                Ok(line) => messages.extend(self.receive(&line)),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+        messages
+    }
+
+    /// Waits up to `timeout` for the next message and applies it.
+    pub fn wait(&mut self, timeout: Duration) -> Option<ServerMessage> {
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => self.receive(&line),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => {
+                self.connected = false;
+                None
+            }
+        }
+    }
+
+    fn receive(&mut self, line: &str) -> Option<ServerMessage> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        let message = match ServerMessage::parse(line, self.get_size()) {
+            Ok(message) => message,
+            Err(e) => return Some(ServerMessage::Error(e)),
+        };
+        match &message {
+            ServerMessage::Welcome {
+                colour,
+                size,
+                handicap,
+                komi,
+                rules,
+            } => {
+                let mut board = Board::new(*size, *rules);
+                if let Err(e) = board.set_handicap(*handicap, HandicapPlacement::Fixed) {
+                    return Some(ServerMessage::Error(e));
+                }
+                board.set_komi(*komi);
+                self.board = Some(board);
+                self.colour = Some(*colour);
+                self.result = None;
+            }
+            ServerMessage::Joined { colour, name } => match colour {
+                CellState::White => self.white = Some(name.clone()),
+                _ => self.black = Some(name.clone()),
+            },
+            ServerMessage::Left { colour, .. } => match colour {
+                CellState::White => self.white = None,
+                _ => self.black = None,
+            },
+            ServerMessage::Played { mv, .. } => {
+                if let Some(board) = &mut self.board {
+                    board.play(*mv);
+                }
+            }
+            ServerMessage::Resigned(colour) => {
+                if let Some(board) = &mut self.board {
+                    board.forfeit(*colour, ForfeitReason::Resignation);
+                }
+            }
+            ServerMessage::GameOver(result) => self.result = Some(result.clone()),
+            ServerMessage::Chat { name, text } => self.chat.push((name.clone(), text.clone())),
+            ServerMessage::Error(_) => {}
+        }
+        Some(message)
+    }
+}
+
+// names are sent as a single word
+fn get_single_word(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
@@ -0,0 +1,239 @@
+use crate::go_core::*;
+use crate::net::*;
+
+/// Who a message from the host goes to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Recipient {
+    Client(usize),
+    Everyone,
+}
+
+/// The authoritative game behind a server, without the networking: clients
+/// are numbered by the server, their messages go in, and the messages to
+/// send back come out. Only the player to move can play, and only legal
+/// moves are accepted. Spectators are sent the game so far when they
+/// start watching and everything after that, but can only chat.
+pub struct GameHost {
+    board: Board,
+    // the client and name sitting in each colour's seat
+    black: Option<(usize, String)>,
+    white: Option<(usize, String)>,
+    spectators: Vec<(usize, String)>,
+}
+
+impl GameHost {
+    /// Hosts a game of `board`, which should be empty apart from any fixed
+    /// handicap stones.
+    pub fn new(board: Board) -> GameHost {
+        GameHost {
+            board,
+            black: None,
+            white: None,
+            spectators: Vec::new(),
+        }
+    }
+
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    fn get_seat(&mut self, colour: CellState) -> &mut Option<(usize, String)> {
+        match colour {
+            CellState::White => &mut self.white,
+            _ => &mut self.black,
+        }
+    }
+
+    /// The colour `client` is playing, if they have joined.
+    pub fn get_colour(&self, client: usize) -> Option<CellState> {
+        if self.black.as_ref().is_some_and(|(c, _)| *c == client) {
+            Some(CellState::Black)
+        } else if self.white.as_ref().is_some_and(|(c, _)| *c == client) {
+            Some(CellState::White)
+        } else {
+            None
+        }
+    }
+
+    /// Whether `client` is watching the game.
+    pub fn is_spectator(&self, client: usize) -> bool {
+        self.spectators.iter().any(|(c, _)| *c == client)
+    }
+
+    /// The names of the spectators, in the order they started watching.
+    pub fn get_spectators(&self) -> Vec<&str> {
+        self.spectators
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .collect()
+    }
+
+    // the name `client` joined or started watching with
+    fn get_name(&self, client: usize) -> Option<&str> {
+        match self.get_colour(client) {
+            Some(colour) => self.get_player_name(colour),
+            None => self
+                .spectators
+                .iter()
+                .find(|(c, _)| *c == client)
+                .map(|(_, name)| name.as_str()),
+        }
+    }
+
+    /// The name of whoever is playing `colour`.
+    pub fn get_player_name(&self, colour: CellState) -> Option<&str> {
+        let seat = match colour {
+            CellState::White => &self.white,
+            _ => &self.black,
+        };
+        seat.as_ref().map(|(_, name)| name.as_str())
+    }
+
+    /// Handles a message from `client`, returning what to send and to whom.
+    pub fn handle(
+        &mut self,
+        client: usize,
+        message: ClientMessage,
+    ) -> Vec<(Recipient, ServerMessage)> {
+        let error = |e: &str| {
+            vec![(
+                Recipient::Client(client),
+                ServerMessage::Error(e.to_string()),
+            )]
+        };
+        match message {
+            ClientMessage::Join { name, colour } => {
+                if self.get_name(client).is_some() {
+                    return error("you have already joined");
+                }
+                let colour = match colour {
+                    Some(colour) if self.get_seat(colour).is_none() => colour,
+                    Some(_) => return error("that colour has been taken"),
+                    None if self.black.is_none() => CellState::Black,
+                    None if self.white.is_none() => CellState::White,
+                    None => return error("the game is full, but you can watch it"),
+                };
+                let mut messages = self.get_welcome(client, colour);
+                *self.get_seat(colour) = Some((client, name.clone()));
+                messages.push((Recipient::Everyone, ServerMessage::Joined { colour, name }));
+                messages
+            }
+            ClientMessage::Watch { name } => {
+                if self.get_name(client).is_some() {
+                    return error("you have already joined");
+                }
+                let messages = self.get_welcome(client, CellState::None);
+                self.spectators.push((client, name));
+                messages
+            }
+            ClientMessage::Play(mv) => {
+                let colour = match self.get_colour(client) {
+                    Some(colour) => colour,
+                    None if self.is_spectator(client) => return error("spectators can't play"),
+                    None => return error("you haven't joined the game"),
+                };
+                if self.board.is_game_over() {
+                    return error("the game is over");
+                }
+                if colour != self.board.get_turn() {
+                    return error("it isn't your turn");
+                }
+                if mv.is_some_and(|p| !self.board.can_place(p)) {
+                    return error("illegal move");
+                }
+                self.board.play(mv);
+                let mut messages =
+                    vec![(Recipient::Everyone, ServerMessage::Played { colour, mv })];
+                messages.extend(self.get_game_over());
+                messages
+            }
+            ClientMessage::Resign => {
+                let colour = match self.get_colour(client) {
+                    Some(colour) => colour,
+                    None if self.is_spectator(client) => return error("spectators can't resign"),
+                    None => return error("you haven't joined the game"),
+                };
+                if self.board.is_game_over() {
+                    return error("the game is over");
+                }
+                self.board.forfeit(colour, ForfeitReason::Resignation);
+                let mut messages = vec![(Recipient::Everyone, ServerMessage::Resigned(colour))];
+                messages.extend(self.get_game_over());
+                messages
+            }
+            ClientMessage::Chat(text) => match self.get_name(client) {
+                Some(name) => {
+                    let name = name.to_string();
+                    vec![(Recipient::Everyone, ServerMessage::Chat { name, text })]
+                }
+                None => error("you haven't joined the game"),
+            },
+        }
+    }
+
+    /// Frees the seat of a client that has gone, telling everyone else, or
+    /// forgets a spectator.
+    pub fn disconnect(&mut self, client: usize) -> Vec<(Recipient, ServerMessage)> {
+        self.spectators.retain(|(c, _)| *c != client);
+        match self.get_colour(client) {
+            Some(colour) => {
+                let (_, name) = self.get_seat(colour).take().unwrap();
+                vec![(Recipient::Everyone, ServerMessage::Left { colour, name })]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    // the game so far for a client that has just joined as `colour`, or
+    // started watching as `None`: the
+    // starting position, who is playing, the moves and the result
+    fn get_welcome(&self, client: usize, colour: CellState) -> Vec<(Recipient, ServerMessage)> {
+        let to = Recipient::Client(client);
+        let mut messages = vec![(
+            to,
+            ServerMessage::Welcome {
+                colour,
+                size: self.board.get_size(),
+                handicap: self.board.get_handicap(),
+                komi: self.board.get_komi(),
+                rules: *self.board.get_rules(),
+            },
+        )];
+        for &player in [CellState::Black, CellState::White].iter() {
+            if let Some(name) = self.get_player_name(player) {
+                let name = name.to_string();
+                messages.push((
+                    to,
+                    ServerMessage::Joined {
+                        colour: player,
+                        name,
+                    },
+                ));
+            }
+        }
+        for &(player, mv) in self.board.get_moves() {
+            messages.push((to, ServerMessage::Played { colour: player, mv }));
+        }
+        if let Some(forfeit) = self.board.get_forfeit() {
+            messages.push((to, ServerMessage::Resigned(forfeit.loser)));
+        }
+        messages.extend(
+            self.get_game_over()
+                .into_iter()
+                .map(|(_, message)| (to, message)),
+        );
+        messages
+    }
+
+    // the result for everyone, if the game has ended
+    fn get_game_over(&self) -> Option<(Recipient, ServerMessage)> {
+        if !self.board.is_game_over() {
+            return None;
+        }
+        let result = match self.board.get_forfeit() {
+            Some(forfeit) => forfeit.to_string(),
+            None => self.board.score(&self.board.get_dead_stones()).to_string(),
+        };
+        Some((Recipient::Everyone, ServerMessage::GameOver(result)))
+    }
+}
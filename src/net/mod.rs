@@ -0,0 +1,17 @@
+//! Playing over the network: a server that hosts a game for two players
+//! and any number of spectators and keeps the authoritative board, and the
+//! client that joins or watches it. The protocol is plain text, a line per
+//! message.
+
+mod client;
+mod host;
+mod protocol;
+mod server;
+
+#[cfg(test)]
+mod tests;
+
+pub use client::*;
+pub use host::*;
+pub use protocol::*;
+pub use server::*;
@@ -0,0 +1,208 @@
+use crate::go_core::*;
+use crate::gtp::*;
+
+/// The TCP port the server listens on unless told otherwise.
+pub const DEFAULT_PORT: u16 = 7007;
+
+/// A line from a client to the server.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientMessage {
+    /// Takes a seat in the game as `name`, playing the colour asked for or
+    /// whichever is free.
+    Join {
+        name: String,
+        colour: Option<CellState>,
+    },
+    /// Follows the game as a spectator called `name`, without a seat.
+    Watch {
+        name: String,
+    },
+    /// A stone, or a pass for `None`.
+    Play(Option<Point>),
+    Resign,
+    Chat(String),
+}
+
+/// A line from the server to its clients. Points are GTP vertices, so the
+/// size of the board has to be known to read them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerMessage {
+    /// Sent to a client that has joined: their colour, or `None` for a
+    /// spectator, and the game, which starts from an empty board with fixed
+    /// handicap stones. The moves so far follow.
+    Welcome {
+        colour: CellState,
+        size: usize,
+        handicap: usize,
+        komi: f32,
+        rules: RuleSet,
+    },
+    Joined {
+        colour: CellState,
+        name: String,
+    },
+    Left {
+        colour: CellState,
+        name: String,
+    },
+    Played {
+        colour: CellState,
+        mv: Option<Point>,
+    },
+    Resigned(CellState),
+    /// The result once the game is over, e.g. `B+R` or `W+6.5`.
+    GameOver(String),
+    Chat {
+        name: String,
+        text: String,
+    },
+    Error(String),
+}
+
+fn parse_colour(colour: &str) -> Result<CellState, String> {
+    match colour.to_lowercase().as_str() {
+        "b" | "black" => Ok(CellState::Black),
+        "w" | "white" => Ok(CellState::White),
+        _ => Err(format!("invalid colour '{}'", colour)),
+    }
+}
+
+// a spectator is welcomed without a colour
+fn parse_seat(seat: &str) -> Result<CellState, String> {
+    match seat.to_lowercase().as_str() {
+        "spectator" => Ok(CellState::None),
+        _ => parse_colour(seat),
+    }
+}
+
+fn colour_name(colour: CellState) -> String {
+    match colour {
+        CellState::None => "spectator".to_string(),
+        _ => colour.to_string().to_lowercase(),
+    }
+}
+
+// the command word and the rest of the line
+fn split_command(line: &str) -> (String, &str) {
+    let line = line.trim();
+    let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+    (command.to_uppercase(), rest.trim())
+}
+
+impl ClientMessage {
+    /// Reads a line such as `JOIN alice black`, `WATCH carol`, `PLAY D4`,
+    /// `PLAY pass`, `RESIGN` or `CHAT good game`. Vertices are read on a `size` board.
+    pub fn parse(line: &str, size: usize) -> Result<ClientMessage, String> {
+        let (command, rest) = split_command(line);
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        match (command.as_str(), words.as_slice()) {
+            ("JOIN", [name]) => Ok(ClientMessage::Join {
+                name: name.to_string(),
+                colour: None,
+            }),
+            ("JOIN", [name, colour]) => Ok(ClientMessage::Join {
+                name: name.to_string(),
+                colour: Some(parse_colour(colour)?),
+            }),
+            ("WATCH", [name]) => Ok(ClientMessage::Watch {
+                name: name.to_string(),
+            }),
+            ("PLAY", [vertex]) => Ok(ClientMessage::Play(gtp_to_point(vertex, size)?)),
+            ("RESIGN", []) => Ok(ClientMessage::Resign),
+            ("CHAT", _) => Ok(ClientMessage::Chat(rest.to_string())),
+            _ => Err(format!("invalid message '{}'", line.trim())),
+        }
+    }
+
+    /// The message as a line, without the newline.
+    pub fn to_line(&self, size: usize) -> String {
+        match self {
+            ClientMessage::Join { name, colour } => match colour {
+                Some(colour) => format!("JOIN {} {}", name, colour_name(*colour)),
+                None => format!("JOIN {}", name),
+            },
+            ClientMessage::Watch { name } => format!("WATCH {}", name),
+            ClientMessage::Play(mv) => format!("PLAY {}", vertex(*mv, size)),
+            ClientMessage::Resign => "RESIGN".to_string(),
+            ClientMessage::Chat(text) => format!("CHAT {}", text),
+        }
+    }
+}
+
+impl ServerMessage {
+    /// Reads a line sent by the server, reading vertices on a `size` board.
+    pub fn parse(line: &str, size: usize) -> Result<ServerMessage, String> {
+        let (command, rest) = split_command(line);
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let invalid = || format!("invalid message '{}'", line.trim());
+        match (command.as_str(), words.as_slice()) {
+            ("WELCOME", [colour, size, handicap, komi, rules]) => Ok(ServerMessage::Welcome {
+                colour: parse_seat(colour)?,
+                size: size.parse().map_err(|_| invalid())?,
+                handicap: handicap.parse().map_err(|_| invalid())?,
+                komi: komi.parse().map_err(|_| invalid())?,
+                rules: RuleSet::from_spec(rules).ok_or_else(invalid)?,
+            }),
+            ("JOINED", [colour, name]) => Ok(ServerMessage::Joined {
+                colour: parse_colour(colour)?,
+                name: name.to_string(),
+            }),
+            ("LEFT", [colour, name]) => Ok(ServerMessage::Left {
+                colour: parse_colour(colour)?,
+                name: name.to_string(),
+            }),
+            ("PLAYED", [colour, vertex]) => Ok(ServerMessage::Played {
+                colour: parse_colour(colour)?,
+                mv: gtp_to_point(vertex, size)?,
+            }),
+            ("RESIGNED", [colour]) => Ok(ServerMessage::Resigned(parse_colour(colour)?)),
+            ("GAMEOVER", [result]) => Ok(ServerMessage::GameOver(result.to_string())),
+            ("CHAT", [name, ..]) => Ok(ServerMessage::Chat {
+                name: name.to_string(),
+                text: rest[name.len()..].trim().to_string(),
+            }),
+            ("ERROR", _) => Ok(ServerMessage::Error(rest.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// The message as a line, without the newline.
+    pub fn to_line(&self, size: usize) -> String {
+        match self {
+            ServerMessage::Welcome {
+                colour,
+                size,
+                handicap,
+                komi,
+                rules,
+            } => format!(
+                "WELCOME {} {} {} {} {}",
+                colour_name(*colour),
+                size,
+                handicap,
+                komi,
+                rules.to_spec()
+            ),
+            ServerMessage::Joined { colour, name } => {
+                format!("JOINED {} {}", colour_name(*colour), name)
+            }
+            ServerMessage::Left { colour, name } => {
+                format!("LEFT {} {}", colour_name(*colour), name)
+            }
+            ServerMessage::Played { colour, mv } => {
+                format!("PLAYED {} {}", colour_name(*colour), vertex(*mv, size))
+            }
+            ServerMessage::Resigned(colour) => format!("RESIGNED {}", colour_name(*colour)),
+            ServerMessage::GameOver(result) => format!("GAMEOVER {}", result),
+            ServerMessage::Chat { name, text } => format!("CHAT {} {}", name, text),
+            ServerMessage::Error(message) => format!("ERROR {}", message),
+        }
+    }
+}
+
+fn vertex(mv: Option<Point>, size: usize) -> String {
+    match mv {
+        Some(p) => point_to_gtp(p, size),
+        None => "pass".to_string(),
+    }
+}
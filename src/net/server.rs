@@ -0,0 +1,136 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::go_core::*;
+use crate::net::*;
+
+// how many lines can wait to be written to a client before it's taken to
+// have stopped reading and is disconnected
+const SEND_QUEUE: usize = 4096;
+
+// a connection, written to by a thread of its own so that a client that
+// stops reading can't hold up the others
+struct Client {
+    id: usize,
+    lines: SyncSender<String>,
+    stream: TcpStream,
+}
+
+// the game and the connections to write to, shared by the client threads
+struct Shared {
+    host: GameHost,
+    clients: Vec<Client>,
+    next_client: usize,
+}
+
+impl Shared {
+    fn send(&mut self, messages: Vec<(Recipient, ServerMessage)>) {
+        let size = self.host.get_board().get_size();
+        for (recipient, message) in messages {
+            let line = format!("{}\n", message.to_line(size));
+            for client in &self.clients {
+                if recipient == Recipient::Everyone || recipient == Recipient::Client(client.id) {
+                    // a client that has fallen behind is cut off, and its
+                    // own thread then drops it
+                    if client.lines.try_send(line.clone()).is_err() {
+                        let _ = client.stream.shutdown(Shutdown::Both);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Hosts one game over TCP for clients speaking the line protocol (see
+/// [`ClientMessage`] and [`ServerMessage`]), with a thread per connection.
+pub struct GameServer {
+    listener: TcpListener,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl GameServer {
+    /// Listens on `address`, e.g. `0.0.0.0:7007`, or `127.0.0.1:0` for any
+    /// free port.
+    pub fn bind(address: &str, board: Board) -> Result<GameServer, String> {
+        let listener = TcpListener::bind(address)
+            .map_err(|e| format!("couldn't listen on {}: {}", address, e))?;
+        Ok(GameServer {
+            listener,
+            shared: Arc::new(Mutex::new(Shared {
+                host: GameHost::new(board),
+                clients: Vec::new(),
+                next_client: 0,
+            })),
+        })
+    }
+
+    pub fn get_address(&self) -> Result<SocketAddr, String> {
+        self.listener.local_addr().map_err(|e| e.to_string())
+    }
+
+    /// The game as it stands.
+    pub fn get_board(&self) -> Board {
+        self.shared.lock().unwrap().host.get_board().clone()
+    }
+
+    /// Accepts connections until listening fails, serving each one on its
+    /// own thread.
+    pub fn run(&self) -> Result<(), String> {
+        for stream in self.listener.incoming() {
+            let stream = stream.map_err(|e| e.to_string())?;
+            let shared = self.shared.clone();
+            thread::spawn(move || serve_client(&shared, stream));
+        }
+        Ok(())
+    }
+}
+
+fn serve_client(shared: &Mutex<Shared>, stream: TcpStream) {
+    let (mut writer, control) = match (stream.try_clone(), stream.try_clone()) {
+        (Ok(writer), Ok(control)) => (writer, control),
+        _ => return,
+    };
+    let (lines, queue) = mpsc::sync_channel::<String>(SEND_QUEUE);
+    thread::spawn(move || {
+        for line in queue {
+            if writer.write_all(line.as_bytes()).is_err() {
+                let _ = writer.shutdown(Shutdown::Both);
+                break;
+            }
+        }
+    });
+    let client = {
+        let mut shared = shared.lock().unwrap();
+        let client = shared.next_client;
+        shared.next_client += 1;
+        shared.clients.push(Client {
+            id: client,
+            lines,
+            stream: control,
+        });
+        client
+    };
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut shared = shared.lock().unwrap();
+        let size = shared.host.get_board().get_size();
+        let messages = match ClientMessage::parse(&line, size) {
+            Ok(message) => shared.host.handle(client, message),
+            Err(e) => vec![(Recipient::Client(client), ServerMessage::Error(e))],
+        };
+        shared.send(messages);
+    }
+    let mut shared = shared.lock().unwrap();
+    shared.clients.retain(|c| c.id != client);
+    let messages = shared.host.disconnect(client);
+    shared.send(messages);
+}
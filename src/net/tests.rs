@@ -0,0 +1,301 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::go_core::*;
+use crate::net::*;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn test_protocol() {
+    let d4 = Point::new(3, 15);
+    assert_eq!(
+        ClientMessage::parse("join alice white", 19),
+        Ok(ClientMessage::Join {
+            name: "alice".to_string(),
+            colour: Some(CellState::White)
+        })
+    );
+    assert_eq!(
+        ClientMessage::parse("PLAY D4", 19),
+        Ok(ClientMessage::Play(Some(d4)))
+    );
+    assert_eq!(
+        ClientMessage::parse("CHAT  good game ", 19),
+        Ok(ClientMessage::Chat("good game".to_string()))
+    );
+    assert!(ClientMessage::parse("PLAY Z99", 19).is_err());
+    assert!(ClientMessage::parse("DANCE", 19).is_err());
+
+    let messages = [
+        ServerMessage::Welcome {
+            colour: CellState::Black,
+            size: 9,
+            handicap: 2,
+            komi: 0.5,
+            rules: RuleSet::chinese(),
+        },
+        ServerMessage::Played {
+            colour: CellState::White,
+            mv: Some(Point::new(2, 6)),
+        },
+        ServerMessage::Played {
+            colour: CellState::Black,
+            mv: None,
+        },
+        ServerMessage::Chat {
+            name: "bob".to_string(),
+            text: "nice move".to_string(),
+        },
+        ServerMessage::GameOver("W+R".to_string()),
+        // rules that aren't a preset are spelt out
+        ServerMessage::Welcome {
+            colour: CellState::None,
+            size: 9,
+            handicap: 0,
+            komi: 7.5,
+            rules: RuleSet {
+                pass_stones: true,
+                ..RuleSet::japanese()
+            },
+        },
+    ];
+    for message in messages.iter() {
+        let line = message.to_line(9);
+        assert_eq!(
+            ServerMessage::parse(&line, 9).as_ref(),
+            Ok(message),
+            "{}",
+            line
+        );
+    }
+    assert_eq!(messages[1].to_line(9), "PLAYED white C3");
+}
+
+#[test]
+fn test_host() {
+    let mut host = GameHost::new(Board::new(9, RuleSet::japanese()));
+    let join = |name: &str, colour| ClientMessage::Join {
+        name: name.to_string(),
+        colour,
+    };
+    let is_error = |messages: &[(Recipient, ServerMessage)]| {
+        matches!(messages, [(Recipient::Client(_), ServerMessage::Error(_))])
+    };
+
+    let messages = host.handle(0, join("alice", Some(CellState::White)));
+    assert!(matches!(
+        messages[0],
+        (
+            Recipient::Client(0),
+            ServerMessage::Welcome {
+                colour: CellState::White,
+                ..
+            }
+        )
+    ));
+    assert!(is_error(
+        &host.handle(1, join("bob", Some(CellState::White)))
+    ));
+    host.handle(1, join("bob", None));
+    assert_eq!(host.get_colour(1), Some(CellState::Black));
+    assert!(is_error(&host.handle(2, join("carol", None))));
+
+    // only the player to move can play, and only legal moves
+    let e5 = Some(Point::new(4, 4));
+    assert!(is_error(&host.handle(0, ClientMessage::Play(e5))));
+    assert!(is_error(&host.handle(2, ClientMessage::Play(e5))));
+    assert_eq!(
+        host.handle(1, ClientMessage::Play(e5)),
+        vec![(
+            Recipient::Everyone,
+            ServerMessage::Played {
+                colour: CellState::Black,
+                mv: e5
+            }
+        )]
+    );
+    assert!(is_error(&host.handle(0, ClientMessage::Play(e5))));
+
+    // a player who rejoins gets the game so far
+    host.disconnect(1);
+    let messages = host.handle(3, join("bob", None));
+    assert!(messages.contains(&(
+        Recipient::Client(3),
+        ServerMessage::Played {
+            colour: CellState::Black,
+            mv: e5
+        }
+    )));
+
+    let messages = host.handle(0, ClientMessage::Resign);
+    assert_eq!(
+        messages[1],
+        (
+            Recipient::Everyone,
+            ServerMessage::GameOver("B+R".to_string())
+        )
+    );
+    assert!(host.get_board().is_game_over());
+    assert!(is_error(&host.handle(3, ClientMessage::Play(None))));
+}
+
+#[test]
+fn test_server() {
+    let mut board = Board::new(9, RuleSet::chinese());
+    board.set_handicap(2, HandicapPlacement::Fixed).unwrap();
+    let server = GameServer::bind("127.0.0.1:0", board).unwrap();
+    let address = server.get_address().unwrap().to_string();
+    thread::spawn(move || server.run());
+
+    // waits for a message matching `found`
+    let wait_for = |client: &mut GameClient, found: &dyn Fn(&ServerMessage) -> bool| loop {
+        match client.wait(TIMEOUT) {
+            Some(message) if found(&message) => break,
+            Some(_) => {}
+            None => panic!("no message from the server"),
+        }
+    };
+    // bob only joins once alice has taken Black
+    let mut black = GameClient::connect(&address, "alice", Some(CellState::Black)).unwrap();
+    wait_for(&mut black, &|m| matches!(m, ServerMessage::Welcome { .. }));
+    let mut white = GameClient::connect(&address, "bob", None).unwrap();
+    let joined = |name: &str| {
+        let name = name.to_string();
+        move |m: &ServerMessage| matches!(m, ServerMessage::Joined { name: n, .. } if *n == name)
+    };
+    wait_for(&mut black, &joined("bob"));
+    wait_for(&mut white, &joined("bob"));
+    assert_eq!(white.get_colour(), Some(CellState::White));
+    assert_eq!(white.get_player_name(CellState::Black), Some("alice"));
+    assert_eq!(white.get_board().unwrap().get_handicap(), 2);
+
+    let played = |m: &ServerMessage| matches!(m, ServerMessage::Played { .. });
+    let e5 = Point::new(4, 4);
+    white.send(ClientMessage::Play(Some(e5))).unwrap();
+    wait_for(&mut black, &played);
+    assert_eq!(black.get_board().unwrap().get(e5), CellState::White);
+
+    // an illegal move is refused and changes nothing
+    black.send(ClientMessage::Play(Some(e5))).unwrap();
+    wait_for(&mut black, &|m| matches!(m, ServerMessage::Error(_)));
+    assert_eq!(black.get_board().unwrap().get_moves().len(), 1);
+
+    black
+        .send(ClientMessage::Chat("hello there".to_string()))
+        .unwrap();
+    wait_for(&mut white, &|m| matches!(m, ServerMessage::Chat { .. }));
+    assert_eq!(
+        white.get_chat(),
+        &[("alice".to_string(), "hello there".to_string())]
+    );
+
+    black.send(ClientMessage::Resign).unwrap();
+    wait_for(&mut white, &|m| matches!(m, ServerMessage::GameOver(_)));
+    assert_eq!(white.get_result(), Some("W+R"));
+    assert!(white.get_board().unwrap().is_game_over());
+}
+
+#[test]
+fn test_spectators() {
+    let server = GameServer::bind("127.0.0.1:0", Board::new(9, RuleSet::japanese())).unwrap();
+    let address = server.get_address().unwrap().to_string();
+    thread::spawn(move || server.run());
+
+    let wait_for = |client: &mut GameClient, found: &dyn Fn(&ServerMessage) -> bool| loop {
+        match client.wait(TIMEOUT) {
+            Some(message) if found(&message) => break,
+            Some(_) => {}
+            None => panic!("no message from the server"),
+        }
+    };
+    let welcome = |m: &ServerMessage| matches!(m, ServerMessage::Welcome { .. });
+    let played = |m: &ServerMessage| matches!(m, ServerMessage::Played { .. });
+    let mut black = GameClient::connect(&address, "alice", None).unwrap();
+    wait_for(&mut black, &welcome);
+    let mut white = GameClient::connect(&address, "bob", None).unwrap();
+    wait_for(&mut white, &welcome);
+    let moves = [Point::new(4, 4), Point::new(2, 2), Point::new(6, 6)];
+    for (i, &p) in moves.iter().enumerate() {
+        let player = if i % 2 == 0 { &mut black } else { &mut white };
+        let colour = player.get_colour().unwrap();
+        player.send(ClientMessage::Play(Some(p))).unwrap();
+        wait_for(
+            player,
+            &|m| matches!(m, ServerMessage::Played { colour: c, .. } if *c == colour),
+        );
+    }
+
+    // a spectator gets the game so far, then follows it live
+    let mut spectator = GameClient::watch(&address, "carol").unwrap();
+    for _ in 0..moves.len() {
+        wait_for(&mut spectator, &played);
+    }
+    assert!(spectator.is_spectator());
+    let board = spectator.get_board().unwrap();
+    assert_eq!(board.get(moves[1]), CellState::White);
+    assert_eq!(board.get_turn(), CellState::White);
+    assert_eq!(spectator.get_player_name(CellState::White), Some("bob"));
+
+    spectator
+        .send(ClientMessage::Play(Some(Point::new(0, 0))))
+        .unwrap();
+    wait_for(&mut spectator, &|m| matches!(m, ServerMessage::Error(_)));
+    spectator
+        .send(ClientMessage::Chat("go bob".to_string()))
+        .unwrap();
+    wait_for(&mut white, &|m| matches!(m, ServerMessage::Chat { .. }));
+    assert_eq!(
+        white.get_chat(),
+        &[("carol".to_string(), "go bob".to_string())]
+    );
+
+    white.send(ClientMessage::Play(None)).unwrap();
+    wait_for(&mut spectator, &played);
+    assert_eq!(spectator.get_board().unwrap().get_moves().len(), 4);
+    assert_eq!(
+        spectator.get_board().unwrap().get(Point::new(0, 0)),
+        CellState::None
+    );
+}
+
+#[test]
+fn test_stalled_client() {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    let server = GameServer::bind("127.0.0.1:0", Board::new(9, RuleSet::japanese())).unwrap();
+    let address = server.get_address().unwrap().to_string();
+    thread::spawn(move || server.run());
+
+    // a spectator that never reads what it's sent
+    let mut lurker = TcpStream::connect(&address).unwrap();
+    lurker.write_all(b"WATCH lurker\n").unwrap();
+    let mut alice = GameClient::connect(&address, "alice", Some(CellState::Black)).unwrap();
+
+    // far more chat than the connection can hold, and then one last line
+    let text = "x".repeat(500);
+    for _ in 0..20000 {
+        alice.send(ClientMessage::Chat(text.clone())).unwrap();
+    }
+    alice.send(ClientMessage::Chat("done".to_string())).unwrap();
+    loop {
+        match alice.wait(TIMEOUT) {
+            Some(ServerMessage::Chat { text, .. }) if text == "done" => break,
+            Some(_) => {}
+            None => panic!("the stalled client held up the others"),
+        }
+    }
+
+    // the spectator was cut off rather than left to fall further behind
+    lurker.set_read_timeout(Some(TIMEOUT)).unwrap();
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        match lurker.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => break,
+            Err(e) => panic!("the spectator is still connected: {}", e),
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::go_core::*;
+use crate::sgf::*;
+
+// the first line of a saved database
+const DATABASE_HEADER: &str = "# go game database v1";
+
+/// A game in a [`GameDatabase`]: where it came from, who played it, and
+/// its main line as far as it could be replayed.
+#[derive(Clone)]
+pub struct GameRecord {
+    /// The file the game was read from, with `#n` on the end for the nth
+    /// game of a file holding more than one.
+    pub source: String,
+    pub black: String,
+    pub white: String,
+    /// The `RE` property, e.g. `B+R` or `W+3.5`.
+    pub result: String,
+    start: Board,
+    moves: Vec<Option<Point>>,
+    // the canonical hash of the position before each move and at the end
+    hashes: Vec<u64>,
+}
+
+impl GameRecord {
+    pub fn get_size(&self) -> usize {
+        self.start.get_size()
+    }
+
+    /// The moves of the main line, with `None` for a pass.
+    pub fn get_moves(&self) -> &[Option<Point>] {
+        &self.moves
+    }
+
+    /// The position after the first `move_number` moves.
+    pub fn get_board(&self, move_number: usize) -> Board {
+        let mut board = self.start.clone();
+        for &mv in self.moves.iter().take(move_number) {
+            board.play(mv);
+        }
+        board
+    }
+
+    /// The winner according to the result, or `None` for a draw or an
+    /// unknown result.
+    pub fn get_winner(&self) -> CellState {
+        let result = self.result.trim().to_uppercase();
+        if result.starts_with("B+") {
+            CellState::Black
+        } else if result.starts_with("W+") {
+            CellState::White
+        } else {
+            CellState::None
+        }
+    }
+}
+
+/// Where a search found the position: the game (an index into the
+/// database), how many moves into it, and the transform that takes the
+/// searched board onto the game's.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GameRef {
+    pub game: usize,
+    pub move_number: usize,
+    pub transform: Transform,
+}
+
+/// How often a move followed the position in the games found, on the
+/// searched board, and who went on to win.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NextMove {
+    pub mv: Option<Point>,
+    pub games: u32,
+    pub black_wins: u32,
+    pub white_wins: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchResult {
+    /// The first time the position turns up in each game, in the order the
+    /// games were added.
+    pub games: Vec<GameRef>,
+    /// The moves played next, most played first.
+    pub next_moves: Vec<NextMove>,
+}
+
+/// A collection of game records that can be searched for positions. Every
+/// position of every game is indexed by its canonical hash (see
+/// [`Board::get_canonical_hash`]), so a whole board is found in any
+/// orientation with one lookup; searching part of the board replays the
+/// games instead. Whose turn it is isn't taken into account.
+///
+/// A saved database is text: a header line, then a line per game with its
+/// source, players, result, main line as a one-line SGF record and the
+/// hashes of its positions, separated by tabs.
+#[derive(Clone, Default)]
+pub struct GameDatabase {
+    games: Vec<GameRecord>,
+    // the games and move numbers each position turns up at, keyed by the
+    // board size and canonical hash
+    positions: HashMap<(usize, u64), Vec<(usize, usize)>>,
+}
+
+impl GameDatabase {
+    pub fn new() -> GameDatabase {
+        GameDatabase::default()
+    }
+
+    pub fn get_game_count(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn get_game(&self, index: usize) -> &GameRecord {
+        &self.games[index]
+    }
+
+    /// Adds every game in an SGF file's text, returning how many there
+    /// were. Each game's main line is kept up to the first move that
+    /// doesn't fit the position.
+    pub fn add_sgf(&mut self, source: &str, text: &str) -> Result<usize, String> {
+        let roots = parse_sgf(text)?;
+        for (i, root) in roots.iter().enumerate() {
+            let source = if roots.len() > 1 {
+                format!("{}#{}", source, i + 1)
+            } else {
+                source.to_string()
+            };
+            let record = read_game(source, root)?;
+            self.add_record(record);
+        }
+        Ok(roots.len())
+    }
+
+    /// Adds every `.sgf` file under `dir`, looking in subdirectories too.
+    /// Files that can't be read are skipped, and the problems with them
+    /// are returned.
+    pub fn add_dir(&mut self, dir: &Path) -> Result<Vec<String>, String> {
+        let mut paths = Vec::new();
+        find_sgf_files(dir, &mut paths)?;
+        paths.sort();
+        let mut problems = Vec::new();
+        for path in paths {
+            let source = path.display().to_string();
+            let added = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| self.add_sgf(&source, &text));
+            if let Err(e) = added {
+                problems.push(format!("{}: {}", source, e));
+            }
+        }
+        Ok(problems)
+    }
+
+    fn add_record(&mut self, record: GameRecord) {
+        let index = self.games.len();
+        let size = record.get_size();
+        for (move_number, &hash) in record.hashes.iter().enumerate() {
+            self.positions
+                .entry((size, hash))
+                .or_default()
+                .push((index, move_number));
+        }
+        self.games.push(record);
+    }
+
+    /// Finds the games where the whole of `board` came up, in any
+    /// orientation, and the moves played next. Moves that are equivalent
+    /// because the position is symmetric are counted together.
+    pub fn search_position(&self, board: &Board) -> SearchResult {
+        let size = board.get_size();
+        let (hash, transform) = board.get_canonical_hash(false);
+        let symmetries = board.get_symmetries();
+        let mut result = SearchResult::default();
+        let found = self.positions.get(&(size, hash)).map_or(&[][..], |f| f);
+        for &(game, move_number) in found {
+            if result.games.last().is_some_and(|r| r.game == game) {
+                continue;
+            }
+            let record = &self.games[game];
+            let (_, game_transform) = record.get_board(move_number).get_canonical_hash(false);
+            let to_game = transform.then(game_transform.inverse());
+            result.games.push(GameRef {
+                game,
+                move_number,
+                transform: to_game,
+            });
+
+            // the next move on the searched board, choosing the same one of
+            // any that are equivalent
+            let mv = record.moves.get(move_number).copied().flatten().map(|p| {
+                let p = to_game.inverse().apply_point(p, size);
+                symmetries
+                    .iter()
+                    .map(|s| s.apply(p, size))
+                    .min_by_key(|q| (q.y, q.x))
+                    .unwrap()
+            });
+            add_next_move(&mut result.next_moves, mv, record.get_winner());
+        }
+        sort_next_moves(&mut result.next_moves);
+        result
+    }
+
+    /// Finds the games where the points of `region` on `board`, empty or
+    /// not, came up in the same place up to the board's symmetries (so a
+    /// corner is found in every corner, but not shifted along the edge), and
+    /// the moves played next. A next move of `None` is a pass, a move outside
+    /// the region or the end of the game. The region isn't indexed, so this
+    /// replays every game of the board's size and compares the region under
+    /// all 8 symmetries after every move: it's far slower than
+    /// `search_position`.
+    pub fn search_region(&self, board: &Board, region: &[Point]) -> SearchResult {
+        let size = board.get_size();
+        let mut result = SearchResult::default();
+        for (game, record) in self.games.iter().enumerate() {
+            if record.get_size() != size {
+                continue;
+            }
+            let mut position = record.start.clone();
+            'positions: for move_number in 0..=record.moves.len() {
+                for &symmetry in Symmetry::ALL.iter() {
+                    let matches = region
+                        .iter()
+                        .all(|&p| position.get(symmetry.apply(p, size)) == board.get(p));
+                    if !matches {
+                        continue;
+                    }
+                    let transform = Transform::new(symmetry, false);
+                    result.games.push(GameRef {
+                        game,
+                        move_number,
+                        transform,
+                    });
+                    let mv = record.moves.get(move_number).copied().flatten();
+                    let mv = mv
+                        .map(|p| transform.inverse().apply_point(p, size))
+                        .filter(|p| region.contains(p));
+                    add_next_move(&mut result.next_moves, mv, record.get_winner());
+                    break 'positions;
+                }
+                if let Some(&mv) = record.moves.get(move_number) {
+                    position.play(mv);
+                }
+            }
+        }
+        sort_next_moves(&mut result.next_moves);
+        result
+    }
+
+    pub fn load(path: &Path) -> Result<GameDatabase, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        GameDatabase::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<GameDatabase, String> {
+        let mut lines = text.lines();
+        if lines.next().map(str::trim) != Some(DATABASE_HEADER) {
+            return Err("not a game database".to_string());
+        }
+        let mut database = GameDatabase::new();
+        for (number, line) in lines.enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let at = |e: String| format!("line {}: {}", number + 2, e);
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 6 {
+                return Err(at("invalid entry".to_string()));
+            }
+            let root = parse_sgf(fields[4]).map_err(at)?.remove(0);
+            let mut record = read_game(fields[0].to_string(), &root).map_err(at)?;
+            record.black = fields[1].to_string();
+            record.white = fields[2].to_string();
+            record.result = fields[3].to_string();
+            let hashes: Result<Vec<u64>, _> = fields[5]
+                .split_whitespace()
+                .map(|h| u64::from_str_radix(h, 16))
+                .collect();
+            match hashes {
+                Ok(hashes) if hashes.len() == record.moves.len() + 1 => record.hashes = hashes,
+                _ => return Err(at("invalid hashes".to_string())),
+            }
+            database.add_record(record);
+        }
+        Ok(database)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.write())
+            .map_err(|e| format!("couldn't write {}: {}", path.display(), e))
+    }
+
+    /// The database in its saved form.
+    pub fn write(&self) -> String {
+        let mut text = format!("{}\n", DATABASE_HEADER);
+        for record in &self.games {
+            let board = record.get_board(record.moves.len());
+            let hashes: Vec<String> = record.hashes.iter().map(|h| format!("{:x}", h)).collect();
+            let fields = [
+                clean(&record.source),
+                clean(&record.black),
+                clean(&record.white),
+                clean(&record.result),
+                write_game(&board).replace('\n', ""),
+                hashes.join(" "),
+            ];
+            text += &fields.join("\t");
+            text.push('\n');
+        }
+        text
+    }
+}
+
+// reads a game's main line, working out the hash of each position
+fn read_game(source: String, root: &SgfNode) -> Result<GameRecord, String> {
+    let start = root.get_setup_board()?;
+    let mut board = start.clone();
+    let mut moves = Vec::new();
+    let mut hashes = vec![board.get_canonical_hash(false).0];
+    let mut node = root;
+    loop {
+        if let Some((player, mv)) = node.get_move() {
+            if player != board.get_turn() || mv.is_some_and(|p| !board.can_place(p)) {
+                break;
+            }
+            board.play(mv);
+            moves.push(mv);
+            hashes.push(board.get_canonical_hash(false).0);
+        }
+        match node.children.first() {
+            Some(child) => node = child,
+            None => break,
+        }
+    }
+    let property = |id: &str| root.get(id).unwrap_or("").to_string();
+    Ok(GameRecord {
+        source,
+        black: property("PB"),
+        white: property("PW"),
+        result: property("RE"),
+        start,
+        moves,
+        hashes,
+    })
+}
+
+// the fields of a saved database are separated by tabs and lines
+fn clean(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+fn find_sgf_files(dir: &Path, paths: &mut Vec<std::path::PathBuf>) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("couldn't read {}: {}", dir.display(), e))?;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            find_sgf_files(&path, paths)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("sgf"))
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn add_next_move(next_moves: &mut Vec<NextMove>, mv: Option<Point>, winner: CellState) {
+    let index = match next_moves.iter().position(|n| n.mv == mv) {
+        Some(i) => i,
+        None => {
+            next_moves.push(NextMove {
+                mv,
+                games: 0,
+                black_wins: 0,
+                white_wins: 0,
+            });
+            next_moves.len() - 1
+        }
+    };
+    let next = &mut next_moves[index];
+    next.games += 1;
+    match winner {
+        CellState::Black => next.black_wins += 1,
+        CellState::White => next.white_wins += 1,
+        CellState::None => {}
+    }
+}
+
+fn sort_next_moves(next_moves: &mut [NextMove]) {
+    next_moves.sort_by_key(|n| std::cmp::Reverse(n.games));
+}
@@ -0,0 +1,40 @@
+//! Reading and writing games in Smart Game Format (SGF, FF\[4\]).
+
+mod database;
+mod problem;
+mod reader;
+mod review;
+mod writer;
+
+#[cfg(test)]
+mod tests;
+
+pub use database::*;
+pub use problem::*;
+pub use reader::*;
+pub use review::*;
+pub use writer::*;
+
+use crate::go_core::*;
+
+/// The SGF coordinate of `p`, e.g. `(3, 15)` is `dp`.
+pub fn point_to_sgf(p: Point) -> String {
+    let x = (b'a' + p.x as u8) as char;
+    let y = (b'a' + p.y as u8) as char;
+    format!("{}{}", x, y)
+}
+
+/// Parses an SGF coordinate, or returns `None` for a pass: an empty value
+/// or `tt`, which FF[3] used for passes on boards up to 19x19.
+pub fn sgf_to_point(s: &str) -> Option<Point> {
+    let bytes = s.trim().as_bytes();
+    if bytes.len() != 2 || bytes == b"tt" {
+        return None;
+    }
+    let coordinate = |c: u8| match c {
+        b'a'..=b'z' => Some((c - b'a') as i32),
+        b'A'..=b'Z' => Some((c - b'A') as i32 + 26),
+        _ => None,
+    };
+    Some(Point::new(coordinate(bytes[0])?, coordinate(bytes[1])?))
+}
@@ -0,0 +1,142 @@
+use crate::go_core::*;
+use crate::sgf::*;
+
+/// Where an attempt at a problem has got to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ProblemStatus {
+    Solving,
+    Solved,
+    Failed,
+}
+
+/// A problem from an SGF problem collection, being attempted. The root
+/// sets up the position and the variations are the answers: the player's
+/// moves alternate with the responses, and a line is correct when its last
+/// node's comment starts with `RIGHT` or `CORRECT`, or the node has a `TE`
+/// property or `GB`/`GW` for the player. Every other line, and every move
+/// not in the file, is wrong.
+#[derive(Clone)]
+pub struct Problem {
+    root: SgfNode,
+    start: Board,
+    board: Board,
+    // the variation taken at each node so far
+    path: Vec<usize>,
+    status: ProblemStatus,
+}
+
+impl Problem {
+    pub fn new(root: SgfNode) -> Result<Problem, String> {
+        let start = root.get_setup_board()?;
+        Ok(Problem {
+            root,
+            board: start.clone(),
+            start,
+            path: Vec::new(),
+            status: ProblemStatus::Solving,
+        })
+    }
+
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn get_status(&self) -> ProblemStatus {
+        self.status
+    }
+
+    /// The colour the problem is set for.
+    pub fn get_player(&self) -> CellState {
+        self.start.get_turn()
+    }
+
+    /// The comment on the position reached so far, if there is one.
+    pub fn get_comment(&self) -> Option<&str> {
+        self.get_node().get("C")
+    }
+
+    fn get_node(&self) -> &SgfNode {
+        let mut node = &self.root;
+        for &i in &self.path {
+            node = &node.children[i];
+        }
+        node
+    }
+
+    /// Plays the player's move, `None` being a pass, and the file's
+    /// response to it. The attempt fails if the move isn't one of the
+    /// file's answers or leads to a wrong line.
+    pub fn play(&mut self, mv: Option<Point>) -> Result<(), String> {
+        if self.status != ProblemStatus::Solving {
+            return Err("the problem is already over".to_string());
+        }
+        if let Some(p) = mv {
+            if !self.board.can_place(p) {
+                return Err("illegal move".to_string());
+            }
+        }
+        let player = self.board.get_turn();
+        let answer = self
+            .get_node()
+            .children
+            .iter()
+            .position(|child| child.get_move() == Some((player, mv)));
+        self.board.play(mv);
+        match answer {
+            Some(i) => {
+                self.path.push(i);
+                if !self.get_node().children.is_empty() {
+                    self.respond();
+                }
+                if self.get_node().children.is_empty() {
+                    self.status = if is_correct(self.get_node(), self.get_player()) {
+                        ProblemStatus::Solved
+                    } else {
+                        ProblemStatus::Failed
+                    };
+                }
+            }
+            None => self.status = ProblemStatus::Failed,
+        }
+        Ok(())
+    }
+
+    // plays the main line's reply, for either colour
+    fn respond(&mut self) {
+        self.path.push(0);
+        if let Some((player, mv)) = self.get_node().get_move() {
+            self.board.set_turn(player);
+            if mv.is_none_or(|p| self.board.can_place(p)) {
+                self.board.play(mv);
+            }
+        }
+    }
+
+    /// Puts the problem back to its starting position.
+    pub fn reset(&mut self) {
+        self.board = self.start.clone();
+        self.path.clear();
+        self.status = ProblemStatus::Solving;
+    }
+}
+
+// the usual ways collections mark the end of a correct line: a good move
+// (TE), a position good for the player (GB or GW), or a comment that starts
+// by saying so, since "not correct" and "the right idea, but..." don't
+fn is_correct(node: &SgfNode, player: CellState) -> bool {
+    let good_for = if player == CellState::White {
+        "GW"
+    } else {
+        "GB"
+    };
+    node.get("TE").is_some()
+        || node.get(good_for).is_some()
+        || node.get("C").is_some_and(|comment| {
+            let word = comment
+                .trim_start()
+                .split(|c: char| !c.is_ascii_alphabetic())
+                .next()
+                .unwrap_or("");
+            word.eq_ignore_ascii_case("right") || word.eq_ignore_ascii_case("correct")
+        })
+}
@@ -0,0 +1,286 @@
+use crate::go_core::*;
+use crate::sgf::*;
+
+/// The marks an SGF node draws on the board: circles (`CR`), crosses
+/// (`MA`), triangles (`TR`), squares (`SQ`) and text labels (`LB`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Markup {
+    pub circles: Vec<Point>,
+    pub crosses: Vec<Point>,
+    pub triangles: Vec<Point>,
+    pub squares: Vec<Point>,
+    pub labels: Vec<(Point, String)>,
+}
+
+/// A node of an SGF game tree: its properties in file order and the
+/// variations that follow it, the main line first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SgfNode {
+    pub properties: Vec<(String, Vec<String>)>,
+    pub children: Vec<SgfNode>,
+}
+
+impl SgfNode {
+    /// The first value of the property `id`, if the node has it.
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.get_all(id).first().map(|v| v.as_str())
+    }
+
+    /// Every value of the property `id`.
+    pub fn get_all(&self, id: &str) -> &[String] {
+        self.properties
+            .iter()
+            .find(|(name, _)| name == id)
+            .map(|(_, values)| values.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The points listed by the property `id`, expanding compressed
+    /// rectangles such as `aa:cc`.
+    pub fn get_points(&self, id: &str) -> Vec<Point> {
+        let mut points = Vec::new();
+        for value in self.get_all(id) {
+            let mut corners = value.split(':').filter_map(sgf_to_point);
+            match (corners.next(), corners.next()) {
+                (Some(a), Some(b)) => {
+                    for y in a.y.min(b.y)..=a.y.max(b.y) {
+                        for x in a.x.min(b.x)..=a.x.max(b.x) {
+                            points.push(Point::new(x, y));
+                        }
+                    }
+                }
+                (Some(a), None) => points.push(a),
+                _ => {}
+            }
+        }
+        points
+    }
+
+    /// The move played in this node, if any, with `None` for a pass.
+    pub fn get_move(&self) -> Option<(CellState, Option<Point>)> {
+        for (tag, player) in [("B", CellState::Black), ("W", CellState::White)].iter() {
+            if let Some(value) = self.get(tag) {
+                return Some((*player, sgf_to_point(value)));
+            }
+        }
+        None
+    }
+
+    /// The marks on this node's position. Labels are written `point:text`.
+    pub fn get_markup(&self) -> Markup {
+        let labels = self
+            .get_all("LB")
+            .iter()
+            .filter_map(|value| {
+                let (point, text) = value.split_once(':')?;
+                Some((sgf_to_point(point)?, text.to_string()))
+            })
+            .collect();
+        Markup {
+            circles: self.get_points("CR"),
+            crosses: self.get_points("MA"),
+            triangles: self.get_points("TR"),
+            squares: self.get_points("SQ"),
+            labels,
+        }
+    }
+
+    /// The position this root node sets up: its size (`SZ`, 19 by
+    /// default), rules (`RU`), komi (`KM`), handicap (`HA`), stones
+    /// (`AB`/`AW`) and the player to move. That is `PL` if the record has
+    /// it, or else the colour of the first move, White in a handicap game
+    /// and Black otherwise.
+    pub fn get_setup_board(&self) -> Result<Board, String> {
+        let size = match self.get("SZ") {
+            Some(size) => size
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid board size '{}'", size))?,
+            None => 19,
+        };
+        if !(2..=25).contains(&size) {
+            return Err(format!("unsupported board size {}", size));
+        }
+        let rules = self
+            .get("RU")
+            .and_then(RuleSet::from_name)
+            .unwrap_or_default();
+        let mut stones = Vec::new();
+        for (tag, player) in [("AB", CellState::Black), ("AW", CellState::White)].iter() {
+            for p in self.get_points(tag) {
+                stones.push((*player, p));
+            }
+        }
+        let handicap = self
+            .get("HA")
+            .and_then(|ha| ha.trim().parse().ok())
+            .filter(|&ha| (MIN_HANDICAP..=MAX_HANDICAP).contains(&ha))
+            .unwrap_or(0);
+        let first_move = self.children.first().and_then(SgfNode::get_move);
+        let turn = match self.get("PL").map(|pl| pl.trim().to_uppercase()) {
+            Some(ref pl) if pl == "W" || pl == "WHITE" => CellState::White,
+            Some(ref pl) if pl == "B" || pl == "BLACK" => CellState::Black,
+            _ => match first_move {
+                Some((player, _)) => player,
+                None if handicap > 0 && stones.iter().any(|s| s.0 == CellState::Black) => {
+                    CellState::White
+                }
+                None => CellState::Black,
+            },
+        };
+        let mut board = Board::from_setup(size, rules, &stones, turn)?;
+        board.set_handicap_count(handicap)?;
+        if let Some(komi) = self.get("KM").and_then(|km| km.trim().parse().ok()) {
+            board.set_komi(komi);
+        }
+        Ok(board)
+    }
+}
+
+/// Parses an SGF file into its game trees, one root node each. Moves that
+/// follow each other in a sequence become a chain of single children.
+pub fn parse_sgf(text: &str) -> Result<Vec<SgfNode>, String> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+    };
+    let mut trees = Vec::new();
+    loop {
+        parser.skip_whitespace();
+        match parser.peek() {
+            Some('(') => trees.push(parser.parse_tree()?),
+            None => break,
+            // anything between the game trees is ignored
+            Some(_) => parser.pos += 1,
+        }
+    }
+    if trees.is_empty() {
+        return Err("no game trees found".to_string());
+    }
+    Ok(trees)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at character {}", c, self.pos))
+        }
+    }
+
+    // ( sequence of nodes, then the variations )
+    fn parse_tree(&mut self) -> Result<SgfNode, String> {
+        self.expect('(')?;
+        let mut sequence = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(';') {
+                break;
+            }
+            self.pos += 1;
+            sequence.push(self.parse_node()?);
+        }
+        if sequence.is_empty() {
+            return Err(format!("empty game tree at character {}", self.pos));
+        }
+        let mut variations = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('(') => variations.push(self.parse_tree()?),
+                Some(')') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err("unterminated game tree".to_string()),
+            }
+        }
+
+        let mut node = sequence.pop().unwrap();
+        node.children = variations;
+        while let Some(mut parent) = sequence.pop() {
+            parent.children = vec![node];
+            node = parent;
+        }
+        Ok(node)
+    }
+
+    fn parse_node(&mut self) -> Result<SgfNode, String> {
+        let mut node = SgfNode::default();
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                self.pos += 1;
+            }
+            if self.pos == start {
+                return Ok(node);
+            }
+            // FF[3] allowed lower case letters in identifiers, which are
+            // ignored
+            let id: String = self.chars[start..self.pos]
+                .iter()
+                .filter(|c| c.is_ascii_uppercase())
+                .collect();
+            let mut values = Vec::new();
+            loop {
+                self.skip_whitespace();
+                if self.peek() != Some('[') {
+                    break;
+                }
+                self.pos += 1;
+                values.push(self.parse_value()?);
+            }
+            if values.is_empty() {
+                return Err(format!("property {} has no value", id));
+            }
+            node.properties.push((id, values));
+        }
+    }
+
+    // the text up to the closing bracket, with escapes removed and escaped
+    // line breaks joined up
+    fn parse_value(&mut self) -> Result<String, String> {
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None => return Err("unterminated property value".to_string()),
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('\n') | Some('\r') => {}
+                        Some(c) => value.push(c),
+                        None => {}
+                    }
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::go_core::*;
+use crate::sgf::*;
+
+/// A game record being stepped through, from the first game tree of an SGF
+/// file. The position is the one reached by following the chosen variation
+/// at each node from the root, which sets up the board as a problem's root
+/// does. Moves played on the board that aren't in the record are added to
+/// it as new variations.
+#[derive(Clone)]
+pub struct Review {
+    root: SgfNode,
+    start: Board,
+    board: Board,
+    // the variation taken at each node so far
+    path: Vec<usize>,
+    file: Option<PathBuf>,
+}
+
+impl Review {
+    pub fn new(root: SgfNode) -> Result<Review, String> {
+        let start = root.get_setup_board()?;
+        Ok(Review {
+            root,
+            board: start.clone(),
+            start,
+            path: Vec::new(),
+            file: None,
+        })
+    }
+
+    /// Opens the first game in the SGF file at `path`, which
+    /// [`Review::save`] writes back to.
+    pub fn load(path: &Path) -> Result<Review, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        let mut review = Review::new(parse_sgf(&text)?.remove(0))?;
+        review.file = Some(path.to_path_buf());
+        Ok(review)
+    }
+
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The record, with any variations that have been added.
+    pub fn get_root(&self) -> &SgfNode {
+        &self.root
+    }
+
+    /// How many nodes into the record the position is, 0 being the root.
+    pub fn get_depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// The current node's place among its siblings and how many there are,
+    /// counting from 0 with the main line first.
+    pub fn get_variation(&self) -> (usize, usize) {
+        match self.path.split_last() {
+            Some((&last, rest)) => (last, self.get_node_at(rest).children.len()),
+            None => (0, 1),
+        }
+    }
+
+    /// The comment on the current node, if there is one.
+    pub fn get_comment(&self) -> Option<&str> {
+        self.get_node().get("C")
+    }
+
+    pub fn get_markup(&self) -> Markup {
+        self.get_node().get_markup()
+    }
+
+    /// The moves that follow the current node, one per variation.
+    pub fn get_next_moves(&self) -> Vec<(CellState, Option<Point>)> {
+        self.get_node()
+            .children
+            .iter()
+            .filter_map(|child| child.get_move())
+            .collect()
+    }
+
+    fn get_node(&self) -> &SgfNode {
+        self.get_node_at(&self.path)
+    }
+
+    fn get_node_at(&self, path: &[usize]) -> &SgfNode {
+        let mut node = &self.root;
+        for &i in path {
+            node = &node.children[i];
+        }
+        node
+    }
+
+    fn get_node_mut(&mut self) -> &mut SgfNode {
+        let mut node = &mut self.root;
+        for &i in &self.path {
+            node = &mut node.children[i];
+        }
+        node
+    }
+
+    /// Steps into the current node's first variation, returning false at
+    /// the end of the line.
+    pub fn forward(&mut self) -> bool {
+        if self.get_node().children.is_empty() {
+            return false;
+        }
+        self.path.push(0);
+        self.apply_move();
+        true
+    }
+
+    /// Steps back a node, returning false at the root.
+    pub fn back(&mut self) -> bool {
+        if self.path.pop().is_none() {
+            return false;
+        }
+        self.replay();
+        true
+    }
+
+    pub fn go_to_start(&mut self) {
+        self.path.clear();
+        self.board = self.start.clone();
+    }
+
+    /// Follows the first variation from the current node to the end.
+    pub fn go_to_end(&mut self) {
+        while self.forward() {}
+    }
+
+    /// Switches to the next of the current node's siblings, wrapping around.
+    pub fn next_variation(&mut self) {
+        let (index, count) = self.get_variation();
+        self.switch_variation((index + 1) % count);
+    }
+
+    /// Switches to the previous of the current node's siblings, wrapping
+    /// around.
+    pub fn previous_variation(&mut self) {
+        let (index, count) = self.get_variation();
+        self.switch_variation((index + count - 1) % count);
+    }
+
+    fn switch_variation(&mut self, index: usize) {
+        if let Some(last) = self.path.last_mut() {
+            if *last != index {
+                *last = index;
+                self.replay();
+            }
+        }
+    }
+
+    /// Plays `mv` for the player to move, `None` being a pass, following
+    /// the record if it already has the move here and adding it as a new
+    /// variation if not.
+    pub fn play(&mut self, mv: Option<Point>) -> Result<(), String> {
+        if mv.is_some_and(|p| !self.board.can_place(p)) {
+            return Err("illegal move".to_string());
+        }
+        let player = self.board.get_turn();
+        let existing = self
+            .get_node()
+            .children
+            .iter()
+            .position(|child| child.get_move() == Some((player, mv)));
+        let index = match existing {
+            Some(i) => i,
+            None => {
+                let tag = if player == CellState::Black { "B" } else { "W" };
+                let value = mv.map(point_to_sgf).unwrap_or_default();
+                let children = &mut self.get_node_mut().children;
+                children.push(SgfNode {
+                    properties: vec![(tag.to_string(), vec![value])],
+                    children: Vec::new(),
+                });
+                children.len() - 1
+            }
+        };
+        self.path.push(index);
+        self.board.play(mv);
+        Ok(())
+    }
+
+    /// Writes the record, with any variations that have been added, back to
+    /// the file it was opened from.
+    pub fn save(&self) -> Result<(), String> {
+        let path = self
+            .file
+            .as_ref()
+            .ok_or_else(|| "the record wasn't opened from a file".to_string())?;
+        fs::write(path, write_tree(&self.root))
+            .map_err(|e| format!("couldn't write {}: {}", path.display(), e))
+    }
+
+    // sets the board up again from the root along the path
+    fn replay(&mut self) {
+        let depth = self.path.len();
+        self.board = self.start.clone();
+        for i in 1..=depth {
+            let node = self.get_node_at(&self.path[..i]);
+            if let Some(mv) = node.get_move() {
+                play_for(&mut self.board, mv);
+            }
+        }
+    }
+
+    fn apply_move(&mut self) {
+        if let Some(mv) = self.get_node().get_move() {
+            play_for(&mut self.board, mv);
+        }
+    }
+}
+
+// plays a move from the record, whoever's turn it is on the board, leaving
+// out moves the board won't take
+fn play_for(board: &mut Board, (player, mv): (CellState, Option<Point>)) {
+    board.set_turn(player);
+    if mv.is_none_or(|p| board.can_place(p)) {
+        board.play(mv);
+    }
+}
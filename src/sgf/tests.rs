@@ -0,0 +1,313 @@
+use crate::go_core::*;
+use crate::sgf::*;
+
+#[test]
+fn test_write_game() {
+    let mut b = Board::new(9, RuleSet::japanese());
+    b.place(Point::new(2, 2));
+    b.place(Point::new(6, 6));
+
+    assert_eq!(
+        write_game(&b),
+        "(;GM[1]FF[4]CA[UTF-8]SZ[9]RU[Japanese]KM[6.5]\n;B[cc]\n;W[gg])\n"
+    );
+}
+
+#[test]
+fn test_write_handicap_game() {
+    let mut b = Board::new(19, RuleSet::chinese());
+    b.set_handicap(3, HandicapPlacement::Fixed).unwrap();
+    b.place(Point::new(16, 16));
+
+    assert_eq!(
+        write_game(&b),
+        "(;GM[1]FF[4]CA[UTF-8]SZ[19]RU[Chinese]KM[7.5]HA[3]AB[pd][dp][pp]\n;W[qq])\n"
+    );
+}
+
+#[test]
+fn test_read_game() {
+    let mut b = Board::new(9, RuleSet::japanese());
+    b.place(Point::new(2, 2));
+    b.pass();
+
+    let trees = parse_sgf(&write_game(&b)).unwrap();
+    assert_eq!(trees.len(), 1);
+    let root = &trees[0];
+    assert_eq!(root.get("SZ"), Some("9"));
+    assert_eq!(root.get("RU"), Some("Japanese"));
+    let first = &root.children[0];
+    assert_eq!(
+        first.get_move(),
+        Some((CellState::Black, Some(Point::new(2, 2))))
+    );
+    assert_eq!(first.children[0].get_move(), Some((CellState::White, None)));
+    assert!(first.children[0].children.is_empty());
+}
+
+#[test]
+fn test_read_handicap_game() {
+    let mut b = Board::new(19, RuleSet::aga());
+    b.set_handicap(2, HandicapPlacement::Fixed).unwrap();
+    b.place(Point::new(10, 10));
+    b.place(Point::new(5, 5));
+
+    // White plays first after the handicap stones, without a PL to say so
+    let text = write_game(&b);
+    let root = parse_sgf(&text).unwrap().remove(0);
+    assert_eq!(root.get("PL"), None);
+    let start = root.get_setup_board().unwrap();
+    assert_eq!(start.get_turn(), CellState::White);
+    assert_eq!(start.get_handicap(), 2);
+    assert_eq!(start.get_setup_stones(), b.get_setup_stones());
+
+    // so the moves replay as they were played, without passes in between
+    let mut review = Review::new(root).unwrap();
+    review.go_to_end();
+    let board = review.get_board();
+    assert_eq!(board.get_moves(), b.get_moves());
+    assert_eq!(board.get_captured_stones(CellState::Black), 0);
+    assert_eq!(write_game(board), text);
+
+    let mut database = GameDatabase::new();
+    database.add_sgf("handicap", &text).unwrap();
+    assert_eq!(database.get_game(0).get_moves().len(), 2);
+}
+
+#[test]
+fn test_read_variations() {
+    let text = "(;FF[4]SZ[5]PL[W]AB[aa:bb]AW[cc]C[a \\] bracket]
+        (;W[dd]C[main];B[ee])
+        (;W[ee]))
+        (;SZ[9])";
+    let trees = parse_sgf(text).unwrap();
+    assert_eq!(trees.len(), 2);
+    let root = &trees[0];
+    assert_eq!(root.get("C"), Some("a ] bracket"));
+    assert_eq!(root.get_points("AB").len(), 4);
+    assert_eq!(root.children.len(), 2);
+    assert_eq!(root.children[0].get("C"), Some("main"));
+    assert_eq!(root.children[0].children.len(), 1);
+
+    let board = root.get_setup_board().unwrap();
+    assert_eq!(board.get_size(), 5);
+    assert_eq!(board.get_turn(), CellState::White);
+    assert_eq!(board.get(Point::new(1, 1)), CellState::Black);
+    assert_eq!(board.get(Point::new(2, 2)), CellState::White);
+    assert!(board.get_moves().is_empty());
+
+    assert!(parse_sgf("(;B[aa]").is_err());
+    assert!(parse_sgf("no games here").is_err());
+}
+
+#[test]
+fn test_problem() {
+    let text = "(;SZ[5]AB[ba][bb]AW[ca][cb]PL[B]
+        (;B[cc];W[dd];B[ee]C[RIGHT])
+        (;B[dd];W[cc]C[that's incorrect])
+        (;B[ee]C[Correct!])
+        (;B[ab]C[not correct])
+        (;B[ad]C[The right idea, but too slow])
+        (;B[ea]GB[1]))";
+    let mut problem = Problem::new(parse_sgf(text).unwrap().remove(0)).unwrap();
+    assert_eq!(problem.get_player(), CellState::Black);
+
+    // the file answers the first move
+    problem.play(Some(Point::new(2, 2))).unwrap();
+    assert_eq!(problem.get_board().get(Point::new(3, 3)), CellState::White);
+    assert_eq!(problem.get_status(), ProblemStatus::Solving);
+    problem.play(Some(Point::new(4, 4))).unwrap();
+    assert_eq!(problem.get_status(), ProblemStatus::Solved);
+    assert_eq!(problem.get_comment(), Some("RIGHT"));
+    assert!(problem.play(Some(Point::new(0, 4))).is_err());
+
+    // a wrong line ends with the refutation
+    problem.reset();
+    assert_eq!(problem.get_board().get(Point::new(2, 2)), CellState::None);
+    problem.play(Some(Point::new(3, 3))).unwrap();
+    assert_eq!(problem.get_board().get(Point::new(2, 2)), CellState::White);
+    assert_eq!(problem.get_status(), ProblemStatus::Failed);
+
+    // a move the file doesn't know is wrong
+    problem.reset();
+    problem.play(Some(Point::new(0, 4))).unwrap();
+    assert_eq!(problem.get_status(), ProblemStatus::Failed);
+
+    problem.reset();
+    problem.play(Some(Point::new(4, 4))).unwrap();
+    assert_eq!(problem.get_status(), ProblemStatus::Solved);
+
+    // a comment only marks the line correct when it starts by saying so
+    for (mv, status) in &[
+        (Point::new(0, 1), ProblemStatus::Failed),
+        (Point::new(0, 3), ProblemStatus::Failed),
+        (Point::new(4, 0), ProblemStatus::Solved),
+    ] {
+        problem.reset();
+        problem.play(Some(*mv)).unwrap();
+        assert_eq!(problem.get_status(), *status);
+    }
+}
+
+#[test]
+fn test_database() {
+    // the second game is the first turned around until Black's fifth move
+    let mut database = GameDatabase::new();
+    let first = "(;SZ[9]PB[Alice]PW[Bob]RE[B+2.5];B[cc];W[gg];B[cg];W[gc];B[ee])";
+    let second = "(;SZ[9]RE[W+R];B[gg];W[cc];B[gc];W[cg];B[dd])";
+    let third = "(;SZ[13];B[cc])(;SZ[9];B[ee];W[cc])";
+    assert_eq!(database.add_sgf("first.sgf", first).unwrap(), 1);
+    assert_eq!(database.add_sgf("second.sgf", second).unwrap(), 1);
+    assert_eq!(database.add_sgf("third.sgf", third).unwrap(), 2);
+    assert_eq!(database.get_game_count(), 4);
+    assert_eq!(database.get_game(0).black, "Alice");
+    assert_eq!(database.get_game(0).get_winner(), CellState::Black);
+    assert_eq!(database.get_game(3).source, "third.sgf#2");
+
+    let search = |database: &GameDatabase| {
+        let mut board = Board::new(9, RuleSet::japanese());
+        for &(x, y) in [(2, 2), (6, 6), (2, 6), (6, 2)].iter() {
+            board.place(Point::new(x, y));
+        }
+        database.search_position(&board)
+    };
+    let result = search(&database);
+    let games: Vec<(usize, usize)> = result
+        .games
+        .iter()
+        .map(|r| (r.game, r.move_number))
+        .collect();
+    assert_eq!(games, vec![(0, 4), (1, 4)]);
+    assert_eq!(result.games[1].transform.symmetry, Symmetry::Rotate180);
+    // the position is symmetric top to bottom, so the second game's move is
+    // counted at the top
+    let next: Vec<(Option<Point>, u32, u32)> = result
+        .next_moves
+        .iter()
+        .map(|n| (n.mv, n.black_wins, n.white_wins))
+        .collect();
+    assert_eq!(
+        next,
+        vec![
+            (Some(Point::new(4, 4)), 1, 0),
+            (Some(Point::new(5, 3)), 0, 1)
+        ]
+    );
+
+    // every 9x9 game starts from the empty board
+    let result = database.search_position(&Board::new(9, RuleSet::japanese()));
+    assert_eq!(result.games.len(), 3);
+    assert_eq!(result.next_moves[0].mv, Some(Point::new(2, 2)));
+    assert_eq!(result.next_moves[0].games, 2);
+
+    // a black stone on the 3-3 point with nothing around it
+    let mut board = Board::new(9, RuleSet::japanese());
+    board.place(Point::new(2, 2));
+    let mut region = Vec::new();
+    for y in 0..4 {
+        for x in 0..4 {
+            region.push(Point::new(x, y));
+        }
+    }
+    let result = database.search_region(&board, &region);
+    let games: Vec<(usize, usize)> = result
+        .games
+        .iter()
+        .map(|r| (r.game, r.move_number))
+        .collect();
+    assert_eq!(games, vec![(0, 1), (1, 1)]);
+    assert_eq!(
+        result.games[1].transform.apply_point(Point::new(2, 2), 9),
+        Point::new(6, 6)
+    );
+    // both games answered away from the corner
+    assert_eq!(result.next_moves.len(), 1);
+    assert_eq!(result.next_moves[0].mv, None);
+    // the same pattern a line further from the edge is another position
+    let mut board = Board::new(9, RuleSet::japanese());
+    board.place(Point::new(2, 3));
+    let region: Vec<Point> = region.iter().map(|p| Point::new(p.x, p.y + 1)).collect();
+    assert!(database.search_region(&board, &region).games.is_empty());
+
+    let saved = GameDatabase::parse(&database.write()).unwrap();
+    assert_eq!(saved.write(), database.write());
+    assert_eq!(search(&saved), search(&database));
+    assert!(GameDatabase::parse("first.sgf").is_err());
+
+    // a game White starts keeps its first player through a save
+    let mut database = GameDatabase::new();
+    database
+        .add_sgf("white.sgf", "(;SZ[9]PL[W];W[ee];B[cc];W[gg])")
+        .unwrap();
+    database.add_sgf("empty.sgf", "(;SZ[9]PL[W])").unwrap();
+    let text = database.write();
+    assert_eq!(text.matches("PL[W]").count(), 2);
+    let saved = GameDatabase::parse(&text).unwrap();
+    assert_eq!(saved.write(), text);
+    assert_eq!(saved.get_game(0).get_moves().len(), 3);
+    let board = saved.get_game(1).get_board(0);
+    assert_eq!(board.get_turn(), CellState::White);
+}
+
+#[test]
+fn test_review() {
+    let text = "(;SZ[5]C[start]
+        ;B[cc]TR[bb][dd]SQ[bd]CR[ab]MA[ba][ca]LB[ee:A][ae:x]
+        (;W[dc]C[main line];B[dd])
+        (;W[cd]C[the other way]))";
+    let mut review = Review::new(parse_sgf(text).unwrap().remove(0)).unwrap();
+    assert_eq!(review.get_comment(), Some("start"));
+    assert!(!review.back());
+
+    assert!(review.forward());
+    assert_eq!(review.get_board().get(Point::new(2, 2)), CellState::Black);
+    let markup = review.get_markup();
+    assert_eq!(markup.triangles, vec![Point::new(1, 1), Point::new(3, 3)]);
+    assert_eq!(markup.squares, vec![Point::new(1, 3)]);
+    assert_eq!(markup.circles, vec![Point::new(0, 1)]);
+    assert_eq!(markup.crosses, vec![Point::new(1, 0), Point::new(2, 0)]);
+    assert_eq!(
+        markup.labels,
+        vec![
+            (Point::new(4, 4), "A".to_string()),
+            (Point::new(0, 4), "x".to_string())
+        ]
+    );
+    assert_eq!(review.get_next_moves().len(), 2);
+
+    review.go_to_end();
+    assert_eq!(review.get_depth(), 3);
+    assert_eq!(review.get_board().get(Point::new(3, 3)), CellState::Black);
+
+    // switching variations replays the position
+    review.back();
+    assert_eq!(review.get_variation(), (0, 2));
+    review.next_variation();
+    assert_eq!(review.get_variation(), (1, 2));
+    assert_eq!(review.get_comment(), Some("the other way"));
+    assert_eq!(review.get_board().get(Point::new(3, 2)), CellState::None);
+    assert_eq!(review.get_board().get(Point::new(2, 3)), CellState::White);
+    review.next_variation();
+    assert_eq!(review.get_variation(), (0, 2));
+
+    // a move the record has follows it, and a new one adds a variation
+    review.back();
+    review.play(Some(Point::new(2, 3))).unwrap();
+    assert_eq!(review.get_variation(), (1, 2));
+    review.back();
+    review.play(Some(Point::new(0, 0))).unwrap();
+    assert_eq!(review.get_variation(), (2, 3));
+    assert!(review.play(Some(Point::new(0, 0))).is_err());
+    review.go_to_start();
+    assert_eq!(review.get_board().get(Point::new(2, 2)), CellState::None);
+
+    // the added variation is written out with the rest of the tree
+    let trees = parse_sgf(&write_tree(review.get_root())).unwrap();
+    let mut reread = Review::new(trees[0].clone()).unwrap();
+    reread.forward();
+    assert_eq!(reread.get_markup(), markup);
+    assert_eq!(
+        reread.get_next_moves()[2],
+        (CellState::White, Some(Point::new(0, 0)))
+    );
+}
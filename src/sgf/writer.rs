@@ -0,0 +1,92 @@
+use crate::go_core::*;
+use crate::sgf::*;
+
+/// Writes the game on `board` as an SGF record: the board size, rules,
+/// komi, handicap (`HA`) and setup stones (`AB`/`AW`) followed by every move.
+pub fn write_game(board: &Board) -> String {
+    write_game_with_properties(board, &[])
+}
+
+/// Like [`write_game`], with extra root properties such as the players
+/// (`PB`/`PW`) or the result (`RE`).
+pub fn write_game_with_properties(board: &Board, properties: &[(&str, &str)]) -> String {
+    let mut sgf = format!(
+        "(;GM[1]FF[4]CA[UTF-8]SZ[{}]RU[{}]KM[{}]",
+        board.get_size(),
+        board.get_rules().name,
+        board.get_komi()
+    );
+    for (id, value) in properties {
+        sgf.push_str(&format!("{}[{}]", id, escape(value)));
+    }
+    if board.get_handicap() > 0 {
+        sgf.push_str(&format!("HA[{}]", board.get_handicap()));
+    }
+    // readers start with Black unless the game is a handicap game
+    let first = board
+        .get_moves()
+        .first()
+        .map_or(board.get_turn(), |&(player, _)| player);
+    if first == CellState::White && board.get_handicap() == 0 {
+        sgf.push_str("PL[W]");
+    }
+    for (color, tag) in [(CellState::Black, "AB"), (CellState::White, "AW")].iter() {
+        let points: Vec<String> = board
+            .get_setup_stones()
+            .iter()
+            .filter(|(c, _)| c == color)
+            .map(|(_, p)| format!("[{}]", point_to_sgf(*p)))
+            .collect();
+        if !points.is_empty() {
+            sgf.push_str(tag);
+            sgf.push_str(&points.concat());
+        }
+    }
+    for (color, p) in board.get_moves() {
+        let tag = if *color == CellState::Black { "B" } else { "W" };
+        let p = p.map(point_to_sgf).unwrap_or_default();
+        sgf.push_str(&format!("\n;{}[{}]", tag, p));
+    }
+    sgf.push_str(")\n");
+    sgf
+}
+
+/// Writes a game tree back out as SGF, every node with its properties in
+/// order and the variations after them, the main line first.
+pub fn write_tree(root: &SgfNode) -> String {
+    let mut sgf = String::new();
+    write_sequence(root, &mut sgf);
+    sgf.push('\n');
+    sgf
+}
+
+// a node and the single children after it on a line each, then any
+// variations in brackets of their own
+fn write_sequence(node: &SgfNode, sgf: &mut String) {
+    sgf.push('(');
+    let mut node = node;
+    loop {
+        sgf.push(';');
+        for (id, values) in &node.properties {
+            sgf.push_str(id);
+            for value in values {
+                sgf.push_str(&format!("[{}]", escape(value)));
+            }
+        }
+        if node.children.len() != 1 {
+            break;
+        }
+        sgf.push('\n');
+        node = &node.children[0];
+    }
+    for child in &node.children {
+        sgf.push('\n');
+        write_sequence(child, sgf);
+    }
+    sgf.push(')');
+}
+
+// a backslash stops `]` or `\` in a value from ending or escaping it
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(']', "\\]")
+}